@@ -1,5 +1,6 @@
 use {
     crate::jsonrpc::JsonRpcError,
+    moved::{Error, MoveAbort, UserError},
     serde::de::DeserializeOwned,
     std::{any, fmt},
 };
@@ -34,3 +35,30 @@ pub fn access_state_error<E: fmt::Debug>(e: E) -> JsonRpcError {
 pub fn transaction_error<E: fmt::Debug>(e: E) -> JsonRpcError {
     JsonRpcError::without_data(3, format!("Execution reverted: {e:?}"))
 }
+
+/// Describes the Move `abort` in the error data so that clients do not have to parse the message.
+pub fn move_abort_error(abort: MoveAbort) -> JsonRpcError {
+    JsonRpcError {
+        code: 3,
+        message: format!("Execution reverted: {abort}"),
+        data: serde_json::json!({
+            "module": abort.module.short_str_lossless(),
+            "function": abort.function.as_ref().map(ToString::to_string),
+            "code": abort.code,
+            "category": abort.category(),
+            "reason": abort.reason,
+        }),
+    }
+}
+
+/// Maps the error of executing a transaction at `block_number`, keeping apart the case where the
+/// state at that height is unavailable.
+pub fn execution_error<T: fmt::Display>(e: Error, block_number: T) -> JsonRpcError {
+    match e {
+        Error::User(UserError::StateUnavailable(e)) => {
+            JsonRpcError::state_unavailable(e, block_number)
+        }
+        Error::User(UserError::MoveAbort(abort)) => move_abort_error(abort),
+        e => transaction_error(e),
+    }
+}
@@ -1,4 +1,5 @@
 use {
+    moved::state_actor::StateUnavailable,
     std::fmt,
     tokio::sync::{mpsc::error::SendError, oneshot::error::RecvError},
 };
@@ -34,6 +35,22 @@ impl JsonRpcError {
     pub fn block_not_found<T: fmt::Display>(block_number: T) -> Self {
         JsonRpcError::without_data(-32001, format!("Block not found: {block_number}"))
     }
+
+    pub fn server_busy() -> Self {
+        Self::without_data(-32000, "Server busy")
+    }
+
+    pub fn request_timeout() -> Self {
+        Self::without_data(-32000, "Request timed out")
+    }
+
+    pub fn state_unavailable<T: fmt::Display>(e: StateUnavailable, block_number: T) -> Self {
+        match e {
+            StateUnavailable::BlockNotFound => Self::block_not_found(block_number),
+            StateUnavailable::Pruned(_) => Self::without_data(-32000, e.to_string()),
+            StateUnavailable::ReadFailed(_) => Self::without_data(-32603, e.to_string()),
+        }
+    }
 }
 
 #[derive(Debug, serde::Serialize)]
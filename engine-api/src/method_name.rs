@@ -1,6 +1,6 @@
 use {crate::jsonrpc::JsonRpcError, std::str::FromStr};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum MethodName {
     ForkChoiceUpdatedV2,
     GetPayloadV2,
@@ -10,6 +10,7 @@ pub enum MethodName {
     NewPayloadV3,
     SendRawTransaction,
     ChainId,
+    GetAccount,
     GetBalance,
     GetBlockByHash,
     GetBlockByNumber,
@@ -19,7 +20,54 @@ pub enum MethodName {
     EstimateGas,
     Call,
     TransactionReceipt,
+    TransactionByHash,
+    RawTransactionByHash,
+    RawTransactionByBlockHashAndIndex,
     GetProof,
+    NetVersion,
+    NetListening,
+    NetPeerCount,
+    ClientVersion,
+    ProtocolVersion,
+    GasPrice,
+    DepositStatus,
+    NodeInfo,
+    GetBlockRoots,
+    ResourceExists,
+    TransactionsBySender,
+    GetL1FeeParams,
+    GetRawBlock,
+    GetRawHeader,
+    TraceTransaction,
+}
+
+impl MethodName {
+    /// Methods serving EVM state or EVM execution only, which are unavailable on Move-only
+    /// chains.
+    ///
+    /// `eth_call` and `eth_estimateGas` stay available as they also execute Move entry
+    /// functions and scripts, EVM calldata is rejected by the execution itself.
+    pub const EVM_SPECIFIC: [Self; 2] = [Self::GetProof, Self::TraceTransaction];
+
+    /// Methods meant for protocol debugging, which are only served when enabled.
+    pub const DEBUG: [Self; 3] = [
+        Self::GetRawBlock,
+        Self::GetRawHeader,
+        Self::TraceTransaction,
+    ];
+
+    /// Whether the method executes transactions or walks the state, so that it is expensive
+    /// enough to be limited by [`crate::request::HeavyReadLimit`].
+    pub fn is_heavy_read(&self) -> bool {
+        matches!(
+            self,
+            Self::Call
+                | Self::EstimateGas
+                | Self::GetProof
+                | Self::FeeHistory
+                | Self::TraceTransaction
+        )
+    }
 }
 
 impl FromStr for MethodName {
@@ -34,17 +82,36 @@ impl FromStr for MethodName {
             "engine_newPayloadV2" => Self::NewPayloadV2,
             "engine_newPayloadV3" => Self::NewPayloadV3,
             "eth_chainId" => Self::ChainId,
+            "eth_protocolVersion" => Self::ProtocolVersion,
+            "eth_getAccount" => Self::GetAccount,
             "eth_getBalance" => Self::GetBalance,
             "eth_getTransactionCount" => Self::GetNonce,
             "eth_getBlockByHash" => Self::GetBlockByHash,
             "eth_getBlockByNumber" => Self::GetBlockByNumber,
             "eth_feeHistory" => Self::FeeHistory,
+            "eth_gasPrice" => Self::GasPrice,
             "eth_blockNumber" => Self::BlockNumber,
             "eth_sendRawTransaction" => Self::SendRawTransaction,
             "eth_estimateGas" => Self::EstimateGas,
             "eth_call" => Self::Call,
             "eth_getTransactionReceipt" => Self::TransactionReceipt,
+            "eth_getTransactionByHash" => Self::TransactionByHash,
+            "eth_getRawTransactionByHash" => Self::RawTransactionByHash,
+            "eth_getRawTransactionByBlockHashAndIndex" => Self::RawTransactionByBlockHashAndIndex,
             "eth_getProof" => Self::GetProof,
+            "net_version" => Self::NetVersion,
+            "net_listening" => Self::NetListening,
+            "net_peerCount" => Self::NetPeerCount,
+            "web3_clientVersion" => Self::ClientVersion,
+            "moved_depositStatus" => Self::DepositStatus,
+            "moved_nodeInfo" => Self::NodeInfo,
+            "moved_getBlockRoots" => Self::GetBlockRoots,
+            "moved_resourceExists" => Self::ResourceExists,
+            "moved_getTransactionsBySender" => Self::TransactionsBySender,
+            "moved_getL1FeeParams" => Self::GetL1FeeParams,
+            "debug_getRawBlock" => Self::GetRawBlock,
+            "debug_getRawHeader" => Self::GetRawHeader,
+            "debug_traceTransaction" => Self::TraceTransaction,
             other => {
                 return Err(JsonRpcError::without_data(
                     -32601,
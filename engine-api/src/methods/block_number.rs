@@ -41,7 +41,11 @@ async fn inner_execute(state_channel: mpsc::Sender<StateMessage>) -> Result<u64,
 
 #[cfg(test)]
 mod tests {
-    use {super::*, crate::methods::tests::create_state_actor};
+    use {
+        super::*,
+        crate::methods::{get_block_by_number, tests::create_state_actor},
+        moved::types::state::Command,
+    };
 
     #[tokio::test]
     async fn test_execute() {
@@ -61,4 +65,51 @@ mod tests {
         assert_eq!(response, expected_response);
         state_handle.await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_block_number_is_readable_during_concurrent_builds() {
+        const BLOCKS: u64 = 20;
+        let (state_actor, state_channel) = create_state_actor();
+        let state_handle = state_actor.spawn();
+
+        let builder_channel = state_channel.clone();
+        let builder = async move {
+            for _ in 0..BLOCKS {
+                let msg = Command::StartBlockBuild {
+                    payload_attributes: Default::default(),
+                    response_channel: oneshot::channel().0,
+                }
+                .into();
+                builder_channel.send(msg).await.unwrap();
+                tokio::task::yield_now().await;
+            }
+        };
+        let reader = async {
+            let mut last_height = 0;
+            while last_height < BLOCKS {
+                let height = inner_execute(state_channel.clone()).await.unwrap();
+                assert!(height >= last_height, "Block number must not go back");
+                let request = serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "method": "eth_getBlockByNumber",
+                    "params": [format!("0x{height:x}"), false],
+                    "id": 1
+                });
+                let block = get_block_by_number::execute(request, state_channel.clone())
+                    .await
+                    .unwrap();
+                assert_eq!(
+                    block["number"],
+                    serde_json::json!(format!("0x{height:x}")),
+                    "Block {height} must be readable once it is the block number"
+                );
+                last_height = height;
+                tokio::task::yield_now().await;
+            }
+        };
+        tokio::join!(builder, reader);
+
+        drop(state_channel);
+        state_handle.await.unwrap();
+    }
 }
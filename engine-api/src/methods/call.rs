@@ -1,7 +1,7 @@
 use {
     crate::{
         json_utils,
-        json_utils::{access_state_error, transaction_error},
+        json_utils::{access_state_error, execution_error},
         jsonrpc::JsonRpcError,
     },
     alloy::{eips::BlockNumberOrTag, rpc::types::TransactionRequest},
@@ -56,18 +56,15 @@ async fn inner_execute(
     .into();
     state_channel.send(msg).await.map_err(access_state_error)?;
     let response = rx.await.map_err(access_state_error)?;
-    response.map_err(transaction_error)
+    response.map_err(|e| execution_error(e, block_number))
 }
 
 #[cfg(test)]
 mod tests {
     use {
         super::*,
-        crate::methods::tests::{create_state_actor, deploy_contract, deposit_eth},
-        alloy::{
-            hex::FromHex,
-            primitives::{Address, Bytes},
-        },
+        crate::methods::tests::{create_state_actor, deploy_counter, deposit_eth},
+        alloy::primitives::Address,
         moved::primitives::U64,
         std::str::FromStr,
         test_case::test_case,
@@ -106,17 +103,18 @@ mod tests {
         }
     }
 
-    #[test_case("0x1")]
-    #[test_case("latest")]
-    #[test_case("pending")]
+    #[test_case("0x1", false)]
+    #[test_case("0x2", true)]
+    #[test_case("latest", true)]
+    #[test_case("pending", true)]
     #[tokio::test]
-    async fn test_execute_call_entry_fn(block: &str) {
+    async fn test_execute_call_entry_fn(block: &str, deployed: bool) {
         let (state_actor, state_channel) = create_state_actor();
         let state_handle = state_actor.spawn();
 
         // Add funds to the account to deploy the `counter` contract
         deposit_eth("0x8fd379246834eac74b8419ffda202cf8051f7a03", &state_channel).await;
-        deploy_contract(Bytes::from_hex("01fd01a11ceb0b0600000009010002020204030614051a0e07283d0865200a8501050c8a01490dd3010200000001080000020001000003000200000400030000050403000105010101030002060c0301070307636f756e74657207436f756e7465720e636f756e7465725f657869737473096765745f636f756e7409696e6372656d656e74077075626c69736801690000000000000000000000008fd379246834eac74b8419ffda202cf8051f7a0300020106030001000003030b00290002010100010003050b002b00100014020201040100050b0b002a000f000c010a0114060100000000000000160b0115020301040003050b000b0112002d0002000000").unwrap(), &state_channel).await;
+        deploy_counter(&state_channel).await;
 
         // Check if the count exists for an address, which returns false
         let request: serde_json::Value = serde_json::json!({
@@ -134,23 +132,29 @@ mod tests {
         });
 
         let expected_response = serde_json::json!([1, 1, 0, 0]);
-        let response = execute(request, state_channel).await.unwrap();
+        let response = execute(request, state_channel).await;
 
-        assert_eq!(response, expected_response);
+        // The counter is deployed in block 2, after the deposit of block 1
+        if deployed {
+            assert_eq!(response.unwrap(), expected_response);
+        } else {
+            assert!(response.is_err(), "Counter is not deployed yet");
+        }
         state_handle.await.unwrap();
     }
 
-    #[test_case("0x1")]
-    #[test_case("latest")]
-    #[test_case("pending")]
+    #[test_case("0x1", false)]
+    #[test_case("0x2", true)]
+    #[test_case("latest", true)]
+    #[test_case("pending", true)]
     #[tokio::test]
-    async fn test_execute_call_script(block: &str) {
+    async fn test_execute_call_script(block: &str, deployed: bool) {
         let (state_actor, state_channel) = create_state_actor();
         let state_handle = state_actor.spawn();
 
         // Add funds to the account to deploy the `counter` contract
         deposit_eth("0x8fd379246834eac74b8419ffda202cf8051f7a03", &state_channel).await;
-        deploy_contract(Bytes::from_hex("01fd01a11ceb0b0600000009010002020204030614051a0e07283d0865200a8501050c8a01490dd3010200000001080000020001000003000200000400030000050403000105010101030002060c0301070307636f756e74657207436f756e7465720e636f756e7465725f657869737473096765745f636f756e7409696e6372656d656e74077075626c69736801690000000000000000000000008fd379246834eac74b8419ffda202cf8051f7a0300020106030001000003030b00290002010100010003050b002b00100014020201040100050b0b002a000f000c010a0114060100000000000000160b0115020301040003050b000b0112002d0002000000").unwrap(), &state_channel).await;
+        deploy_counter(&state_channel).await;
 
         let request: serde_json::Value = serde_json::json!({
             "jsonrpc": "2.0",
@@ -164,8 +168,9 @@ mod tests {
             ],
             "id": 1
         });
-        // Counter script call should succeed
-        execute(request, state_channel).await.unwrap();
+        // Counter script call should succeed once the counter is deployed in block 2
+        let response = execute(request, state_channel).await;
+        assert_eq!(response.is_ok(), deployed, "{response:?}");
 
         state_handle.await.unwrap();
     }
@@ -12,7 +12,9 @@ pub async fn execute(
         .expect("Must be able to JSON-serialize response"))
 }
 
-async fn inner_execute(state_channel: mpsc::Sender<StateMessage>) -> Result<u64, JsonRpcError> {
+pub(super) async fn inner_execute(
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<u64, JsonRpcError> {
     let (tx, rx) = oneshot::channel();
     let msg = Query::ChainId {
         response_channel: tx,
@@ -24,7 +26,11 @@ async fn inner_execute(state_channel: mpsc::Sender<StateMessage>) -> Result<u64,
 
 #[cfg(test)]
 mod tests {
-    use {super::*, crate::methods::tests::create_state_actor};
+    use {
+        super::*,
+        crate::methods::tests::{create_state_actor, create_state_actor_with_genesis},
+        moved::genesis::config::GenesisConfig,
+    };
 
     #[tokio::test]
     async fn test_execute() {
@@ -38,4 +44,20 @@ mod tests {
         assert_eq!(response, expected_response);
         state_handle.await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_execute_returns_configured_chain_id() {
+        let genesis_config = GenesisConfig {
+            chain_id: 42069,
+            verify_state_root: false,
+            ..GenesisConfig::dev()
+        };
+        let (state, state_channel) = create_state_actor_with_genesis(genesis_config);
+        let state_handle = state.spawn();
+
+        let response = execute(state_channel).await.unwrap();
+
+        assert_eq!(response, serde_json::json!("0xa455"));
+        state_handle.await.unwrap();
+    }
 }
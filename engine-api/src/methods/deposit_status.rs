@@ -0,0 +1,111 @@
+use {
+    crate::{
+        json_utils::{self, access_state_error},
+        jsonrpc::JsonRpcError,
+        schema::DepositStatus,
+    },
+    moved::{
+        primitives::B256,
+        types::state::{Query, StateMessage},
+    },
+    tokio::sync::{mpsc, oneshot},
+};
+
+pub async fn execute(
+    request: serde_json::Value,
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<serde_json::Value, JsonRpcError> {
+    let source_hash = parse_params(request)?;
+    let response = inner_execute(source_hash, state_channel).await?;
+    Ok(serde_json::to_value(response).expect("Must be able to JSON-serialize response"))
+}
+
+async fn inner_execute(
+    source_hash: B256,
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<DepositStatus, JsonRpcError> {
+    let (response_channel, rx) = oneshot::channel();
+    let msg = Query::DepositStatus {
+        source_hash,
+        response_channel,
+    }
+    .into();
+    state_channel.send(msg).await.map_err(access_state_error)?;
+    let maybe_inclusion = rx.await.map_err(access_state_error)?;
+
+    Ok(maybe_inclusion.into())
+}
+
+fn parse_params(request: serde_json::Value) -> Result<B256, JsonRpcError> {
+    let params = json_utils::get_params_list(&request);
+    match params {
+        [] => Err(JsonRpcError::parse_error(request, "Not enough params")),
+        [x] => {
+            let source_hash: B256 = json_utils::deserialize(x)?;
+            Ok(source_hash)
+        }
+        _ => Err(JsonRpcError::parse_error(request, "Too many params")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::methods::{forkchoice_updated, tests::create_state_actor},
+        alloy::{
+            hex,
+            primitives::{keccak256, U64},
+        },
+    };
+
+    #[tokio::test]
+    async fn test_execute() {
+        let (state, state_channel) = create_state_actor();
+        let state_handle = state.spawn();
+
+        // Source hash of the L1 attributes deposit in the forkchoice example request
+        let source_hash = "0xde86bef815fc910df65a9459ccb2b9a35fa8596dfcfed1ff01bbf28891d86d5e";
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "moved_depositStatus",
+            "params": [source_hash],
+        });
+
+        // 1. The deposit is not processed before the block is built
+        let status: DepositStatus = serde_json::from_value(
+            execute(request.clone(), state_channel.clone())
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(status, DepositStatus::Pending);
+
+        // 2. Trigger block production including the deposit
+        let forkchoice_request = forkchoice_updated::tests::example_request();
+        let deposit = forkchoice_request["params"][1]["transactions"][0]
+            .as_str()
+            .unwrap();
+        let tx_hash = keccak256(hex::decode(deposit).unwrap());
+        forkchoice_updated::execute_v3(forkchoice_request, state_channel.clone())
+            .await
+            .unwrap();
+
+        // 3. The deposit is processed in the new block
+        let status: DepositStatus =
+            serde_json::from_value(execute(request, state_channel).await.unwrap()).unwrap();
+        let DepositStatus::Processed {
+            transaction_hash,
+            block_number,
+            ..
+        } = status
+        else {
+            panic!("Deposit should be processed, got {status:?}");
+        };
+        assert_eq!(transaction_hash, tx_hash);
+        assert_eq!(block_number, U64::from(1));
+
+        state_handle.await.unwrap();
+    }
+}
@@ -1,7 +1,7 @@
 use {
     crate::{
         json_utils,
-        json_utils::{access_state_error, transaction_error},
+        json_utils::{access_state_error, execution_error},
         jsonrpc::JsonRpcError,
     },
     alloy::{eips::BlockNumberOrTag, rpc::types::TransactionRequest},
@@ -58,14 +58,14 @@ async fn inner_execute(
     .into();
     state_channel.send(msg).await.map_err(access_state_error)?;
     let response = rx.await.map_err(access_state_error)?;
-    response.map_err(transaction_error)
+    response.map_err(|e| execution_error(e, block_number))
 }
 
 #[cfg(test)]
 mod tests {
     use {
         super::*,
-        crate::methods::tests::{create_state_actor, deposit_eth},
+        crate::methods::tests::{create_state_actor, deposit_eth, COUNTER_MODULE},
         alloy::primitives::Address,
         moved::primitives::U64,
         std::str::FromStr,
@@ -121,7 +121,7 @@ mod tests {
             "params": [
                 {
                     "from": "0x8fd379246834eac74b8419ffda202cf8051f7a03",
-                    "input": "0x01fd01a11ceb0b0600000009010002020204030614051a0e07283d0865200a8501050c8a01490dd3010200000001080000020001000003000200000400030000050403000105010101030002060c0301070307636f756e74657207436f756e7465720e636f756e7465725f657869737473096765745f636f756e7409696e6372656d656e74077075626c69736801690000000000000000000000008fd379246834eac74b8419ffda202cf8051f7a0300020106030001000003030b00290002010100010003050b002b00100014020201040100050b0b002a000f000c010a0114060100000000000000160b0115020301040003050b000b0112002d0002000000"
+                    "input": format!("0x{COUNTER_MODULE}")
                 },
                 block,
             ],
@@ -187,8 +187,13 @@ mod tests {
             "id": 1
         });
 
-        let expected_response: serde_json::Value =
-            serde_json::json!({"gasUsedRatio": [], "oldestBlock": "0x0"});
+        // Only the genesis block exists, requested ranges past it are clamped
+        let expected_response: serde_json::Value = serde_json::json!({
+            "baseFeePerGas": ["0x0", "0x0"],
+            "gasUsedRatio": [0.0],
+            "oldestBlock": "0x0",
+            "reward": [["0x0"]]
+        });
         let response = execute(request, state_channel).await.unwrap();
 
         assert_eq!(response, expected_response);
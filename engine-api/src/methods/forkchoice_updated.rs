@@ -79,7 +79,11 @@ async fn inner_execute_v3(
         }
         .into();
         state_channel.send(msg).await.map_err(access_state_error)?;
-        Some(PayloadId(rx.await.map_err(access_state_error)?))
+        let payload_id = rx
+            .await
+            .map_err(access_state_error)?
+            .map_err(|e| JsonRpcError::without_data(-38003, e.to_string()))?;
+        Some(PayloadId(payload_id))
     } else {
         None
     };
@@ -179,6 +183,7 @@ pub(super) mod tests {
                     Bytes::from_static(&hex!("7ef8f8a0de86bef815fc910df65a9459ccb2b9a35fa8596dfcfed1ff01bbf28891d86d5e94deaddeaddeaddeaddeaddeaddeaddeaddead00019442000000000000000000000000000000000000158080830f424080b8a4440a5e2000000558000c5fc50000000000000000000000006660735b00000000000001a9000000000000000000000000000000000000000000000000000000000000000700000000000000000000000000000000000000000000000000000000000000017ae3f74f0134521a7d62a387ac75a5153bcd1aab1c7e003e9b9e15a5d8846363000000000000000000000000e25583099ba105d9ec0a67f5ae86d90e50036425"))
                 ],
                 gas_limit: U64::from_be_slice(&hex!("01c9c380")),
+                no_tx_pool: false,
             }),
         );
 
@@ -0,0 +1,64 @@
+use {
+    crate::{json_utils, json_utils::access_state_error, jsonrpc::JsonRpcError},
+    moved::{
+        primitives::U256,
+        types::state::{Query, StateMessage},
+    },
+    tokio::sync::{mpsc, oneshot},
+};
+
+pub async fn execute(
+    request: serde_json::Value,
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<serde_json::Value, JsonRpcError> {
+    parse_params(request)?;
+    let response = inner_execute(state_channel).await?;
+    Ok(serde_json::to_value(response).expect("Must be able to JSON-serialize response"))
+}
+
+fn parse_params(request: serde_json::Value) -> Result<(), JsonRpcError> {
+    let params = json_utils::get_params_list(&request);
+    match params {
+        [] => Ok(()),
+        _ => Err(JsonRpcError {
+            code: -32602,
+            data: request,
+            message: "Too many params".into(),
+        }),
+    }
+}
+
+async fn inner_execute(state_channel: mpsc::Sender<StateMessage>) -> Result<U256, JsonRpcError> {
+    let (tx, rx) = oneshot::channel();
+    let msg = Query::GasPrice {
+        response_channel: tx,
+    }
+    .into();
+    state_channel.send(msg).await.map_err(access_state_error)?;
+    let response = rx.await.map_err(access_state_error)?;
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::methods::tests::create_state_actor};
+
+    #[tokio::test]
+    async fn test_execute() {
+        let (state_actor, state_channel) = create_state_actor();
+
+        let state_handle = state_actor.spawn();
+        let request: serde_json::Value = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "eth_gasPrice",
+            "params": [],
+            "id": 1
+        });
+
+        let expected_response: serde_json::Value = serde_json::from_str(r#""0x0""#).unwrap();
+        let response = execute(request, state_channel).await.unwrap();
+
+        assert_eq!(response, expected_response);
+        state_handle.await.unwrap();
+    }
+}
@@ -0,0 +1,159 @@
+use {
+    crate::{json_utils, json_utils::access_state_error, jsonrpc::JsonRpcError},
+    alloy::{eips::BlockNumberOrTag, primitives::Address},
+    moved::types::{
+        queries::AccountResponse,
+        state::{Query, StateMessage},
+    },
+    tokio::sync::{mpsc, oneshot},
+};
+
+pub async fn execute(
+    request: serde_json::Value,
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<serde_json::Value, JsonRpcError> {
+    let (address, block_number) = parse_params(request)?;
+    let response = inner_execute(address, block_number, state_channel).await?;
+
+    Ok(serde_json::to_value(response).expect("Must be able to JSON-serialize response"))
+}
+
+fn parse_params(request: serde_json::Value) -> Result<(Address, BlockNumberOrTag), JsonRpcError> {
+    let params = json_utils::get_params_list(&request);
+    match params {
+        [] | [_] => Err(JsonRpcError {
+            code: -32602,
+            data: request,
+            message: "Not enough params".into(),
+        }),
+        [a, b] => {
+            let address: Address = json_utils::deserialize(a)?;
+            let block_number: BlockNumberOrTag = json_utils::deserialize(b)?;
+            Ok((address, block_number))
+        }
+        _ => Err(JsonRpcError {
+            code: -32602,
+            data: request,
+            message: "Too many params".into(),
+        }),
+    }
+}
+
+async fn inner_execute(
+    address: Address,
+    height: BlockNumberOrTag,
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<AccountResponse, JsonRpcError> {
+    let (tx, rx) = oneshot::channel();
+    let msg = Query::AccountByHeight {
+        address,
+        height,
+        response_channel: tx,
+    }
+    .into();
+    state_channel.send(msg).await.map_err(access_state_error)?;
+    let response = rx
+        .await
+        .map_err(access_state_error)?
+        .map_err(|e| JsonRpcError::state_unavailable(e, height))?;
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::methods::{
+            get_balance, get_nonce, get_proof,
+            tests::{create_state_actor, deposit_eth},
+        },
+        moved::types::queries::ProofResponse,
+        std::str::FromStr,
+    };
+
+    #[test]
+    fn test_parse_params() {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "eth_getAccount",
+            "params": [
+                "0x0000000000000000000000000000000000000001",
+                "latest",
+            ],
+            "id": 1
+        });
+
+        let (address, block_number) = parse_params(request).unwrap();
+        assert_eq!(
+            address,
+            Address::from_str("0x0000000000000000000000000000000000000001").unwrap()
+        );
+        assert_eq!(block_number, BlockNumberOrTag::Latest);
+    }
+
+    fn request(method: &str, address: &str) -> serde_json::Value {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": [address, "latest"],
+            "id": 1
+        })
+    }
+
+    #[tokio::test]
+    async fn test_execute_matches_individual_queries() {
+        let (state_actor, state_channel) = create_state_actor();
+        let state_handle = state_actor.spawn();
+
+        // An externally owned account, whose values are read from Move resources
+        let address = "0x8fd379246834eac74B8419FfdA202CF8051F7A03";
+        deposit_eth(address, &state_channel).await;
+
+        let account: AccountResponse = serde_json::from_value(
+            execute(request("eth_getAccount", address), state_channel.clone())
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+        let balance =
+            get_balance::execute(request("eth_getBalance", address), state_channel.clone())
+                .await
+                .unwrap();
+        let nonce = get_nonce::execute(
+            request("eth_getTransactionCount", address),
+            state_channel.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(serde_json::to_value(account.balance).unwrap(), balance);
+        assert_eq!(serde_json::to_value(account.nonce).unwrap(), nonce);
+
+        // An L2 contract, whose values are read from the EVM state
+        let address = "0x4200000000000000000000000000000000000016";
+        let account: AccountResponse = serde_json::from_value(
+            execute(request("eth_getAccount", address), state_channel.clone())
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+        let proof_request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "eth_getProof",
+            "params": [address, [], "latest"],
+            "id": 1
+        });
+        let proof: ProofResponse = serde_json::from_value(
+            get_proof::execute(proof_request, state_channel)
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(account.balance, proof.balance);
+        assert_eq!(account.nonce.to::<u64>(), proof.nonce);
+        assert_eq!(account.code_hash, proof.code_hash);
+        assert_eq!(account.storage_root, proof.storage_hash);
+
+        state_handle.await.unwrap();
+    }
+}
@@ -13,9 +13,7 @@ pub async fn execute(
     state_channel: mpsc::Sender<StateMessage>,
 ) -> Result<serde_json::Value, JsonRpcError> {
     let (address, block_number) = parse_params(request)?;
-    let response = inner_execute(address, block_number, state_channel)
-        .await?
-        .ok_or(JsonRpcError::block_not_found(block_number))?;
+    let response = inner_execute(address, block_number, state_channel).await?;
 
     // Format the balance as a hex string
     Ok(serde_json::Value::String(format!("0x{:x}", response)))
@@ -46,7 +44,7 @@ async fn inner_execute(
     address: Address,
     height: BlockNumberOrTag,
     state_channel: mpsc::Sender<StateMessage>,
-) -> Result<Option<U256>, JsonRpcError> {
+) -> Result<U256, JsonRpcError> {
     let (tx, rx) = oneshot::channel();
     let msg = Query::BalanceByHeight {
         address,
@@ -55,7 +53,10 @@ async fn inner_execute(
     }
     .into();
     state_channel.send(msg).await.map_err(access_state_error)?;
-    let response = rx.await.map_err(access_state_error)?;
+    let response = rx
+        .await
+        .map_err(access_state_error)?
+        .map_err(|e| JsonRpcError::state_unavailable(e, height))?;
     Ok(response)
 }
 
@@ -0,0 +1,141 @@
+use {
+    crate::{
+        json_utils::{self, access_state_error},
+        jsonrpc::JsonRpcError,
+        schema::BlockRoots,
+    },
+    alloy::eips::BlockId,
+    moved::types::state::{Query, StateMessage},
+    tokio::sync::{mpsc, oneshot},
+};
+
+pub async fn execute(
+    request: serde_json::Value,
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<serde_json::Value, JsonRpcError> {
+    let block_id = parse_params(request)?;
+    let response = inner_execute(block_id, state_channel).await?;
+    Ok(serde_json::to_value(response).expect("Must be able to JSON-serialize response"))
+}
+
+async fn inner_execute(
+    block_id: BlockId,
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<Option<BlockRoots>, JsonRpcError> {
+    let (response_channel, rx) = oneshot::channel();
+    let msg = match block_id {
+        BlockId::Hash(hash) => Query::BlockByHash {
+            hash: hash.block_hash,
+            include_transactions: false,
+            response_channel,
+        },
+        BlockId::Number(height) => Query::BlockByHeight {
+            height,
+            include_transactions: false,
+            response_channel,
+        },
+    }
+    .into();
+    state_channel.send(msg).await.map_err(access_state_error)?;
+    let maybe_response = rx.await.map_err(access_state_error)?;
+
+    Ok(maybe_response.map(BlockRoots::from))
+}
+
+fn parse_params(request: serde_json::Value) -> Result<BlockId, JsonRpcError> {
+    let params = json_utils::get_params_list(&request);
+    match params {
+        [] => Err(JsonRpcError::parse_error(request, "Not enough params")),
+        [x] => {
+            let block_id: BlockId = json_utils::deserialize(x)?;
+            Ok(block_id)
+        }
+        _ => Err(JsonRpcError::parse_error(request, "Too many params")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::methods::{forkchoice_updated, get_block_by_number, tests::create_state_actor},
+        moved::primitives::B256,
+    };
+
+    fn request(block_id: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "moved_getBlockRoots",
+            "params": [block_id],
+        })
+    }
+
+    #[tokio::test]
+    async fn test_execute_matches_block_header() {
+        let (state, state_channel) = create_state_actor();
+        let state_handle = state.spawn();
+
+        // Build a block with a deposit so that its roots are not the empty ones
+        forkchoice_updated::execute_v3(
+            forkchoice_updated::tests::example_request(),
+            state_channel.clone(),
+        )
+        .await
+        .unwrap();
+
+        let block_request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_getBlockByNumber",
+            "params": ["0x1", false],
+        });
+        let block = get_block_by_number::execute(block_request, state_channel.clone())
+            .await
+            .unwrap();
+        let header_field =
+            |name: &str| -> B256 { serde_json::from_value(block[name].clone()).unwrap() };
+
+        let by_number: BlockRoots = serde_json::from_value(
+            execute(request("0x1".into()), state_channel.clone())
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(by_number.hash, header_field("hash"));
+        assert_eq!(by_number.number.to::<u64>(), 1);
+        assert_eq!(by_number.state_root, header_field("stateRoot"));
+        assert_eq!(
+            by_number.transactions_root,
+            header_field("transactionsRoot")
+        );
+        assert_eq!(by_number.receipts_root, header_field("receiptsRoot"));
+
+        let by_hash: BlockRoots = serde_json::from_value(
+            execute(request(block["hash"].clone()), state_channel.clone())
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(by_hash, by_number);
+
+        let latest: BlockRoots = serde_json::from_value(
+            execute(request("latest".into()), state_channel.clone())
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(latest, by_number);
+
+        // Unknown blocks have no roots
+        let missing = execute(
+            request(B256::repeat_byte(1).to_string().into()),
+            state_channel,
+        )
+        .await
+        .unwrap();
+        assert_eq!(missing, serde_json::Value::Null);
+
+        state_handle.await.unwrap();
+    }
+}
@@ -0,0 +1,72 @@
+use {
+    crate::{json_utils, json_utils::access_state_error, jsonrpc::JsonRpcError},
+    moved::types::{
+        queries::L1FeeParams,
+        state::{Query, StateMessage},
+    },
+    tokio::sync::{mpsc, oneshot},
+};
+
+pub async fn execute(
+    request: serde_json::Value,
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<serde_json::Value, JsonRpcError> {
+    parse_params(request)?;
+    let response = inner_execute(state_channel).await?;
+    Ok(serde_json::to_value(response).expect("Must be able to JSON-serialize response"))
+}
+
+fn parse_params(request: serde_json::Value) -> Result<(), JsonRpcError> {
+    let params = json_utils::get_params_list(&request);
+    match params {
+        [] => Ok(()),
+        _ => Err(JsonRpcError::parse_error(request, "Too many params")),
+    }
+}
+
+async fn inner_execute(
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<Option<L1FeeParams>, JsonRpcError> {
+    let (response_channel, rx) = oneshot::channel();
+    let msg = Query::L1FeeParams { response_channel }.into();
+    state_channel.send(msg).await.map_err(access_state_error)?;
+    let response = rx.await.map_err(access_state_error)?;
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::methods::tests::create_state_actor};
+
+    #[tokio::test]
+    async fn test_execute_without_l1_attributes_deposit() {
+        let (state_actor, state_channel) = create_state_actor();
+        let state_handle = state_actor.spawn();
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "moved_getL1FeeParams",
+            "params": [],
+            "id": 1
+        });
+
+        // The genesis block does not start with a deposit setting the parameters
+        let response = execute(request, state_channel).await.unwrap();
+
+        assert_eq!(response, serde_json::Value::Null);
+        state_handle.await.unwrap();
+    }
+
+    #[test]
+    fn test_parse_params_rejects_params() {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "moved_getL1FeeParams",
+            "params": ["latest"],
+            "id": 1
+        });
+
+        let err = parse_params(request).unwrap_err();
+
+        assert_eq!(err.message, "Too many params");
+    }
+}
@@ -55,7 +55,9 @@ async fn inner_execute(
     }
     .into();
     state_channel.send(msg).await.map_err(access_state_error)?;
-    let response = rx.await?.ok_or(JsonRpcError::block_not_found(height))?;
+    let response = rx
+        .await?
+        .map_err(|e| JsonRpcError::state_unavailable(e, height))?;
 
     Ok(response)
 }
@@ -103,7 +103,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_execute_v3() {
-        let genesis_config = GenesisConfig::default();
+        let genesis_config = GenesisConfig::dev();
         let (state_channel, rx) = mpsc::channel(10);
 
         // Set known block height
@@ -117,7 +117,7 @@ mod tests {
         repository.add(&mut block_memory, genesis_block);
 
         let mut state = InMemoryState::new();
-        let (changes, table_changes) = genesis::init(&genesis_config, &state);
+        let (changes, table_changes) = genesis::init(&genesis_config, &state).unwrap();
         genesis::apply(changes.clone(), table_changes, &genesis_config, &mut state);
         let initial_state_root = genesis_config.initial_state_root;
 
@@ -7,9 +7,12 @@ use {
         eips::{BlockId, BlockNumberOrTag},
         primitives::{Address, U256},
     },
-    moved::types::{
-        queries::ProofResponse,
-        state::{Query, StateMessage},
+    moved::{
+        state_actor::ProofError,
+        types::{
+            queries::ProofResponse,
+            state::{Query, StateMessage},
+        },
     },
     tokio::sync::{mpsc, oneshot},
 };
@@ -71,7 +74,10 @@ async fn inner_execute(
     }
     .into();
     state_channel.send(msg).await.map_err(access_state_error)?;
-    let response = rx.await?.ok_or(JsonRpcError::block_not_found(height))?;
+    let response = rx.await?.map_err(|e| match e {
+        ProofError::StateUnavailable(e) => JsonRpcError::state_unavailable(e, height),
+        ProofError::TooManyStorageSlots { .. } => JsonRpcError::without_data(-32602, e.to_string()),
+    })?;
 
     Ok(response)
 }
@@ -157,4 +163,41 @@ mod tests {
 
         state_handle.await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_execute_rejects_too_many_storage_slots() {
+        let (state_actor, state_channel) = create_state_actor();
+        let state_handle = state_actor.with_max_proof_slots(2).spawn();
+        let request = |storage_slots: &[&str]| {
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "eth_getProof",
+                "params": [
+                    "0x4200000000000000000000000000000000000016",
+                    storage_slots,
+                    "latest",
+                ],
+                "id": 1
+            })
+        };
+
+        let response: ProofResponse = serde_json::from_value(
+            execute(request(&["0x0", "0x1"]), state_channel.clone())
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(response.storage_proof.len(), 2);
+
+        let error = execute(request(&["0x0", "0x1", "0x2"]), state_channel)
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, -32602);
+        assert_eq!(
+            error.message,
+            "Too many storage slots: 3 requested, at most 2 allowed"
+        );
+
+        state_handle.await.unwrap();
+    }
 }
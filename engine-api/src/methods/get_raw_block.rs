@@ -0,0 +1,123 @@
+use {
+    crate::{
+        json_utils::{self, access_state_error},
+        jsonrpc::JsonRpcError,
+    },
+    alloy::eips::BlockId,
+    moved::{
+        block::ExtendedBlock,
+        primitives::Bytes,
+        types::state::{Query, StateMessage},
+    },
+    tokio::sync::{mpsc, oneshot},
+};
+
+pub async fn execute(
+    request: serde_json::Value,
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<serde_json::Value, JsonRpcError> {
+    let block_id = parse_params(request)?;
+    let response = inner_execute(block_id, state_channel).await?;
+    Ok(serde_json::to_value(response).expect("Must be able to JSON-serialize response"))
+}
+
+async fn inner_execute(
+    block_id: BlockId,
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<Option<Bytes>, JsonRpcError> {
+    let maybe_block = extended_block(block_id, state_channel).await?;
+
+    Ok(maybe_block.map(|block| block.block.rlp_encoded().into()))
+}
+
+/// Fetches the block identified by `block_id` as it is stored by the node.
+pub async fn extended_block(
+    block_id: BlockId,
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<Option<ExtendedBlock>, JsonRpcError> {
+    let (response_channel, rx) = oneshot::channel();
+    let msg = Query::ExtendedBlock {
+        block_id,
+        response_channel,
+    }
+    .into();
+    state_channel.send(msg).await.map_err(access_state_error)?;
+    rx.await.map_err(access_state_error)
+}
+
+pub fn parse_params(request: serde_json::Value) -> Result<BlockId, JsonRpcError> {
+    let params = json_utils::get_params_list(&request);
+    match params {
+        [] => Err(JsonRpcError::parse_error(request, "Not enough params")),
+        [x] => {
+            let block_id: BlockId = json_utils::deserialize(x)?;
+            Ok(block_id)
+        }
+        _ => Err(JsonRpcError::parse_error(request, "Too many params")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::methods::{forkchoice_updated, get_raw_header, tests::create_state_actor},
+        alloy::rlp,
+    };
+
+    fn request(method: &str, block_id: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": [block_id],
+        })
+    }
+
+    #[tokio::test]
+    async fn test_execute_starts_with_raw_header() {
+        let (state, state_channel) = create_state_actor();
+        let state_handle = state.spawn();
+
+        forkchoice_updated::execute_v3(
+            forkchoice_updated::tests::example_request(),
+            state_channel.clone(),
+        )
+        .await
+        .unwrap();
+
+        let raw_block: Bytes = serde_json::from_value(
+            execute(
+                request("debug_getRawBlock", "0x1".into()),
+                state_channel.clone(),
+            )
+            .await
+            .unwrap(),
+        )
+        .unwrap();
+        let raw_header: Bytes = serde_json::from_value(
+            get_raw_header::execute(
+                request("debug_getRawHeader", "0x1".into()),
+                state_channel.clone(),
+            )
+            .await
+            .unwrap(),
+        )
+        .unwrap();
+
+        // The block is a list with the header as its first item
+        let mut payload = raw_block.as_ref();
+        let list_header = rlp::Header::decode(&mut payload).unwrap();
+        assert!(list_header.list);
+        assert_eq!(list_header.payload_length, payload.len());
+        assert!(payload.starts_with(&raw_header));
+
+        // Unknown blocks have no encoding
+        let missing = execute(request("debug_getRawBlock", "0x2".into()), state_channel)
+            .await
+            .unwrap();
+        assert_eq!(missing, serde_json::Value::Null);
+
+        state_handle.await.unwrap();
+    }
+}
@@ -0,0 +1,102 @@
+use {
+    crate::{
+        jsonrpc::JsonRpcError,
+        methods::get_raw_block::{extended_block, parse_params},
+    },
+    alloy::{eips::BlockId, rlp::Encodable},
+    moved::{primitives::Bytes, types::state::StateMessage},
+    tokio::sync::mpsc,
+};
+
+pub async fn execute(
+    request: serde_json::Value,
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<serde_json::Value, JsonRpcError> {
+    let block_id = parse_params(request)?;
+    let response = inner_execute(block_id, state_channel).await?;
+    Ok(serde_json::to_value(response).expect("Must be able to JSON-serialize response"))
+}
+
+async fn inner_execute(
+    block_id: BlockId,
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<Option<Bytes>, JsonRpcError> {
+    let maybe_block = extended_block(block_id, state_channel).await?;
+
+    Ok(maybe_block.map(|block| {
+        let mut bytes = Vec::new();
+        block.block.header.encode(&mut bytes);
+        bytes.into()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::methods::{forkchoice_updated, get_block_by_number, tests::create_state_actor},
+        alloy::{primitives::keccak256, rlp::Decodable, rpc::types::Header as RpcHeader},
+        moved::{block::Header, primitives::B256},
+    };
+
+    fn request(block_id: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "debug_getRawHeader",
+            "params": [block_id],
+        })
+    }
+
+    #[tokio::test]
+    async fn test_execute_decodes_to_block_header() {
+        let (state, state_channel) = create_state_actor();
+        let state_handle = state.spawn();
+
+        // Build a block with a deposit so that its header is not the genesis one
+        forkchoice_updated::execute_v3(
+            forkchoice_updated::tests::example_request(),
+            state_channel.clone(),
+        )
+        .await
+        .unwrap();
+
+        let block_request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_getBlockByNumber",
+            "params": ["0x1", false],
+        });
+        let block = get_block_by_number::execute(block_request, state_channel.clone())
+            .await
+            .unwrap();
+        let expected_header: RpcHeader = serde_json::from_value(block.clone()).unwrap();
+
+        let raw_header: Bytes = serde_json::from_value(
+            execute(request("0x1".into()), state_channel.clone())
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+        let header = Header::decode(&mut raw_header.as_ref()).unwrap();
+        assert_eq!(header, expected_header.inner);
+        assert_eq!(keccak256(&raw_header), expected_header.hash);
+        assert_eq!(header.hash_slow(), expected_header.hash);
+
+        // The block can be identified by hash too
+        let by_hash = execute(request(block["hash"].clone()), state_channel.clone())
+            .await
+            .unwrap();
+        assert_eq!(by_hash, serde_json::to_value(&raw_header).unwrap());
+
+        let missing = execute(
+            request(B256::repeat_byte(1).to_string().into()),
+            state_channel,
+        )
+        .await
+        .unwrap();
+        assert_eq!(missing, serde_json::Value::Null);
+
+        state_handle.await.unwrap();
+    }
+}
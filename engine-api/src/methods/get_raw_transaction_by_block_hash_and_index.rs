@@ -0,0 +1,127 @@
+use {
+    crate::{
+        json_utils::{self, access_state_error},
+        jsonrpc::JsonRpcError,
+        methods::get_raw_transaction_by_hash::raw_transaction,
+    },
+    moved::{
+        primitives::{Bytes, B256, U64},
+        types::state::{Query, StateMessage},
+    },
+    tokio::sync::{mpsc, oneshot},
+};
+
+pub async fn execute(
+    request: serde_json::Value,
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<serde_json::Value, JsonRpcError> {
+    let (block_hash, index) = parse_params(request)?;
+    let response = inner_execute(block_hash, index, state_channel).await?;
+    Ok(serde_json::to_value(response).expect("Must be able to JSON-serialize response"))
+}
+
+async fn inner_execute(
+    hash: B256,
+    index: U64,
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<Option<Bytes>, JsonRpcError> {
+    let (response_channel, rx) = oneshot::channel();
+    let msg = Query::BlockByHash {
+        hash,
+        include_transactions: true,
+        response_channel,
+    }
+    .into();
+    state_channel.send(msg).await.map_err(access_state_error)?;
+    let maybe_response = rx.await.map_err(access_state_error)?;
+
+    Ok(maybe_response.and_then(|block| {
+        let transactions = block.0.transactions.as_transactions()?;
+        let tx = transactions.get(index.saturating_to::<usize>())?;
+        Some(raw_transaction(tx))
+    }))
+}
+
+fn parse_params(request: serde_json::Value) -> Result<(B256, U64), JsonRpcError> {
+    let params = json_utils::get_params_list(&request);
+    match params {
+        [] | [_] => Err(JsonRpcError::parse_error(request, "Not enough params")),
+        [x, y] => {
+            let block_hash: B256 = json_utils::deserialize(x)?;
+            let index: U64 = json_utils::deserialize(y)?;
+            Ok((block_hash, index))
+        }
+        _ => Err(JsonRpcError::parse_error(request, "Too many params")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::methods::{
+            forkchoice_updated, get_transaction_by_hash, send_raw_transaction,
+            tests::create_state_actor,
+        },
+    };
+
+    #[tokio::test]
+    async fn test_execute() {
+        let (state, state_channel) = create_state_actor();
+        let state_handle = state.spawn();
+
+        // 1. Send transaction and include it in a block
+        let send_request = send_raw_transaction::tests::example_request();
+        let raw_tx = send_request["params"][0].clone();
+        let tx_hash = send_raw_transaction::execute(send_request, state_channel.clone())
+            .await
+            .unwrap();
+        forkchoice_updated::execute_v3(
+            forkchoice_updated::tests::example_request(),
+            state_channel.clone(),
+        )
+        .await
+        .unwrap();
+        let tx = get_transaction_by_hash::execute(
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "eth_getTransactionByHash",
+                "params": [tx_hash],
+            }),
+            state_channel.clone(),
+        )
+        .await
+        .unwrap();
+        let request = |block_hash: &serde_json::Value, index: &str| {
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "eth_getRawTransactionByBlockHashAndIndex",
+                "params": [block_hash, index],
+            })
+        };
+
+        // 2. The included transaction is returned as submitted
+        let response = execute(
+            request(&tx["blockHash"], tx["transactionIndex"].as_str().unwrap()),
+            state_channel.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response, raw_tx);
+
+        // 3. Out of range indices and unknown blocks are null
+        let response = execute(request(&tx["blockHash"], "0x10"), state_channel.clone())
+            .await
+            .unwrap();
+        assert!(response.is_null());
+        let unknown_block = serde_json::to_value(B256::ZERO).unwrap();
+        let response = execute(request(&unknown_block, "0x0"), state_channel)
+            .await
+            .unwrap();
+        assert!(response.is_null());
+
+        state_handle.await.unwrap();
+    }
+}
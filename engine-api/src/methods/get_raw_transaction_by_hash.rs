@@ -0,0 +1,113 @@
+use {
+    crate::{
+        json_utils::{self, access_state_error},
+        jsonrpc::JsonRpcError,
+    },
+    alloy::rlp::Encodable,
+    moved::{
+        primitives::{Bytes, B256},
+        types::state::{Query, RpcTransaction, StateMessage},
+    },
+    tokio::sync::{mpsc, oneshot},
+};
+
+pub async fn execute(
+    request: serde_json::Value,
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<serde_json::Value, JsonRpcError> {
+    let tx_hash = parse_params(request)?;
+    let response = inner_execute(tx_hash, state_channel).await?;
+    Ok(serde_json::to_value(response).expect("Must be able to JSON-serialize response"))
+}
+
+async fn inner_execute(
+    tx_hash: B256,
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<Option<Bytes>, JsonRpcError> {
+    let (response_channel, rx) = oneshot::channel();
+    let msg = Query::TransactionByHash {
+        tx_hash,
+        response_channel,
+    }
+    .into();
+    state_channel.send(msg).await.map_err(access_state_error)?;
+    let maybe_response = rx.await.map_err(access_state_error)?;
+
+    Ok(maybe_response.as_ref().map(raw_transaction))
+}
+
+/// RLP encodes `tx` the same way it is accepted by `eth_sendRawTransaction`.
+pub fn raw_transaction(tx: &RpcTransaction) -> Bytes {
+    let mut bytes = Vec::new();
+    tx.inner.inner.encode(&mut bytes);
+    bytes.into()
+}
+
+fn parse_params(request: serde_json::Value) -> Result<B256, JsonRpcError> {
+    let params = json_utils::get_params_list(&request);
+    match params {
+        [] => Err(JsonRpcError::parse_error(request, "Not enough params")),
+        [x] => {
+            let tx_hash: B256 = json_utils::deserialize(x)?;
+            Ok(tx_hash)
+        }
+        _ => Err(JsonRpcError::parse_error(request, "Too many params")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::methods::{forkchoice_updated, send_raw_transaction, tests::create_state_actor},
+    };
+
+    #[tokio::test]
+    async fn test_execute() {
+        let (state, state_channel) = create_state_actor();
+        let state_handle = state.spawn();
+
+        // 1. Send transaction
+        let send_request = send_raw_transaction::tests::example_request();
+        let raw_tx = send_request["params"][0].clone();
+        let tx_hash = send_raw_transaction::execute(send_request, state_channel.clone())
+            .await
+            .unwrap();
+        let request = |tx_hash| {
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "eth_getRawTransactionByHash",
+                "params": [tx_hash],
+            })
+        };
+
+        // 2. The pending transaction is returned as submitted
+        let response = execute(request(tx_hash.clone()), state_channel.clone())
+            .await
+            .unwrap();
+        assert_eq!(response, raw_tx);
+
+        // 3. Trigger block production
+        forkchoice_updated::execute_v3(
+            forkchoice_updated::tests::example_request(),
+            state_channel.clone(),
+        )
+        .await
+        .unwrap();
+
+        // 4. The included transaction is returned as submitted
+        let response = execute(request(tx_hash), state_channel.clone())
+            .await
+            .unwrap();
+        assert_eq!(response, raw_tx);
+
+        // 5. Unknown transactions are null
+        let response = execute(request(B256::ZERO.to_string().into()), state_channel)
+            .await
+            .unwrap();
+        assert!(response.is_null());
+
+        state_handle.await.unwrap();
+    }
+}
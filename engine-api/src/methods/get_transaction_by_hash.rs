@@ -0,0 +1,130 @@
+use {
+    crate::{
+        json_utils::{self, access_state_error},
+        jsonrpc::JsonRpcError,
+    },
+    moved::{
+        primitives::B256,
+        types::state::{Query, RpcTransaction, StateMessage},
+    },
+    tokio::sync::{mpsc, oneshot},
+};
+
+pub async fn execute(
+    request: serde_json::Value,
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<serde_json::Value, JsonRpcError> {
+    let tx_hash = parse_params(request)?;
+    let response = inner_execute(tx_hash, state_channel).await?;
+    Ok(serde_json::to_value(response).expect("Must be able to JSON-serialize response"))
+}
+
+async fn inner_execute(
+    tx_hash: B256,
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<Option<RpcTransaction>, JsonRpcError> {
+    let (response_channel, rx) = oneshot::channel();
+    let msg = Query::TransactionByHash {
+        tx_hash,
+        response_channel,
+    }
+    .into();
+    state_channel.send(msg).await.map_err(access_state_error)?;
+    let maybe_response = rx.await.map_err(access_state_error)?;
+
+    Ok(maybe_response)
+}
+
+fn parse_params(request: serde_json::Value) -> Result<B256, JsonRpcError> {
+    let params = json_utils::get_params_list(&request);
+    match params {
+        [] => Err(JsonRpcError::parse_error(request, "Not enough params")),
+        [x] => {
+            let tx_hash: B256 = json_utils::deserialize(x)?;
+            Ok(tx_hash)
+        }
+        _ => Err(JsonRpcError::parse_error(request, "Too many params")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::methods::{forkchoice_updated, send_raw_transaction, tests::create_state_actor},
+        moved::genesis::config::CHAIN_ID,
+    };
+
+    #[tokio::test]
+    async fn test_execute() {
+        let (state, state_channel) = create_state_actor();
+        let state_handle = state.spawn();
+
+        // 1. Send transaction
+        let tx_hash = send_raw_transaction::execute(
+            send_raw_transaction::tests::example_request(),
+            state_channel.clone(),
+        )
+        .await
+        .unwrap();
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_getTransactionByHash",
+            "params": [tx_hash],
+        });
+
+        // 2. The pending transaction has no block fields
+        let response = execute(request.clone(), state_channel.clone())
+            .await
+            .unwrap();
+        assert_eq!(response["hash"], tx_hash);
+        assert!(response["blockHash"].is_null());
+        assert!(response["blockNumber"].is_null());
+        assert!(response["transactionIndex"].is_null());
+
+        // 3. Trigger block production
+        forkchoice_updated::execute_v3(
+            forkchoice_updated::tests::example_request(),
+            state_channel.clone(),
+        )
+        .await
+        .unwrap();
+
+        // 4. The included transaction has block fields
+        let response = execute(request, state_channel).await.unwrap();
+        assert_eq!(response["hash"], tx_hash);
+        assert!(response["blockHash"].is_string());
+        assert_eq!(response["blockNumber"], "0x1");
+        assert_eq!(response["transactionIndex"], "0x2");
+
+        state_handle.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_execute_includes_y_parity_and_chain_id() {
+        let (state, state_channel) = create_state_actor();
+        let state_handle = state.spawn();
+
+        // The example is an EIP-1559 transaction with a signature y parity of 0
+        let tx_hash = send_raw_transaction::execute(
+            send_raw_transaction::tests::example_request(),
+            state_channel.clone(),
+        )
+        .await
+        .unwrap();
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_getTransactionByHash",
+            "params": [tx_hash],
+        });
+
+        let response = execute(request, state_channel).await.unwrap();
+        assert_eq!(response["type"], "0x2");
+        assert_eq!(response["chainId"], format!("{CHAIN_ID:#x}"));
+        assert_eq!(response["yParity"], "0x0");
+
+        state_handle.await.unwrap();
+    }
+}
@@ -5,7 +5,7 @@ use {
     },
     moved::{
         primitives::B256,
-        types::state::{Query, StateMessage, TransactionReceipt},
+        types::state::{ExtendedTransactionReceipt, Query, StateMessage},
     },
     tokio::sync::{mpsc, oneshot},
 };
@@ -22,7 +22,7 @@ pub async fn execute(
 async fn inner_execute(
     tx_hash: B256,
     state_channel: mpsc::Sender<StateMessage>,
-) -> Result<Option<TransactionReceipt>, JsonRpcError> {
+) -> Result<Option<ExtendedTransactionReceipt>, JsonRpcError> {
     let (response_channel, rx) = oneshot::channel();
     let msg = Query::TransactionReceipt {
         tx_hash,
@@ -57,6 +57,7 @@ mod tests {
             },
             schema::{ForkchoiceUpdatedResponseV1, GetPayloadResponseV3},
         },
+        moved::types::state::TransactionReceipt,
         std::iter,
     };
 
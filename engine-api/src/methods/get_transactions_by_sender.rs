@@ -0,0 +1,135 @@
+use {
+    crate::{json_utils, json_utils::access_state_error, jsonrpc::JsonRpcError},
+    alloy::primitives::{Address, B256, U64},
+    moved::types::state::{Query, StateMessage},
+    tokio::sync::{mpsc, oneshot},
+};
+
+/// The most transaction hashes returned by a single request, also used when no limit is given.
+const MAX_LIMIT: u64 = 1000;
+
+pub async fn execute(
+    request: serde_json::Value,
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<serde_json::Value, JsonRpcError> {
+    let (address, offset, limit) = parse_params(request)?;
+    let response = inner_execute(address, offset, limit, state_channel).await?;
+
+    Ok(serde_json::to_value(response).expect("Must be able to JSON-serialize response"))
+}
+
+fn parse_params(request: serde_json::Value) -> Result<(Address, u64, u64), JsonRpcError> {
+    let params = json_utils::get_params_list(&request);
+    let (address, offset, limit): (Address, U64, U64) = match params {
+        [] => return Err(JsonRpcError::parse_error(request, "Not enough params")),
+        [a] => (json_utils::deserialize(a)?, U64::ZERO, U64::from(MAX_LIMIT)),
+        [a, b] => (
+            json_utils::deserialize(a)?,
+            json_utils::deserialize(b)?,
+            U64::from(MAX_LIMIT),
+        ),
+        [a, b, c] => (
+            json_utils::deserialize(a)?,
+            json_utils::deserialize(b)?,
+            json_utils::deserialize(c)?,
+        ),
+        _ => return Err(JsonRpcError::parse_error(request, "Too many params")),
+    };
+    if limit > U64::from(MAX_LIMIT) {
+        return Err(JsonRpcError::parse_error(
+            request,
+            format!("Limit must not exceed {MAX_LIMIT}"),
+        ));
+    }
+    Ok((address, offset.to(), limit.to()))
+}
+
+async fn inner_execute(
+    address: Address,
+    offset: u64,
+    limit: u64,
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<Vec<B256>, JsonRpcError> {
+    let (tx, rx) = oneshot::channel();
+    let msg = Query::TransactionsBySender {
+        address,
+        offset: usize::try_from(offset).unwrap_or(usize::MAX),
+        limit: limit as usize,
+        response_channel: tx,
+    }
+    .into();
+    state_channel.send(msg).await.map_err(access_state_error)?;
+    let response = rx.await.map_err(access_state_error)?;
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::methods::tests::{create_state_actor, deploy_counter},
+        std::str::FromStr,
+        test_case::test_case,
+    };
+
+    // Address of the `PRIVATE_KEY` signer deploying the contract
+    const SIGNER: &str = "0x8fd379246834eac74B8419FfdA202CF8051F7A03";
+
+    fn request(params: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "moved_getTransactionsBySender",
+            "params": params,
+            "id": 1
+        })
+    }
+
+    #[test_case(serde_json::json!([SIGNER]), 0, MAX_LIMIT)]
+    #[test_case(serde_json::json!([SIGNER, "0x2"]), 2, MAX_LIMIT)]
+    #[test_case(serde_json::json!([SIGNER, "0x2", "0x5"]), 2, 5)]
+    fn test_parse_params(params: serde_json::Value, offset: u64, limit: u64) {
+        let address = Address::from_str(SIGNER).unwrap();
+
+        assert_eq!(
+            parse_params(request(params)).unwrap(),
+            (address, offset, limit)
+        );
+    }
+
+    #[test]
+    fn test_parse_params_rejects_limit_above_max() {
+        let err = parse_params(request(serde_json::json!([SIGNER, "0x0", "0x3e9"]))).unwrap_err();
+
+        assert_eq!(err.message, "Limit must not exceed 1000");
+    }
+
+    #[tokio::test]
+    async fn test_execute_lists_transactions_of_sender() {
+        let (state_actor, state_channel) = create_state_actor();
+        let state_handle = state_actor.spawn();
+
+        let response = execute(request(serde_json::json!([SIGNER])), state_channel.clone())
+            .await
+            .unwrap();
+        assert_eq!(response, serde_json::json!([]));
+
+        deploy_counter(&state_channel).await;
+        let response = execute(request(serde_json::json!([SIGNER])), state_channel.clone())
+            .await
+            .unwrap();
+        let tx_hashes: Vec<B256> = serde_json::from_value(response).unwrap();
+        assert_eq!(tx_hashes.len(), 1);
+
+        let response = execute(
+            request(serde_json::json!([SIGNER, "0x1"])),
+            state_channel.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response, serde_json::json!([]));
+
+        drop(state_channel);
+        state_handle.await.unwrap();
+    }
+}
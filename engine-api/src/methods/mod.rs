@@ -1,18 +1,35 @@
 pub mod block_number;
 pub mod call;
 pub mod chain_id;
+pub mod deposit_status;
 pub mod estimate_gas;
 pub mod fee_history;
 pub mod forkchoice_updated;
+pub mod gas_price;
+pub mod get_account;
 pub mod get_balance;
 pub mod get_block_by_hash;
 pub mod get_block_by_number;
+pub mod get_block_roots;
+pub mod get_l1_fee_params;
 pub mod get_nonce;
 pub mod get_payload;
 pub mod get_proof;
+pub mod get_raw_block;
+pub mod get_raw_header;
+pub mod get_raw_transaction_by_block_hash_and_index;
+pub mod get_raw_transaction_by_hash;
+pub mod get_transaction_by_hash;
 pub mod get_transaction_receipt;
+pub mod get_transactions_by_sender;
+pub mod net;
 pub mod new_payload;
+pub mod node_info;
+pub mod protocol_version;
+pub mod resource_exists;
 pub mod send_raw_transaction;
+pub mod trace_transaction;
+pub mod web3_client_version;
 
 #[cfg(test)]
 pub mod tests {
@@ -59,7 +76,12 @@ pub mod tests {
     pub const PRIVATE_KEY: [u8; 32] = [0xaa; 32];
 
     pub fn create_state_actor() -> (moved::state_actor::InMemStateActor, Sender<StateMessage>) {
-        let genesis_config = GenesisConfig::default();
+        create_state_actor_with_genesis(GenesisConfig::dev())
+    }
+
+    pub fn create_state_actor_with_genesis(
+        genesis_config: GenesisConfig,
+    ) -> (moved::state_actor::InMemStateActor, Sender<StateMessage>) {
         let (state_channel, rx) = mpsc::channel(10);
 
         let head_hash = B256::new(hex!(
@@ -72,9 +94,9 @@ pub mod tests {
         repository.add(&mut block_memory, genesis_block);
 
         let mut state = InMemoryState::new();
-        let (changes, table_changes) = genesis::init(&genesis_config, &state);
-        genesis::apply(changes.clone(), table_changes, &genesis_config, &mut state);
-        let initial_state_root = genesis_config.initial_state_root;
+        let (changes, table_changes) = genesis::init(&genesis_config, &state).unwrap();
+        let initial_state_root =
+            genesis::apply(changes.clone(), table_changes, &genesis_config, &mut state);
 
         let state = StateActor::new(
             rx,
@@ -93,7 +115,7 @@ pub mod tests {
             block_memory,
             InMemoryStateQueries::from_genesis(initial_state_root),
             StateActor::on_tx_noop(),
-            StateActor::on_tx_batch_noop(),
+            StateActor::on_tx_batch_in_memory(),
         );
         (state, state_channel)
     }
@@ -123,7 +145,14 @@ pub mod tests {
         }
         .into();
         channel.send(msg).await.map_err(access_state_error).unwrap();
-        receiver.await.map_err(access_state_error).unwrap();
+        receiver.await.map_err(access_state_error).unwrap().unwrap();
+    }
+
+    /// Deployment data of the `counter` module at the address of [`PRIVATE_KEY`].
+    pub const COUNTER_MODULE: &str = "01fd01a11ceb0b0600000009010002020204030614051a0e07283d0865200a8501050c8a01490dd3010200000001080000020001000003000200000400030000050403000105010101030002060c0301070307636f756e74657207436f756e7465720e636f756e7465725f657869737473096765745f636f756e7409696e6372656d656e74077075626c69736801690000000000000000000000008fd379246834eac74b8419ffda202cf8051f7a0300020106030001000003030b00290002010100010003050b002b00100014020201040100050b0b002a000f000c010a0114060100000000000000160b0115020301040003050b000b0112002d0002000000";
+
+    pub async fn deploy_counter(channel: &Sender<StateMessage>) {
+        deploy_contract(Bytes::from_hex(COUNTER_MODULE).unwrap(), channel).await;
     }
 
     pub async fn deploy_contract(contract_bytes: Bytes, channel: &Sender<StateMessage>) {
@@ -156,7 +185,7 @@ pub mod tests {
         }
         .into();
         channel.send(msg).await.map_err(access_state_error).unwrap();
-        receiver.await.map_err(access_state_error).unwrap();
+        receiver.await.map_err(access_state_error).unwrap().unwrap();
     }
 
     pub fn create_state_actor_with_mock_state_queries(
@@ -186,7 +215,7 @@ pub mod tests {
                 "e56ec7ba741931e8c55b7f654a6e56ed61cf8b8279bf5e3ef6ac86a11eb33a9d"
             )),
             height,
-            GenesisConfig::default(),
+            GenesisConfig::dev(),
             0x03421ee50df45cacu64,
             MovedBlockHash,
             (),
@@ -0,0 +1,51 @@
+use {
+    crate::{jsonrpc::JsonRpcError, methods::chain_id},
+    moved::types::state::StateMessage,
+    tokio::sync::mpsc,
+};
+
+/// Returns the network id, which is the same as the chain id but formatted as a decimal string.
+pub async fn execute_version(
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<serde_json::Value, JsonRpcError> {
+    let response = chain_id::inner_execute(state_channel).await?;
+    Ok(
+        serde_json::to_value(response.to_string())
+            .expect("Must be able to JSON-serialize response"),
+    )
+}
+
+/// The node runs on its own so it is always listening and has no peers.
+pub fn execute_listening() -> Result<serde_json::Value, JsonRpcError> {
+    Ok(serde_json::Value::Bool(true))
+}
+
+pub fn execute_peer_count() -> Result<serde_json::Value, JsonRpcError> {
+    Ok(serde_json::to_value("0x0").expect("Must be able to JSON-serialize response"))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::methods::tests::create_state_actor};
+
+    #[tokio::test]
+    async fn test_execute_version() {
+        let (state, state_channel) = create_state_actor();
+        let state_handle = state.spawn();
+
+        let response = execute_version(state_channel).await.unwrap();
+
+        assert_eq!(response, serde_json::json!("404"));
+        state_handle.await.unwrap();
+    }
+
+    #[test]
+    fn test_execute_listening() {
+        assert_eq!(execute_listening().unwrap(), serde_json::json!(true));
+    }
+
+    #[test]
+    fn test_execute_peer_count() {
+        assert_eq!(execute_peer_count().unwrap(), serde_json::json!("0x0"));
+    }
+}
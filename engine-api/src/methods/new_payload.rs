@@ -4,9 +4,18 @@ use {
         jsonrpc::JsonRpcError,
         schema::{ExecutionPayloadV3, GetPayloadResponseV3, PayloadStatusV1, Status},
     },
+    alloy::{
+        consensus::TxEnvelope,
+        primitives::{keccak256, Bytes},
+        rlp::Decodable,
+    },
     moved::{
-        primitives::B256,
-        types::state::{Command, StateMessage},
+        move_execution::LogsBloom,
+        primitives::{B2048, B256, U64},
+        types::{
+            state::{Command, ExecutionOutcome, Query, StateMessage},
+            transactions::ExtendedTxEnvelope,
+        },
     },
     tokio::sync::{mpsc, oneshot},
 };
@@ -14,6 +23,17 @@ use {
 pub async fn execute_v3(
     request: serde_json::Value,
     state_channel: mpsc::Sender<StateMessage>,
+) -> Result<serde_json::Value, JsonRpcError> {
+    execute_v3_with_execution_checks(request, state_channel, false).await
+}
+
+/// Like [`execute_v3`], but with `check_execution` the payload transactions are executed again on
+/// top of the state of the parent block and the payload is rejected if its state root, receipts
+/// root or gas used differ from the outcome.
+pub async fn execute_v3_with_execution_checks(
+    request: serde_json::Value,
+    state_channel: mpsc::Sender<StateMessage>,
+    check_execution: bool,
 ) -> Result<serde_json::Value, JsonRpcError> {
     let (execution_payload, expected_blob_versioned_hashes, parent_beacon_block_root) =
         parse_params_v3(request)?;
@@ -22,6 +42,7 @@ pub async fn execute_v3(
         expected_blob_versioned_hashes,
         parent_beacon_block_root,
         state_channel,
+        check_execution,
     )
     .await?;
     Ok(serde_json::to_value(response).expect("Must be able to JSON-serialize response"))
@@ -60,9 +81,22 @@ async fn inner_execute_v3(
     expected_blob_versioned_hashes: Vec<B256>,
     parent_beacon_block_root: B256,
     state_channel: mpsc::Sender<StateMessage>,
+    check_execution: bool,
 ) -> Result<PayloadStatusV1, JsonRpcError> {
     // Spec: https://github.com/ethereum/execution-apis/blob/main/src/engine/cancun.md#specification
 
+    // Such a payload cannot have been built by this node, so it is rejected rather than reported
+    // as an unknown block.
+    if let Some(validation_error) =
+        validate_no_blobs(&execution_payload, &expected_blob_versioned_hashes)
+    {
+        return Ok(PayloadStatusV1 {
+            status: Status::Invalid,
+            latest_valid_hash: None,
+            validation_error: Some(validation_error),
+        });
+    }
+
     let (tx, rx) = oneshot::channel();
     let msg = Command::GetPayloadByBlockHash {
         block_hash: execution_payload.block_hash,
@@ -82,19 +116,110 @@ async fn inner_execute_v3(
         })?
         .into();
 
-    validate_payload(
-        execution_payload,
-        expected_blob_versioned_hashes,
+    let receipts_bloom =
+        receipts_logs_bloom(&response.execution_payload.transactions, &state_channel).await?;
+    let status = validate_payload(
+        execution_payload.clone(),
         parent_beacon_block_root,
         response,
-    )
+        receipts_bloom,
+    )?;
+    if status.status != Status::Valid || !check_execution {
+        return Ok(status);
+    }
+
+    let executed = execute_payload(&execution_payload, state_channel).await?;
+    let Some(validation_error) = validate_execution(&execution_payload, executed.as_ref()) else {
+        return Ok(status);
+    };
+    Ok(PayloadStatusV1 {
+        status: Status::Invalid,
+        latest_valid_hash: None,
+        validation_error: Some(validation_error.into()),
+    })
+}
+
+/// Aggregates the blooms of the logs in the receipts of the payload `transactions`, as stored by
+/// the node when the block was built.
+async fn receipts_logs_bloom(
+    transactions: &[Bytes],
+    state_channel: &mpsc::Sender<StateMessage>,
+) -> Result<B2048, JsonRpcError> {
+    let mut receipts = Vec::with_capacity(transactions.len());
+    for tx in transactions {
+        let (tx_sender, rx) = oneshot::channel();
+        let msg = Query::TransactionReceipt {
+            // The payload transactions are EIP-2718 encoded, which is what their hash is over
+            tx_hash: keccak256(tx),
+            response_channel: tx_sender,
+        }
+        .into();
+        state_channel.send(msg).await.map_err(access_state_error)?;
+        receipts.extend(rx.await.map_err(access_state_error)?);
+    }
+    let bloom = receipts
+        .iter()
+        .flat_map(|receipt| receipt.inner.inner.inner.logs())
+        .map(|log| &log.inner)
+        .logs_bloom();
+    Ok(bloom.into())
+}
+
+/// Executes the transactions of `execution_payload` again on top of the state of its parent block,
+/// `None` if the parent block is unknown.
+async fn execute_payload(
+    execution_payload: &ExecutionPayloadV3,
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<Option<ExecutionOutcome>, JsonRpcError> {
+    let (tx, rx) = oneshot::channel();
+    let msg = Query::ExecutePayload {
+        payload: execution_payload.clone().into(),
+        response_channel: tx,
+    }
+    .into();
+    state_channel.send(msg).await.map_err(access_state_error)?;
+    let parent_height = execution_payload.block_number.saturating_sub(U64::from(1));
+    rx.await
+        .map_err(access_state_error)?
+        .map_err(|e| json_utils::execution_error(e, parent_height))
+}
+
+/// Blobs are not supported (EIP-4844), so any payload making use of them is invalid.
+///
+/// Returns the validation error for the first blob related field found: the blob gas fields of
+/// the header, the expected blob versioned hashes or a blob transaction. All errors start with
+/// "Blobs are not supported".
+// TODO: Support blobs (low priority).
+fn validate_no_blobs(
+    execution_payload: &ExecutionPayloadV3,
+    expected_blob_versioned_hashes: &[B256],
+) -> Option<String> {
+    if !execution_payload.blob_gas_used.is_zero() || !execution_payload.excess_blob_gas.is_zero() {
+        return Some("Blobs are not supported: blob gas must be zero".into());
+    }
+
+    if !expected_blob_versioned_hashes.is_empty() {
+        return Some("Blobs are not supported: unexpected blob versioned hashes".into());
+    }
+
+    // Transactions that fail to decode are left to be rejected by the execution
+    execution_payload
+        .transactions
+        .iter()
+        .position(|bytes| {
+            matches!(
+                ExtendedTxEnvelope::decode(&mut bytes.as_ref()),
+                Ok(ExtendedTxEnvelope::Canonical(TxEnvelope::Eip4844(_)))
+            )
+        })
+        .map(|index| format!("Blobs are not supported: blob transaction at index {index}"))
 }
 
 fn validate_payload(
     execution_payload: ExecutionPayloadV3,
-    expected_blob_versioned_hashes: Vec<B256>,
     parent_beacon_block_root: B256,
     known_payload: GetPayloadResponseV3,
+    receipts_bloom: B2048,
 ) -> Result<PayloadStatusV1, JsonRpcError> {
     if execution_payload.block_number != known_payload.execution_payload.block_number {
         return Ok(PayloadStatusV1 {
@@ -160,18 +285,16 @@ fn validate_payload(
         });
     }
 
-    // TODO: validate execution relates fields once op-geth no longer used
-    // base_fee_per_gas, gas_used, logs_bool, receipts_root, state_root, transactions
-
-    // TODO: Support blobs (low priority).
-    if !expected_blob_versioned_hashes.is_empty() {
+    if execution_payload.logs_bloom != receipts_bloom {
         return Ok(PayloadStatusV1 {
             status: Status::Invalid,
             latest_valid_hash: None,
-            validation_error: Some("Unexpected blob hashes".into()),
+            validation_error: Some("Logs bloom does not match the receipts".into()),
         });
     }
 
+    // TODO: validate base_fee_per_gas too once op-geth no longer used
+
     if parent_beacon_block_root != known_payload.parent_beacon_block_root {
         return Ok(PayloadStatusV1 {
             status: Status::Invalid,
@@ -187,12 +310,37 @@ fn validate_payload(
     })
 }
 
+/// Compares the roots and gas used claimed by `execution_payload` with `executed`, the outcome of
+/// executing its transactions again, returning the validation error of the first mismatch.
+fn validate_execution(
+    execution_payload: &ExecutionPayloadV3,
+    executed: Option<&ExecutionOutcome>,
+) -> Option<&'static str> {
+    let Some(executed) = executed else {
+        return Some("Parent block is unknown");
+    };
+    if execution_payload.state_root != executed.state_root {
+        return Some("State root does not match the execution");
+    }
+    if execution_payload.receipts_root != executed.receipts_root {
+        return Some("Receipts root does not match the execution");
+    }
+    if execution_payload.gas_used != executed.gas_used {
+        return Some("Gas used does not match the execution");
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use {
         super::*,
         crate::methods::{forkchoice_updated, get_payload},
-        alloy::primitives::hex,
+        alloy::{
+            consensus::{SignableTransaction, TxEip4844},
+            primitives::hex,
+            rlp::Encodable,
+        },
         moved::{
             block::{
                 Block, BlockMemory, BlockRepository, Eip1559GasFee, InMemoryBlockQueries,
@@ -273,9 +421,128 @@ mod tests {
         assert_eq!(params, expected_params);
     }
 
+    #[test]
+    fn test_validate_payload_rejects_tampered_logs_bloom() {
+        let execution_payload = ExecutionPayloadV3 {
+            block_hash: B256::new(hex!(
+                "c013e1ff1b8bca9f0d074618cc9e661983bc91d7677168b156765781aee775d3"
+            )),
+            ..Default::default()
+        };
+        let known_payload = GetPayloadResponseV3 {
+            execution_payload: execution_payload.clone(),
+            block_value: U256::ZERO,
+            blobs_bundle: Default::default(),
+            should_override_builder: false,
+            parent_beacon_block_root: B256::ZERO,
+        };
+
+        // Untampered payload is valid
+        let status = validate_payload(
+            execution_payload.clone(),
+            B256::ZERO,
+            known_payload.clone(),
+            B2048::ZERO,
+        )
+        .unwrap();
+        assert_eq!(status.status, Status::Valid);
+
+        let mut tampered_payload = execution_payload;
+        tampered_payload.logs_bloom = B2048::with_last_byte(1);
+        let status =
+            validate_payload(tampered_payload, B256::ZERO, known_payload, B2048::ZERO).unwrap();
+
+        assert_eq!(status.status, Status::Invalid);
+        assert_eq!(status.latest_valid_hash, None);
+        assert_eq!(
+            status.validation_error.as_deref(),
+            Some("Logs bloom does not match the receipts")
+        );
+    }
+
+    async fn blob_rejection(
+        execution_payload: ExecutionPayloadV3,
+        expected_blob_versioned_hashes: Vec<B256>,
+    ) -> PayloadStatusV1 {
+        // The state actor is gone, so the payload must be rejected before looking it up
+        let (state_channel, _) = mpsc::channel(1);
+        inner_execute_v3(
+            execution_payload,
+            expected_blob_versioned_hashes,
+            B256::ZERO,
+            state_channel,
+            false,
+        )
+        .await
+        .unwrap()
+    }
+
     #[tokio::test]
-    async fn test_execute_v3() {
-        let genesis_config = GenesisConfig::default();
+    async fn test_payload_with_blob_gas_is_rejected() {
+        for execution_payload in [
+            ExecutionPayloadV3 {
+                blob_gas_used: U64::from(0x20000),
+                ..Default::default()
+            },
+            ExecutionPayloadV3 {
+                excess_blob_gas: U64::from(0x20000),
+                ..Default::default()
+            },
+        ] {
+            let status = blob_rejection(execution_payload, Vec::new()).await;
+
+            assert_eq!(status.status, Status::Invalid);
+            assert_eq!(status.latest_valid_hash, None);
+            assert_eq!(
+                status.validation_error.as_deref(),
+                Some("Blobs are not supported: blob gas must be zero")
+            );
+        }
+
+        let status = blob_rejection(Default::default(), vec![B256::repeat_byte(1)]).await;
+        assert_eq!(status.status, Status::Invalid);
+        assert_eq!(
+            status.validation_error.as_deref(),
+            Some("Blobs are not supported: unexpected blob versioned hashes")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_payload_with_blob_transaction_is_rejected() {
+        // Any signature will do, the transaction is rejected before recovering the signer
+        let encoded = hex!("02f86f82a45580808346a8928252089465d08a056c17ae13370565b04cf77d2afa1cb9fa8806f05b59d3b2000080c080a0dd50efde9a4d2f01f5248e1a983165c8cfa5f193b07b4b094f4078ad4717c1e4a017db1be1e8751b09e033bcffca982d0fe4919ff6b8594654e06647dee9292750");
+        let Ok(ExtendedTxEnvelope::Canonical(TxEnvelope::Eip1559(signed))) =
+            ExtendedTxEnvelope::decode(&mut encoded.as_slice())
+        else {
+            panic!("Transaction should decode as EIP-1559");
+        };
+        let blob_tx = TxEip4844 {
+            chain_id: 42069,
+            blob_versioned_hashes: vec![B256::repeat_byte(1)],
+            max_fee_per_blob_gas: 1,
+            ..Default::default()
+        }
+        .into_signed(*signed.signature());
+        let blob_tx = ExtendedTxEnvelope::Canonical(blob_tx.into());
+        let mut blob_tx_bytes = Vec::new();
+        blob_tx.encode(&mut blob_tx_bytes);
+        let execution_payload = ExecutionPayloadV3 {
+            transactions: vec![encoded.to_vec().into(), blob_tx_bytes.into()],
+            ..Default::default()
+        };
+
+        let status = blob_rejection(execution_payload, Vec::new()).await;
+
+        assert_eq!(status.status, Status::Invalid);
+        assert_eq!(status.latest_valid_hash, None);
+        assert_eq!(
+            status.validation_error.as_deref(),
+            Some("Blobs are not supported: blob transaction at index 1")
+        );
+    }
+
+    fn spawn_state_actor() -> (mpsc::Sender<StateMessage>, tokio::task::JoinHandle<()>) {
+        let genesis_config = GenesisConfig::dev();
         let (state_channel, rx) = mpsc::channel(10);
 
         // Set known block height
@@ -289,7 +556,7 @@ mod tests {
         repository.add(&mut block_memory, genesis_block);
 
         let mut state = InMemoryState::new();
-        let (changes, table_changes) = genesis::init(&genesis_config, &state);
+        let (changes, table_changes) = genesis::init(&genesis_config, &state).unwrap();
         genesis::apply(changes.clone(), table_changes, &genesis_config, &mut state);
         let initial_state_root = genesis_config.initial_state_root;
 
@@ -314,9 +581,11 @@ mod tests {
             moved::state_actor::StateActor::on_tx_noop(),
             moved::state_actor::StateActor::on_tx_batch_noop(),
         );
-        let state_handle = state.spawn();
+        (state_channel, state.spawn())
+    }
 
-        let fc_updated_request: serde_json::Value = serde_json::from_str(
+    fn fc_updated_request() -> serde_json::Value {
+        serde_json::from_str(
             r#"
                 {
                     "jsonrpc": "2.0",
@@ -343,8 +612,11 @@ mod tests {
                 }
         "#,
         )
-            .unwrap();
-        let get_payload_request: serde_json::Value = serde_json::from_str(
+            .unwrap()
+    }
+
+    fn get_payload_request() -> serde_json::Value {
+        serde_json::from_str(
             r#"
                 {
                     "jsonrpc": "2.0",
@@ -356,7 +628,12 @@ mod tests {
                 }
         "#,
         )
-        .unwrap();
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_execute_v3() {
+        let (state_channel, state_handle) = spawn_state_actor();
         let new_payload_request: serde_json::Value = serde_json::from_str(
             r#"
                 {
@@ -393,11 +670,11 @@ mod tests {
         )
         .unwrap();
 
-        forkchoice_updated::execute_v3(fc_updated_request, state_channel.clone())
+        forkchoice_updated::execute_v3(fc_updated_request(), state_channel.clone())
             .await
             .unwrap();
 
-        get_payload::execute_v3(get_payload_request, state_channel.clone())
+        get_payload::execute_v3(get_payload_request(), state_channel.clone())
             .await
             .unwrap();
 
@@ -419,4 +696,88 @@ mod tests {
         assert_eq!(response, expected_response);
         state_handle.await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_logs_bloom_is_checked_against_the_stored_receipts() {
+        let (state_channel, state_handle) = spawn_state_actor();
+        forkchoice_updated::execute_v3(fc_updated_request(), state_channel.clone())
+            .await
+            .unwrap();
+        let response = get_payload::execute_v3(get_payload_request(), state_channel.clone())
+            .await
+            .unwrap();
+        let built: GetPayloadResponseV3 = serde_json::from_value(response).unwrap();
+        let validate = |execution_payload| {
+            inner_execute_v3(
+                execution_payload,
+                Vec::new(),
+                built.parent_beacon_block_root,
+                state_channel.clone(),
+                false,
+            )
+        };
+
+        let status = validate(built.execution_payload.clone()).await.unwrap();
+        assert_eq!(status.status, Status::Valid);
+
+        let mut tampered_payload = built.execution_payload.clone();
+        tampered_payload.logs_bloom = B2048::with_last_byte(1);
+        let status = validate(tampered_payload).await.unwrap();
+        assert_eq!(status.status, Status::Invalid);
+        assert_eq!(
+            status.validation_error.as_deref(),
+            Some("Logs bloom does not match the receipts")
+        );
+
+        drop(state_channel);
+        state_handle.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_tampered_state_root_is_rejected_when_checking_execution() {
+        let (state_channel, state_handle) = spawn_state_actor();
+        forkchoice_updated::execute_v3(fc_updated_request(), state_channel.clone())
+            .await
+            .unwrap();
+        let response = get_payload::execute_v3(get_payload_request(), state_channel.clone())
+            .await
+            .unwrap();
+        let built: GetPayloadResponseV3 = serde_json::from_value(response).unwrap();
+        let validate = |execution_payload, check_execution| {
+            inner_execute_v3(
+                execution_payload,
+                Vec::new(),
+                built.parent_beacon_block_root,
+                state_channel.clone(),
+                check_execution,
+            )
+        };
+        let mut tampered_payload = built.execution_payload.clone();
+        tampered_payload.state_root = B256::repeat_byte(1);
+
+        // Executing the payload again gives the same outcome as building it
+        let status = validate(built.execution_payload.clone(), true)
+            .await
+            .unwrap();
+        assert_eq!(status.status, Status::Valid);
+        assert_eq!(
+            status.latest_valid_hash,
+            Some(built.execution_payload.block_hash)
+        );
+
+        // Execution fields are trusted unless checked
+        let status = validate(tampered_payload.clone(), false).await.unwrap();
+        assert_eq!(status.status, Status::Valid);
+
+        let status = validate(tampered_payload, true).await.unwrap();
+        assert_eq!(status.status, Status::Invalid);
+        assert_eq!(status.latest_valid_hash, None);
+        assert_eq!(
+            status.validation_error.as_deref(),
+            Some("State root does not match the execution")
+        );
+
+        drop(state_channel);
+        state_handle.await.unwrap();
+    }
 }
@@ -0,0 +1,98 @@
+use {
+    crate::{
+        json_utils::access_state_error, jsonrpc::JsonRpcError, methods::web3_client_version,
+        schema::NodeInfo,
+    },
+    moved::{
+        move_execution::evm_native::EVM_SPEC_ID,
+        primitives::U64,
+        types::state::{Query, StateMessage},
+    },
+    tokio::sync::{mpsc, oneshot},
+};
+
+pub async fn execute(
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<serde_json::Value, JsonRpcError> {
+    let response = inner_execute(state_channel).await?;
+    Ok(serde_json::to_value(response).expect("Must be able to JSON-serialize response"))
+}
+
+async fn inner_execute(
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<NodeInfo, JsonRpcError> {
+    let max_state_queue_depth = state_channel.max_capacity();
+    let state_queue_depth = max_state_queue_depth - state_channel.capacity();
+    let (response_channel, rx) = oneshot::channel();
+    let msg = Query::NodeSettings { response_channel }.into();
+    state_channel.send(msg).await.map_err(access_state_error)?;
+    let settings = rx.await.map_err(access_state_error)?;
+
+    Ok(NodeInfo {
+        chain_id: U64::from(settings.chain_id),
+        client_version: web3_client_version::client_version(),
+        evm_hard_fork: format!("{EVM_SPEC_ID:?}"),
+        state_retention: settings.state_retention.into(),
+        dev: settings.dev,
+        state_queue_depth: U64::from(state_queue_depth),
+        max_state_queue_depth: U64::from(max_state_queue_depth),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*, crate::methods::tests::create_state_actor_with_genesis,
+        move_core_types::account_address::AccountAddress, moved::genesis::config::GenesisConfig,
+    };
+
+    const JWT_SECRET: &str = "5ec7e75ec7e75ec7e75ec7e75ec7e75ec7e75ec7e75ec7e75ec7e75ec7e75ec7";
+
+    #[tokio::test]
+    async fn test_execute_returns_configuration_without_secrets() {
+        // The server reads its secret from this variable, so a known value can be looked for
+        std::env::set_var("JWT_SECRET", JWT_SECRET);
+        let genesis_config = GenesisConfig {
+            chain_id: 42069,
+            verify_state_root: false,
+            treasury: AccountAddress::TWO,
+            ..Default::default()
+        };
+        let (state, state_channel) = create_state_actor_with_genesis(genesis_config);
+        let state_handle = state.spawn();
+
+        let response = execute(state_channel).await.unwrap();
+
+        assert_eq!(
+            response,
+            serde_json::json!({
+                "chainId": "0xa455",
+                "clientVersion": web3_client_version::client_version(),
+                "evmHardFork": "CANCUN",
+                "stateRetention": { "mode": "archive" },
+                "dev": false,
+                "stateQueueDepth": "0x0",
+                "maxStateQueueDepth": "0xa",
+            })
+        );
+        let fields = response.as_object().unwrap();
+        for field in [
+            "chainId",
+            "clientVersion",
+            "evmHardFork",
+            "stateRetention",
+            "dev",
+            "stateQueueDepth",
+            "maxStateQueueDepth",
+        ] {
+            assert!(fields.contains_key(field), "Node info must contain {field}");
+        }
+        let response = response.to_string().to_lowercase();
+        assert!(
+            !response.contains(JWT_SECRET),
+            "Node info must not leak the JWT secret"
+        );
+        assert!(!response.contains("jwt"));
+        state_handle.await.unwrap();
+    }
+}
@@ -0,0 +1,19 @@
+use crate::jsonrpc::JsonRpcError;
+
+/// Version of the `eth` wire protocol, `eth/68` is the latest one supported by geth.
+const PROTOCOL_VERSION: u64 = 68;
+
+pub fn execute() -> Result<serde_json::Value, JsonRpcError> {
+    Ok(serde_json::to_value(format!("{PROTOCOL_VERSION:#x}"))
+        .expect("Must be able to JSON-serialize response"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_execute() {
+        assert_eq!(execute().unwrap(), serde_json::json!("0x44"));
+    }
+}
@@ -0,0 +1,126 @@
+use {
+    crate::{json_utils, json_utils::access_state_error, jsonrpc::JsonRpcError},
+    alloy::eips::BlockNumberOrTag,
+    move_core_types::{account_address::AccountAddress, language_storage::StructTag},
+    moved::types::state::{Query, StateMessage},
+    std::str::FromStr,
+    tokio::sync::{mpsc, oneshot},
+};
+
+pub async fn execute(
+    request: serde_json::Value,
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<serde_json::Value, JsonRpcError> {
+    let (address, resource, block_number) = parse_params(request)?;
+    let response = inner_execute(address, resource, block_number, state_channel).await?;
+
+    Ok(serde_json::to_value(response).expect("Must be able to JSON-serialize response"))
+}
+
+fn parse_params(
+    request: serde_json::Value,
+) -> Result<(AccountAddress, StructTag, BlockNumberOrTag), JsonRpcError> {
+    let params = json_utils::get_params_list(&request);
+    match params {
+        [] | [_] | [_, _] => Err(JsonRpcError::parse_error(request, "Not enough params")),
+        [a, b, c] => {
+            let address: AccountAddress = json_utils::deserialize(a)?;
+            let resource: String = json_utils::deserialize(b)?;
+            let Ok(resource) = StructTag::from_str(&resource) else {
+                return Err(JsonRpcError::parse_error(request, "Invalid resource type"));
+            };
+            let block_number: BlockNumberOrTag = json_utils::deserialize(c)?;
+            Ok((address, resource, block_number))
+        }
+        _ => Err(JsonRpcError::parse_error(request, "Too many params")),
+    }
+}
+
+async fn inner_execute(
+    address: AccountAddress,
+    resource: StructTag,
+    height: BlockNumberOrTag,
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<bool, JsonRpcError> {
+    let (tx, rx) = oneshot::channel();
+    let msg = Query::ResourceExists {
+        address,
+        resource,
+        height,
+        response_channel: tx,
+    }
+    .into();
+    state_channel.send(msg).await.map_err(access_state_error)?;
+    let response = rx
+        .await
+        .map_err(access_state_error)?
+        .map_err(|e| JsonRpcError::state_unavailable(e, height))?;
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::methods::tests::{create_state_actor, deploy_counter},
+    };
+
+    // Address of the `PRIVATE_KEY` signer deploying the contract
+    const SIGNER: &str = "0x8fd379246834eac74B8419FfdA202CF8051F7A03";
+    const ACCOUNT_RESOURCE: &str = "0x1::account::Account";
+
+    fn request(address: &str, resource: &str, block_number: &str) -> serde_json::Value {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "moved_resourceExists",
+            "params": [address, resource, block_number],
+            "id": 1
+        })
+    }
+
+    #[test]
+    fn test_parse_params() {
+        let (address, resource, block_number) =
+            parse_params(request("0x1", ACCOUNT_RESOURCE, "latest")).unwrap();
+        assert_eq!(address, AccountAddress::ONE);
+        assert_eq!(resource.to_canonical_string(), "0x1::account::Account");
+        assert_eq!(block_number, BlockNumberOrTag::Latest);
+
+        let err = parse_params(request("0x1", "account", "latest")).unwrap_err();
+        assert_eq!(err.message, "Invalid resource type");
+    }
+
+    #[tokio::test]
+    async fn test_execute_reports_resource_after_creation() {
+        let (state_actor, state_channel) = create_state_actor();
+        let state_handle = state_actor.spawn();
+
+        // 1. The account resource does not exist before the first transaction
+        let response = execute(
+            request(SIGNER, ACCOUNT_RESOURCE, "latest"),
+            state_channel.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response, serde_json::Value::Bool(false));
+
+        // 2. Sending a transaction creates the account of the sender
+        deploy_counter(&state_channel).await;
+        let response = execute(
+            request(SIGNER, ACCOUNT_RESOURCE, "latest"),
+            state_channel.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response, serde_json::Value::Bool(true));
+
+        // 3. The state before the transaction is unchanged
+        let response = execute(request(SIGNER, ACCOUNT_RESOURCE, "0x0"), state_channel)
+            .await
+            .unwrap();
+        assert_eq!(response, serde_json::Value::Bool(false));
+
+        state_handle.await.unwrap();
+    }
+}
@@ -3,19 +3,44 @@ use {
         json_utils::{self, access_state_error},
         jsonrpc::JsonRpcError,
     },
-    alloy::{consensus::transaction::TxEnvelope, rlp::Decodable},
+    alloy::{
+        consensus::{transaction::TxEnvelope, Transaction},
+        rlp::Decodable,
+    },
     moved::{
         primitives::{Bytes, B256},
-        types::state::{Command, StateMessage},
+        types::{
+            state::{Command, StateMessage},
+            transactions::SECP256K1N_HALF,
+        },
     },
-    tokio::sync::mpsc,
+    tokio::sync::mpsc::{self, error::TrySendError},
 };
 
 pub async fn execute(
     request: serde_json::Value,
     state_channel: mpsc::Sender<StateMessage>,
+) -> Result<serde_json::Value, JsonRpcError> {
+    execute_with_min_priority_fee(request, state_channel, 0).await
+}
+
+/// Like [`execute`], but rejects transactions tipping less than `min_priority_fee` wei per gas.
+/// The tip is the max priority fee of EIP-1559 transactions and the gas price of older ones.
+pub async fn execute_with_min_priority_fee(
+    request: serde_json::Value,
+    state_channel: mpsc::Sender<StateMessage>,
+    min_priority_fee: u128,
 ) -> Result<serde_json::Value, JsonRpcError> {
     let tx = parse_params(request)?;
+    let tip = tx.priority_fee_or_price();
+    if tip < min_priority_fee {
+        return Err(JsonRpcError::without_data(
+            -32000,
+            format!(
+                "transaction underpriced: tip {tip} is below the minimum of {min_priority_fee}"
+            ),
+        ));
+    }
     let response = inner_execute(tx, state_channel).await?;
     Ok(serde_json::to_value(response).expect("Must be able to JSON-serialize response"))
 }
@@ -36,6 +61,15 @@ fn parse_params(request: serde_json::Value) -> Result<TxEnvelope, JsonRpcError>
                 data: request,
                 message: format!("RLP decode failed: {e:?}"),
             })?;
+            // Reject malleable signatures before they reach the mem-pool (EIP-2)
+            if tx.signature().s() > SECP256K1N_HALF {
+                return Err(JsonRpcError {
+                    code: -32602,
+                    data: request,
+                    message: "Signature s value must be in the lower half of the curve order"
+                        .into(),
+                });
+            }
             Ok(tx)
         }
         _ => Err(JsonRpcError {
@@ -52,15 +86,36 @@ async fn inner_execute(
 ) -> Result<B256, JsonRpcError> {
     let tx_hash = tx.tx_hash().0.into();
 
+    // Reject instead of waiting for the queue to have room so that the request is not stuck
     let msg = Command::AddTransaction { tx }.into();
-    state_channel.send(msg).await.map_err(access_state_error)?;
+    state_channel.try_send(msg).map_err(|e| match e {
+        TrySendError::Full(_) => JsonRpcError::server_busy(),
+        e => access_state_error(e),
+    })?;
 
     Ok(tx_hash)
 }
 
 #[cfg(test)]
 pub mod tests {
-    use {super::*, crate::methods::tests::create_state_actor};
+    use {
+        super::*,
+        crate::methods::tests::{create_state_actor, PRIVATE_KEY},
+        alloy::{
+            consensus::{SignableTransaction, TxEip1559, TxEip2930, TxLegacy},
+            hex,
+            network::TxSignerSync,
+            primitives::{address, TxKind},
+            rlp::Encodable,
+            signers::local::PrivateKeySigner,
+        },
+        moved::{
+            genesis::config::CHAIN_ID,
+            types::state::{Command, Query},
+        },
+        test_case::test_case,
+        tokio::sync::oneshot,
+    };
 
     pub fn example_request() -> serde_json::Value {
         serde_json::from_str(
@@ -94,4 +149,167 @@ pub mod tests {
         assert_eq!(response, expected_response);
         state_handle.await.unwrap();
     }
+
+    /// Transaction types accepted by `eth_sendRawTransaction`.
+    #[derive(Debug, Clone, Copy)]
+    enum SupportedTxType {
+        Legacy,
+        Eip2930,
+        Eip1559,
+    }
+
+    fn signed_tx(tx_type: SupportedTxType) -> TxEnvelope {
+        let signer = PrivateKeySigner::from_bytes(&PRIVATE_KEY.into()).unwrap();
+        let to = TxKind::Call(address!("44223344556677889900ffeeaabbccddee111111"));
+        match tx_type {
+            SupportedTxType::Legacy => {
+                let mut tx = TxLegacy {
+                    chain_id: Some(CHAIN_ID),
+                    gas_limit: u64::MAX,
+                    to,
+                    ..Default::default()
+                };
+                let signature = signer.sign_transaction_sync(&mut tx).unwrap();
+                TxEnvelope::Legacy(tx.into_signed(signature))
+            }
+            SupportedTxType::Eip2930 => {
+                let mut tx = TxEip2930 {
+                    chain_id: CHAIN_ID,
+                    gas_limit: u64::MAX,
+                    to,
+                    ..Default::default()
+                };
+                let signature = signer.sign_transaction_sync(&mut tx).unwrap();
+                TxEnvelope::Eip2930(tx.into_signed(signature))
+            }
+            SupportedTxType::Eip1559 => {
+                let mut tx = TxEip1559 {
+                    chain_id: CHAIN_ID,
+                    gas_limit: u64::MAX,
+                    to,
+                    ..Default::default()
+                };
+                let signature = signer.sign_transaction_sync(&mut tx).unwrap();
+                TxEnvelope::Eip1559(tx.into_signed(signature))
+            }
+        }
+    }
+
+    #[test_case(SupportedTxType::Legacy; "legacy")]
+    #[test_case(SupportedTxType::Eip2930; "eip2930")]
+    #[test_case(SupportedTxType::Eip1559; "eip1559")]
+    #[tokio::test]
+    async fn test_execute_returns_hash_of_stored_transaction(tx_type: SupportedTxType) {
+        let (state, state_channel) = create_state_actor();
+        let state_handle = state.spawn();
+
+        let mut encoded = Vec::new();
+        signed_tx(tx_type).encode(&mut encoded);
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_sendRawTransaction",
+            "params": [hex::encode_prefixed(encoded)],
+        });
+        let response = execute(request, state_channel.clone()).await.unwrap();
+        let tx_hash: B256 = serde_json::from_value(response).unwrap();
+
+        let (response_channel, rx) = oneshot::channel();
+        let msg = Query::TransactionByHash {
+            tx_hash,
+            response_channel,
+        };
+        state_channel.send(msg.into()).await.unwrap();
+        let pending_tx = rx
+            .await
+            .unwrap()
+            .expect("Transaction should be in the mem-pool");
+        let pending_tx = serde_json::to_value(pending_tx).unwrap();
+        assert_eq!(pending_tx["hash"], serde_json::to_value(tx_hash).unwrap());
+
+        let (response_channel, rx) = oneshot::channel();
+        let msg = Command::StartBlockBuild {
+            payload_attributes: Default::default(),
+            response_channel,
+        };
+        state_channel.send(msg.into()).await.unwrap();
+        rx.await.unwrap().unwrap();
+
+        let (response_channel, rx) = oneshot::channel();
+        let msg = Query::TransactionReceipt {
+            tx_hash,
+            response_channel,
+        };
+        state_channel.send(msg.into()).await.unwrap();
+        let receipt = rx.await.unwrap().expect("Receipt should exist");
+        assert_eq!(receipt.inner.inner.transaction_hash, tx_hash);
+
+        drop(state_channel);
+        state_handle.await.unwrap();
+    }
+
+    fn tipped_request(max_priority_fee_per_gas: u128) -> serde_json::Value {
+        let signer = PrivateKeySigner::from_bytes(&PRIVATE_KEY.into()).unwrap();
+        let mut tx = TxEip1559 {
+            chain_id: CHAIN_ID,
+            gas_limit: u64::MAX,
+            max_fee_per_gas: max_priority_fee_per_gas,
+            max_priority_fee_per_gas,
+            to: TxKind::Call(address!("44223344556677889900ffeeaabbccddee111111")),
+            ..Default::default()
+        };
+        let signature = signer.sign_transaction_sync(&mut tx).unwrap();
+        let mut encoded = Vec::new();
+        TxEnvelope::Eip1559(tx.into_signed(signature)).encode(&mut encoded);
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_sendRawTransaction",
+            "params": [hex::encode_prefixed(encoded)],
+        })
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_transaction_below_min_priority_fee() {
+        let (state_channel, _rx) = mpsc::channel(1);
+
+        let err = execute_with_min_priority_fee(tipped_request(9), state_channel, 10)
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.code, -32000);
+        assert_eq!(
+            err.message,
+            "transaction underpriced: tip 9 is below the minimum of 10"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_accepts_transaction_at_min_priority_fee() {
+        let (state_channel, mut rx) = mpsc::channel(1);
+
+        let response = execute_with_min_priority_fee(tipped_request(10), state_channel, 10)
+            .await
+            .unwrap();
+        let tx_hash: B256 = serde_json::from_value(response).unwrap();
+
+        let Some(StateMessage::Command(Command::AddTransaction { tx })) = rx.recv().await else {
+            panic!("Transaction should be sent to the state actor");
+        };
+        assert_eq!(B256::from(tx.tx_hash().0), tx_hash);
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_transaction_when_queue_is_full() {
+        let (state_channel, _rx) = mpsc::channel(1);
+        let msg = Query::BlockNumber {
+            response_channel: oneshot::channel().0,
+        };
+        state_channel.send(msg.into()).await.unwrap();
+
+        let err = execute(example_request(), state_channel).await.unwrap_err();
+
+        assert_eq!(err.code, -32000);
+        assert_eq!(err.message, "Server busy");
+    }
 }
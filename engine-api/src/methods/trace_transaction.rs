@@ -0,0 +1,123 @@
+use {
+    crate::{
+        json_utils::{self, access_state_error, execution_error},
+        jsonrpc::JsonRpcError,
+    },
+    moved::{
+        move_execution::evm_native::{StructLoggerConfig, TransactionTrace},
+        primitives::B256,
+        types::state::{Query, StateMessage},
+    },
+    serde::Deserialize,
+    tokio::sync::{mpsc, oneshot},
+};
+
+/// Options of `debug_traceTransaction`, only the default (struct logger) tracer is supported.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TraceOptions {
+    tracer: Option<String>,
+    #[serde(flatten)]
+    config: StructLoggerConfig,
+}
+
+pub async fn execute(
+    request: serde_json::Value,
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<serde_json::Value, JsonRpcError> {
+    let (tx_hash, config) = parse_params(request)?;
+    let response = inner_execute(tx_hash, config, state_channel).await?;
+    Ok(serde_json::to_value(response).expect("Must be able to JSON-serialize response"))
+}
+
+async fn inner_execute(
+    tx_hash: B256,
+    config: StructLoggerConfig,
+    state_channel: mpsc::Sender<StateMessage>,
+) -> Result<Option<TransactionTrace>, JsonRpcError> {
+    let (response_channel, rx) = oneshot::channel();
+    let msg = Query::TraceTransaction {
+        tx_hash,
+        config,
+        response_channel,
+    }
+    .into();
+    state_channel.send(msg).await.map_err(access_state_error)?;
+    let response = rx.await.map_err(access_state_error)?;
+    response.map_err(|e| execution_error(e, format!("parent of {tx_hash}")))
+}
+
+fn parse_params(request: serde_json::Value) -> Result<(B256, StructLoggerConfig), JsonRpcError> {
+    let params = json_utils::get_params_list(&request);
+    let (tx_hash, options) = match params {
+        [] => return Err(JsonRpcError::parse_error(request, "Not enough params")),
+        [x] => (json_utils::deserialize(x)?, TraceOptions::default()),
+        [x, y] => (json_utils::deserialize(x)?, json_utils::deserialize(y)?),
+        _ => return Err(JsonRpcError::parse_error(request, "Too many params")),
+    };
+    if let Some(tracer) = options.tracer {
+        return Err(JsonRpcError::parse_error(
+            request,
+            format!("Unsupported tracer: {tracer}"),
+        ));
+    }
+    Ok((tx_hash, options.config))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::methods::tests::create_state_actor};
+
+    fn request(params: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "debug_traceTransaction",
+            "params": params,
+        })
+    }
+
+    #[test]
+    fn test_parse_params() {
+        let tx_hash = B256::repeat_byte(0x11);
+
+        let (actual_hash, config) = parse_params(request(serde_json::json!([tx_hash]))).unwrap();
+        assert_eq!(actual_hash, tx_hash);
+        assert_eq!(config, StructLoggerConfig::default());
+
+        let (_, config) = parse_params(request(serde_json::json!([
+            tx_hash,
+            { "disableStack": true, "disableStorage": true }
+        ])))
+        .unwrap();
+        assert_eq!(
+            config,
+            StructLoggerConfig {
+                disable_memory: false,
+                disable_stack: true,
+                disable_storage: true,
+            }
+        );
+
+        let error = parse_params(request(serde_json::json!([
+            tx_hash,
+            { "tracer": "callTracer" }
+        ])))
+        .unwrap_err();
+        assert_eq!(error.code, -32602);
+        assert_eq!(error.message, "Unsupported tracer: callTracer");
+    }
+
+    #[tokio::test]
+    async fn test_execute_unknown_transaction_is_null() {
+        let (state, state_channel) = create_state_actor();
+        let state_handle = state.spawn();
+
+        let response = execute(request(serde_json::json!([B256::ZERO])), state_channel)
+            .await
+            .unwrap();
+        assert!(response.is_null());
+
+        state_handle.await.unwrap();
+    }
+}
@@ -0,0 +1,28 @@
+use crate::jsonrpc::JsonRpcError;
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Set by the build script, missing when building outside a git checkout.
+const GIT_HASH: Option<&str> = option_env!("GIT_HASH");
+
+pub fn execute() -> Result<serde_json::Value, JsonRpcError> {
+    Ok(serde_json::to_value(client_version()).expect("Must be able to JSON-serialize response"))
+}
+
+pub fn client_version() -> String {
+    format!("op-move/v{VERSION}-{}", GIT_HASH.unwrap_or("unknown"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_execute() {
+        let response = execute().unwrap();
+
+        let version = response.as_str().unwrap();
+        assert!(version.starts_with(&format!("op-move/v{VERSION}-")));
+        assert!(!version.ends_with('-'));
+    }
+}
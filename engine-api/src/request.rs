@@ -5,17 +5,113 @@ use {
         method_name::MethodName,
     },
     moved::types::state::StateMessage,
-    tokio::sync::mpsc,
+    std::{collections::HashSet, sync::Arc, time::Duration},
+    tokio::sync::{mpsc, OwnedSemaphorePermit, Semaphore},
 };
 
+/// Maximum time spent on a request before it is abandoned with a timeout error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestTimeouts {
+    pub default: Duration,
+    /// Applies to the long-running `debug_*` methods instead of `default`.
+    pub debug: Duration,
+}
+
+impl Default for RequestTimeouts {
+    fn default() -> Self {
+        Self {
+            default: Duration::from_secs(30),
+            debug: Duration::from_secs(300),
+        }
+    }
+}
+
+impl RequestTimeouts {
+    pub fn for_method(&self, method_name: &str) -> Duration {
+        if method_name.starts_with("debug_") {
+            self.debug
+        } else {
+            self.default
+        }
+    }
+}
+
+/// The default maximum number of heavy read requests processed concurrently.
+pub const DEFAULT_MAX_HEAVY_READS: usize = 16;
+
+/// Bounds the number of requests to heavy read methods, see [`MethodName::is_heavy_read`], that
+/// are processed concurrently. Requests over the limit wait for a permit, requests to other
+/// methods are not limited.
+///
+/// Clones share the same permits.
+#[derive(Debug, Clone)]
+pub struct HeavyReadLimit {
+    permits: Arc<Semaphore>,
+    max: usize,
+}
+
+impl Default for HeavyReadLimit {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_HEAVY_READS)
+    }
+}
+
+impl HeavyReadLimit {
+    pub fn new(max: usize) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(max)),
+            max,
+        }
+    }
+
+    /// The number of heavy read requests currently being processed.
+    pub fn in_flight(&self) -> usize {
+        self.max - self.permits.available_permits()
+    }
+
+    /// Waits for a permit if `method` is a heavy read. The request is processed once the permit is
+    /// dropped.
+    async fn acquire(&self, method: &MethodName) -> Option<OwnedSemaphorePermit> {
+        if !method.is_heavy_read() {
+            return None;
+        }
+        let permit = self
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("Heavy read semaphore is never closed");
+
+        Some(permit)
+    }
+}
+
+/// Settings applied by [`handle`] to every request.
+#[derive(Debug, Clone, Default)]
+pub struct RequestConfig {
+    /// Methods rejected as if they did not exist.
+    pub disabled_methods: HashSet<MethodName>,
+    /// If processing takes longer than allowed the in-flight work is dropped, the time spent
+    /// waiting for the `read_limit` counts towards it.
+    pub timeouts: RequestTimeouts,
+    pub read_limit: HeavyReadLimit,
+    /// Transactions sent with `eth_sendRawTransaction` tipping less than this many wei per gas are
+    /// rejected as underpriced.
+    pub min_priority_fee: u128,
+    /// Whether `engine_newPayloadV3` executes payloads again to check the results they claim.
+    pub check_payload_execution: bool,
+}
+
+/// Handles the JSON-RPC `request` with the settings of `config`.
 pub async fn handle(
     request: serde_json::Value,
     state_channel: mpsc::Sender<StateMessage>,
+    config: &RequestConfig,
 ) -> JsonRpcResponse {
     let id = json_utils::get_field(&request, "id");
     let jsonrpc = json_utils::get_field(&request, "jsonrpc");
 
-    match inner_handle_request(request, state_channel).await {
+    match inner_handle_request(request, state_channel, config).await {
         Ok(r) => JsonRpcResponse {
             id,
             jsonrpc,
@@ -34,32 +130,240 @@ pub async fn handle(
 async fn inner_handle_request(
     request: serde_json::Value,
     state_channel: mpsc::Sender<StateMessage>,
+    config: &RequestConfig,
 ) -> Result<serde_json::Value, JsonRpcError> {
-    use {crate::methods::*, MethodName::*};
-
-    let method: MethodName = json_utils::get_field(&request, "method")
+    let method_name = json_utils::get_field(&request, "method");
+    let method_name = method_name
         .as_str()
-        .ok_or(JsonRpcError::without_data(-32601, "Invalid/missing method"))?
-        .parse()?;
+        .ok_or(JsonRpcError::without_data(-32601, "Invalid/missing method"))?;
+    let method: MethodName = method_name.parse()?;
+
+    if config.disabled_methods.contains(&method) {
+        return Err(JsonRpcError::without_data(
+            -32601,
+            format!("Method not found: {method_name}"),
+        ));
+    }
+
+    let timeout = config.timeouts.for_method(method_name);
+    let limited = async {
+        let _permit = config.read_limit.acquire(&method).await;
+        execute(method, request, state_channel, config).await
+    };
+    tokio::time::timeout(timeout, limited)
+        .await
+        .map_err(|_| JsonRpcError::request_timeout())?
+}
+
+async fn execute(
+    method: MethodName,
+    request: serde_json::Value,
+    state_channel: mpsc::Sender<StateMessage>,
+    config: &RequestConfig,
+) -> Result<serde_json::Value, JsonRpcError> {
+    use {crate::methods::*, MethodName::*};
 
     match method {
         ForkChoiceUpdatedV3 => forkchoice_updated::execute_v3(request, state_channel).await,
         GetPayloadV3 => get_payload::execute_v3(request, state_channel).await,
-        NewPayloadV3 => new_payload::execute_v3(request, state_channel).await,
-        SendRawTransaction => send_raw_transaction::execute(request, state_channel).await,
+        NewPayloadV3 => {
+            new_payload::execute_v3_with_execution_checks(
+                request,
+                state_channel,
+                config.check_payload_execution,
+            )
+            .await
+        }
+        SendRawTransaction => {
+            send_raw_transaction::execute_with_min_priority_fee(
+                request,
+                state_channel,
+                config.min_priority_fee,
+            )
+            .await
+        }
         ChainId => chain_id::execute(state_channel).await,
+        ProtocolVersion => protocol_version::execute(),
+        GetAccount => get_account::execute(request, state_channel).await,
         GetBalance => get_balance::execute(request, state_channel).await,
         GetNonce => get_nonce::execute(request, state_channel).await,
         GetBlockByHash => get_block_by_hash::execute(request, state_channel).await,
         GetBlockByNumber => get_block_by_number::execute(request, state_channel).await,
         BlockNumber => block_number::execute(request, state_channel).await,
         FeeHistory => fee_history::execute(request, state_channel).await,
+        GasPrice => gas_price::execute(request, state_channel).await,
         EstimateGas => estimate_gas::execute(request, state_channel).await,
         Call => call::execute(request, state_channel).await,
         TransactionReceipt => get_transaction_receipt::execute(request, state_channel).await,
+        TransactionByHash => get_transaction_by_hash::execute(request, state_channel).await,
+        RawTransactionByHash => get_raw_transaction_by_hash::execute(request, state_channel).await,
+        RawTransactionByBlockHashAndIndex => {
+            get_raw_transaction_by_block_hash_and_index::execute(request, state_channel).await
+        }
         GetProof => get_proof::execute(request, state_channel).await,
+        DepositStatus => deposit_status::execute(request, state_channel).await,
+        NodeInfo => node_info::execute(state_channel).await,
+        GetBlockRoots => get_block_roots::execute(request, state_channel).await,
+        ResourceExists => resource_exists::execute(request, state_channel).await,
+        TransactionsBySender => get_transactions_by_sender::execute(request, state_channel).await,
+        GetL1FeeParams => get_l1_fee_params::execute(request, state_channel).await,
+        GetRawBlock => get_raw_block::execute(request, state_channel).await,
+        GetRawHeader => get_raw_header::execute(request, state_channel).await,
+        TraceTransaction => trace_transaction::execute(request, state_channel).await,
+        NetVersion => net::execute_version(state_channel).await,
+        NetListening => net::execute_listening(),
+        NetPeerCount => net::execute_peer_count(),
+        ClientVersion => web3_client_version::execute(),
         ForkChoiceUpdatedV2 => todo!(),
         GetPayloadV2 => todo!(),
         NewPayloadV2 => todo!(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::methods::tests::create_state_actor};
+
+    #[tokio::test]
+    async fn test_disallowed_method_is_rejected() {
+        let (state_actor, state_channel) = create_state_actor();
+        let state_handle = state_actor.spawn();
+        let config = RequestConfig {
+            disabled_methods: HashSet::from([MethodName::ChainId]),
+            ..Default::default()
+        };
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "eth_chainId",
+            "params": [],
+            "id": 1
+        });
+        let response = handle(request, state_channel.clone(), &config).await;
+        let error = response.error.expect("Disallowed method should fail");
+
+        assert_eq!(error.code, -32601);
+        assert_eq!(error.message, "Method not found: eth_chainId");
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "eth_blockNumber",
+            "params": [],
+            "id": 2
+        });
+        let response = handle(request, state_channel, &config).await;
+
+        assert_eq!(response.result, Some(serde_json::json!("0x0")));
+        state_handle.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_evm_methods_are_not_found_on_move_only_chains() {
+        // The state actor is never spawned, disabled methods are rejected before reaching it
+        let (_state_actor, state_channel) = create_state_actor();
+        let config = RequestConfig {
+            disabled_methods: MethodName::EVM_SPECIFIC.into_iter().collect(),
+            ..Default::default()
+        };
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "eth_getProof",
+            "params": ["0x0000000000000000000000000000000000000001", [], "latest"],
+            "id": 1
+        });
+        let response = handle(request, state_channel, &config).await;
+        let error = response.error.expect("EVM method should fail");
+
+        assert_eq!(error.code, -32601);
+        assert_eq!(error.message, "Method not found: eth_getProof");
+    }
+
+    #[tokio::test]
+    async fn test_slow_request_times_out() {
+        // The state actor is never spawned so the request waits for a response forever
+        let (_state_actor, state_channel) = create_state_actor();
+        let config = RequestConfig {
+            timeouts: RequestTimeouts {
+                default: Duration::from_millis(10),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "eth_chainId",
+            "params": [],
+            "id": 1
+        });
+        let response = handle(request, state_channel, &config).await;
+        let error = response.error.expect("Slow request should fail");
+
+        assert_eq!(error.code, -32000);
+        assert_eq!(error.message, "Request timed out");
+    }
+
+    #[tokio::test]
+    async fn test_heavy_reads_are_limited() {
+        // The state actor is never spawned so heavy reads hold their permit forever
+        let (_state_actor, state_channel) = create_state_actor();
+        let config = RequestConfig {
+            read_limit: HeavyReadLimit::new(2),
+            ..Default::default()
+        };
+
+        let mut handles = Vec::new();
+        for id in 0..5 {
+            let request = serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "eth_getProof",
+                "params": ["0x4200000000000000000000000000000000000016", [], "latest"],
+                "id": id
+            });
+            let state_channel = state_channel.clone();
+            let config = config.clone();
+            handles.push(tokio::spawn(async move {
+                handle(request, state_channel, &config).await
+            }));
+        }
+        // The spawned requests run until they are all holding or waiting for a permit
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while config.read_limit.in_flight() < 2 {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("Heavy reads should take the permits");
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(config.read_limit.in_flight(), 2);
+
+        // Lightweight calls are not held up by the saturated limit
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "net_listening",
+            "params": [],
+            "id": 5
+        });
+        let response = handle(request, state_channel, &config).await;
+        assert_eq!(response.result, Some(serde_json::json!(true)));
+        assert_eq!(config.read_limit.in_flight(), 2);
+
+        for handle in handles {
+            handle.abort();
+        }
+    }
+
+    #[test]
+    fn test_debug_methods_use_debug_timeout() {
+        let timeouts = RequestTimeouts::default();
+
+        assert_eq!(
+            timeouts.for_method("debug_traceTransaction"),
+            timeouts.debug
+        );
+        assert_eq!(timeouts.for_method("eth_call"), timeouts.default);
+    }
+}
@@ -115,6 +115,8 @@ pub struct PayloadAttributesV3 {
     pub parent_beacon_block_root: B256,
     pub transactions: Vec<Bytes>,
     pub gas_limit: U64,
+    #[serde(default)]
+    pub no_tx_pool: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -315,6 +317,7 @@ impl From<PayloadAttributesV3> for Payload {
             parent_beacon_block_root: value.parent_beacon_block_root,
             transactions: value.transactions,
             gas_limit: value.gas_limit,
+            no_tx_pool: value.no_tx_pool,
         }
     }
 }
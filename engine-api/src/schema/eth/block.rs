@@ -1,7 +1,10 @@
 pub use alloy::eips::BlockNumberOrTag;
 
 use {
-    moved::types::state::{BlockResponse, RpcBlock},
+    moved::{
+        primitives::{B256, U64},
+        types::state::{BlockResponse, RpcBlock},
+    },
     serde::{Deserialize, Serialize},
 };
 
@@ -13,3 +16,28 @@ impl From<BlockResponse> for GetBlockResponse {
         Self(value.0)
     }
 }
+
+/// Roots committed to by the header of a block, for clients that verify state or receipts
+/// without downloading the block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockRoots {
+    pub hash: B256,
+    pub number: U64,
+    pub state_root: B256,
+    pub transactions_root: B256,
+    pub receipts_root: B256,
+}
+
+impl From<BlockResponse> for BlockRoots {
+    fn from(value: BlockResponse) -> Self {
+        let header = value.0.header;
+        Self {
+            hash: header.hash,
+            number: U64::from(header.number),
+            state_root: header.state_root,
+            transactions_root: header.transactions_root,
+            receipts_root: header.receipts_root,
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use {
+    moved::{
+        primitives::{B256, U64},
+        types::state::DepositInclusion,
+    },
+    serde::{Deserialize, Serialize},
+};
+
+/// Whether a deposit from L1 has been processed on L2.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum DepositStatus {
+    /// The deposit has not been included in any L2 block yet.
+    Pending,
+    /// The deposit was included as `transaction_hash` in the L2 block `block_hash`.
+    #[serde(rename_all = "camelCase")]
+    Processed {
+        transaction_hash: B256,
+        block_hash: B256,
+        block_number: U64,
+    },
+}
+
+impl From<Option<DepositInclusion>> for DepositStatus {
+    fn from(value: Option<DepositInclusion>) -> Self {
+        match value {
+            None => Self::Pending,
+            Some(inclusion) => Self::Processed {
+                transaction_hash: inclusion.tx_hash,
+                block_hash: inclusion.block_hash,
+                block_number: U64::from(inclusion.block_number),
+            },
+        }
+    }
+}
@@ -1,4 +1,6 @@
-pub use {block::*, withdrawal::*};
+pub use {block::*, deposit::*, node_info::*, withdrawal::*};
 
 mod block;
+mod deposit;
+mod node_info;
 mod withdrawal;
@@ -0,0 +1,42 @@
+use {
+    moved::{primitives::U64, state_actor::StateRetention},
+    serde::{Deserialize, Serialize},
+};
+
+/// Configuration of the node that contains no secrets, for debugging deployments.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeInfo {
+    pub chain_id: U64,
+    pub client_version: String,
+    /// EVM hard fork contracts are executed with.
+    pub evm_hard_fork: String,
+    pub state_retention: StateRetentionMode,
+    pub dev: bool,
+    /// Messages waiting for the state actor when the request was received, from a queue of at
+    /// most `maxStateQueueDepth` messages. Transactions are rejected while the queue is full.
+    pub state_queue_depth: U64,
+    pub max_state_queue_depth: U64,
+}
+
+/// For how long the state of past blocks remains available for queries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "camelCase")]
+pub enum StateRetentionMode {
+    Archive,
+    /// Only the state of the most recent `blocks` blocks is available.
+    Pruned {
+        blocks: U64,
+    },
+}
+
+impl From<StateRetention> for StateRetentionMode {
+    fn from(value: StateRetention) -> Self {
+        match value {
+            StateRetention::Archive => Self::Archive,
+            StateRetention::Pruned { blocks } => Self::Pruned {
+                blocks: U64::from(blocks),
+            },
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use {alloy::primitives::B256, std::collections::VecDeque};
+
+/// Hashes of the most recent blocks of the canonical chain, read by the EVM `BLOCKHASH` opcode.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockHashLookup {
+    /// Height of the oldest block in `hashes`.
+    first_height: u64,
+    /// Consecutive block hashes, the most recent last.
+    hashes: VecDeque<B256>,
+}
+
+impl BlockHashLookup {
+    /// Number of blocks preceding the current one that `BLOCKHASH` can read the hash of.
+    pub const WINDOW: u64 = 256;
+
+    /// Adds the `hash` of the block at `height`, which must directly follow the last added block.
+    ///
+    /// Only the hashes readable from the block after `height` and from `height` itself are kept.
+    pub fn push(&mut self, height: u64, hash: B256) {
+        debug_assert!(
+            self.hashes.is_empty() || height == self.first_height + self.hashes.len() as u64,
+            "Block hashes must be added in height order"
+        );
+        if self.hashes.is_empty() {
+            self.first_height = height;
+        }
+        self.hashes.push_back(hash);
+        while self.hashes.len() as u64 > Self::WINDOW + 1 {
+            self.hashes.pop_front();
+            self.first_height += 1;
+        }
+    }
+
+    /// Hash of the most recently added block.
+    pub fn last(&self) -> Option<B256> {
+        self.hashes.back().copied()
+    }
+
+    /// Returns the hash of block `number` as seen by `BLOCKHASH` executing in block `current`.
+    ///
+    /// Only the [`Self::WINDOW`] blocks before `current` are visible, the hash is zero for any
+    /// other block including `current` itself and the blocks after it.
+    pub fn get(&self, number: u64, current: u64) -> B256 {
+        let in_window = number < current && current - number <= Self::WINDOW;
+        match number.checked_sub(self.first_height) {
+            Some(index) if in_window => {
+                self.hashes.get(index as usize).copied().unwrap_or_default()
+            }
+            _ => B256::ZERO,
+        }
+    }
+}
@@ -3,15 +3,18 @@
 //! * Defines the structure of Ethereum blocks.
 //! * Implements an algorithm for producing its hashes.
 //! * Declares a collection of blocks in the node.
+//! * Keeps the recent block hashes visible to the EVM.
 
 mod gas;
 mod hash;
 mod in_memory;
+mod lookup;
 mod root;
 
 pub use {
     gas::{BaseGasFee, Eip1559GasFee},
     hash::{BlockHash, MovedBlockHash},
     in_memory::{BlockMemory, InMemoryBlockQueries, InMemoryBlockRepository},
+    lookup::BlockHashLookup,
     root::{Block, BlockQueries, BlockRepository, ExtendedBlock, Header, HeaderForExecution},
 };
@@ -1,10 +1,12 @@
 use {
     crate::{
+        block::BlockHashLookup,
         primitives::{B256, U256},
-        types::state::BlockResponse,
+        types::state::{BlockResponse, Withdrawal},
     },
+    alloy::rlp::{self, Encodable, EMPTY_LIST_CODE},
     op_alloy::consensus::OpTxEnvelope,
-    std::fmt::Debug,
+    std::{fmt::Debug, sync::Arc},
 };
 
 pub trait BlockQueries: Debug {
@@ -61,11 +63,12 @@ impl ExtendedBlock {
     }
 }
 
-/// TODO: Add withdrawals
 #[derive(Debug, Clone, Default)]
 pub struct Block {
     pub header: Header,
     pub transactions: Vec<OpTxEnvelope>,
+    /// Committed to by the `withdrawals_root` of the header.
+    pub withdrawals: Vec<Withdrawal>,
 }
 
 impl Block {
@@ -73,12 +76,56 @@ impl Block {
         Self {
             header,
             transactions,
+            withdrawals: Vec::new(),
+        }
+    }
+
+    pub fn with_withdrawals(self, withdrawals: Vec<Withdrawal>) -> Self {
+        Self {
+            withdrawals,
+            ..self
         }
     }
 
     pub fn with_hash(self, hash: B256) -> ExtendedBlock {
         ExtendedBlock::new(hash, U256::ZERO, self)
     }
+
+    /// Length of the RLP encoded block, which has no ommers on L2.
+    pub fn rlp_length(&self) -> usize {
+        let transactions = self.transactions.as_slice();
+        // The empty ommer list is a single byte
+        let mut payload_length =
+            self.header.length() + rlp::list_length::<_, OpTxEnvelope>(transactions) + 1;
+        if self.header.withdrawals_root.is_some() {
+            payload_length += rlp::list_length::<_, Withdrawal>(self.withdrawals.as_slice());
+        }
+        rlp::Header {
+            list: true,
+            payload_length,
+        }
+        .length()
+            + payload_length
+    }
+
+    /// The RLP encoded block, see [`Self::rlp_length`].
+    pub fn rlp_encoded(&self) -> Vec<u8> {
+        let mut payload = Vec::new();
+        self.header.encode(&mut payload);
+        rlp::encode_list::<_, OpTxEnvelope>(self.transactions.as_slice(), &mut payload);
+        payload.push(EMPTY_LIST_CODE);
+        if self.header.withdrawals_root.is_some() {
+            rlp::encode_list::<_, Withdrawal>(self.withdrawals.as_slice(), &mut payload);
+        }
+        let mut encoded = Vec::new();
+        rlp::Header {
+            list: true,
+            payload_length: payload.len(),
+        }
+        .encode(&mut encoded);
+        encoded.extend(payload);
+        encoded
+    }
 }
 
 /// A subset of the `Header` fields that are available while the transactions
@@ -88,4 +135,6 @@ pub struct HeaderForExecution {
     pub number: u64,
     pub timestamp: u64,
     pub prev_randao: B256,
+    /// Hashes of the blocks before this one, read by the EVM `BLOCKHASH` opcode.
+    pub block_hashes: Arc<BlockHashLookup>,
 }
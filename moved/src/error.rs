@@ -9,9 +9,14 @@
 //! [`Display`] trait, they serve only an informative purpose and a human-readable representation.   
 
 use {
+    crate::state_actor::StateUnavailable,
     alloy::consensus::TxType,
     move_binary_format::errors::{PartialVMError, VMError},
-    move_core_types::language_storage::TypeTag,
+    move_core_types::{
+        identifier::Identifier,
+        language_storage::{ModuleId, TypeTag},
+    },
+    std::fmt,
     thiserror::Error,
 };
 
@@ -74,6 +79,64 @@ pub enum UserError {
     DepositFailure(Vec<u8>),
     #[error("L2 contract call failure")]
     L2ContractCallFailure,
+    #[error("{0}")]
+    StateUnavailable(#[from] StateUnavailable),
+    #[error("Invalid transaction forced by the payload attributes: {0}")]
+    InvalidForcedTransaction(InvalidTransactionCause),
+    #[error("Deposit rejected by the node settings: {0}")]
+    RejectedDeposit(&'static str),
+    #[error("{0}")]
+    MoveAbort(MoveAbort),
+    #[error("Gas required exceeds the estimation cap of {0}")]
+    GasCapExceeded(u64),
+}
+
+/// An `abort` raised by Move code, decoded from the [`VMError`] by
+/// [`crate::move_execution::decode_abort`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoveAbort {
+    pub module: ModuleId,
+    /// The aborting function, unknown if its module cannot be read.
+    pub function: Option<Identifier>,
+    pub code: u64,
+    /// Human-readable description of the `code` if it is a known framework abort.
+    pub reason: Option<&'static str>,
+}
+
+impl MoveAbort {
+    /// The category of the `code` as defined by `std::error`, e.g. `INVALID_ARGUMENT`.
+    pub fn category(&self) -> Option<&'static str> {
+        Some(match self.code >> 16 {
+            0x1 => "INVALID_ARGUMENT",
+            0x2 => "OUT_OF_RANGE",
+            0x3 => "INVALID_STATE",
+            0x4 => "UNAUTHENTICATED",
+            0x5 => "PERMISSION_DENIED",
+            0x6 => "NOT_FOUND",
+            0x7 => "ABORTED",
+            0x8 => "ALREADY_EXISTS",
+            0x9 => "RESOURCE_EXHAUSTED",
+            0xA => "CANCELLED",
+            0xB => "INTERNAL",
+            0xC => "NOT_IMPLEMENTED",
+            0xD => "UNAVAILABLE",
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for MoveAbort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Move abort in {}", self.module.short_str_lossless())?;
+        if let Some(function) = &self.function {
+            write!(f, "::{function}")?;
+        }
+        write!(f, " with code {:#x}", self.code)?;
+        if let Some(reason) = self.reason {
+            write!(f, ": {reason}")?;
+        }
+        Ok(())
+    }
 }
 
 /// The error caused by invalid transaction input parameter.
@@ -115,6 +178,16 @@ pub enum InvalidTransactionCause {
     FailedToPayL1Fee,
     #[error("Failed to pay L2 fee")]
     FailedToPayL2Fee,
+    #[error("EVM is disabled on this chain")]
+    EvmDisabled,
+    #[error("Signature s value must be in the lower half of the curve order")]
+    NonCanonicalSignature,
+    #[error("Transactions without a chain id are not accepted")]
+    UnprotectedTransaction,
+    #[error("Module size {size} exceeds the maximum of {max} bytes")]
+    ModuleTooLarge { size: usize, max: usize },
+    #[error("Module depends on {count} modules, exceeding the maximum of {max}")]
+    TooManyModuleDependencies { count: usize, max: usize },
 }
 
 impl From<InvalidTransactionCause> for Error {
@@ -171,6 +244,8 @@ pub enum EthToken {
     GetBalanceReturnsU256,
     #[error("Unused gas refund should never fail")]
     RefundAlwaysSucceeds,
+    #[error("Paying out collected fees should never fail")]
+    PayFeeAlwaysSucceeds,
 }
 
 #[derive(Debug, Error)]
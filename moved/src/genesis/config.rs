@@ -1,5 +1,5 @@
 use {
-    crate::primitives::B256,
+    crate::{move_execution::evm_native::DEFAULT_EVM_MEMORY_LIMIT, primitives::B256},
     alloy::primitives::hex,
     aptos_gas_schedule::{InitialGasSchedule, VMGasParameters},
     aptos_vm_types::storage::StorageGasParameters,
@@ -9,6 +9,15 @@ use {
 
 pub const CHAIN_ID: u64 = 404;
 
+/// Treasury of the default config, only accepted on development chains.
+pub const PLACEHOLDER_TREASURY: AccountAddress = AccountAddress::ONE;
+
+/// Default limit of the serialized size in bytes of a deployed Move module.
+pub const DEFAULT_MAX_MODULE_SIZE: usize = 64 * 1024;
+
+/// Default limit of the number of other modules a deployed Move module may depend on.
+pub const DEFAULT_MAX_MODULE_DEPENDENCIES: usize = 128;
+
 #[derive(Debug, Clone)]
 pub struct GasCosts {
     pub vm: VMGasParameters,
@@ -18,12 +27,40 @@ pub struct GasCosts {
 
 #[derive(Debug, Clone)]
 pub struct GenesisConfig {
+    /// Returned by `eth_chainId` and required in the signature of every canonical transaction.
     pub chain_id: u64,
     pub initial_state_root: B256,
     pub gas_costs: GasCosts,
+    /// Account collecting the fees of every transaction before they are paid out at the end of
+    /// the block. Must not be [`PLACEHOLDER_TREASURY`] unless `dev` is enabled.
     pub treasury: AccountAddress,
     // TODO: the genesis config should be self-contained instead of referring to an external file.
     pub l2_contract_genesis: PathBuf,
+    /// When `false` the computed genesis state root is accepted instead of being checked against
+    /// `initial_state_root`. Useful for bootstrapping new dev chains where the root is not known.
+    pub verify_state_root: bool,
+    /// When `true` placeholder settings meant for local development chains are accepted.
+    pub dev: bool,
+    /// Timestamp of the genesis block, can be pinned to make test chains reproducible.
+    pub timestamp: u64,
+    /// When `false` the chain runs Move only: the EVM natives are not registered, the L2
+    /// contracts are not deployed and transactions reaching the EVM are rejected.
+    pub evm_native: bool,
+    /// When `true` legacy transactions signed without a chain id (pre EIP-155) are accepted,
+    /// they can be replayed on any other chain accepting them.
+    pub allow_unprotected_txs: bool,
+    /// Opcodes reverting the EVM call executing them, e.g. `0xff` to disable `SELFDESTRUCT`.
+    pub disabled_evm_opcodes: Vec<u8>,
+    /// Bytes of memory a single EVM call may expand to, exceeding it halts the call as out of gas.
+    pub evm_memory_limit: u64,
+    /// Custom Move frameworks deployed after the default Aptos and Sui frameworks. Each path is
+    /// either a release bundle file (`.mrb`) or a directory of compiled modules (`.mv`).
+    pub custom_frameworks: Vec<PathBuf>,
+    /// Module deployments larger than this many bytes are rejected before reaching the VM.
+    pub max_module_size: usize,
+    /// Module deployments depending on more than this many other modules are rejected before
+    /// reaching the VM.
+    pub max_module_dependencies: usize,
 }
 
 impl Default for GasCosts {
@@ -36,6 +73,16 @@ impl Default for GasCosts {
     }
 }
 
+impl GenesisConfig {
+    /// Default config of a local development chain, accepting the placeholder treasury.
+    pub fn dev() -> Self {
+        Self {
+            dev: true,
+            ..Default::default()
+        }
+    }
+}
+
 impl Default for GenesisConfig {
     fn default() -> Self {
         Self {
@@ -44,8 +91,18 @@ impl Default for GenesisConfig {
                 "b9450794e6d37656082d40239a6e40d93a8865b7813a8a26d2e9706e8de74863"
             )),
             gas_costs: GasCosts::default(),
-            treasury: AccountAddress::ONE, // todo: fill in the real address
+            treasury: PLACEHOLDER_TREASURY,
             l2_contract_genesis: Path::new("../moved/src/tests/res/l2_genesis_tests.json").into(),
+            verify_state_root: true,
+            dev: false,
+            timestamp: 0,
+            evm_native: true,
+            allow_unprotected_txs: false,
+            disabled_evm_opcodes: Vec::new(),
+            evm_memory_limit: DEFAULT_EVM_MEMORY_LIMIT,
+            custom_frameworks: Vec::new(),
+            max_module_size: DEFAULT_MAX_MODULE_SIZE,
+            max_module_dependencies: DEFAULT_MAX_MODULE_DEPENDENCIES,
         }
     }
 }
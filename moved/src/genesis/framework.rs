@@ -3,7 +3,7 @@ use {
     alloy::primitives::address,
     aptos_framework::ReleaseBundle,
     aptos_table_natives::{NativeTableContext, TableChange, TableChangeSet},
-    move_binary_format::errors::PartialVMError,
+    move_binary_format::{errors::PartialVMError, CompiledModule},
     move_core_types::{
         account_address::AccountAddress,
         effects::{ChangeSet, Op},
@@ -18,7 +18,11 @@ use {
     },
     move_vm_types::gas::UnmeteredGasMeter,
     once_cell::sync::Lazy,
-    std::{collections::BTreeMap, fs, path::PathBuf},
+    std::{
+        collections::{BTreeMap, BTreeSet},
+        fs, io,
+        path::{Path, PathBuf},
+    },
     sui_framework::SystemPackage,
     sui_types::base_types::ObjectID,
 };
@@ -55,6 +59,30 @@ static SUI_SYSTEM_PACKAGES: Lazy<BTreeMap<ObjectID, SystemPackage>> = Lazy::new(
     snapshot.into_iter().map(|pkg| (pkg.id, pkg)).collect()
 });
 
+/// Reasons the genesis frameworks cannot be deployed.
+#[derive(Debug, thiserror::Error)]
+pub enum FrameworkError {
+    #[error("Custom framework {} cannot be read: {source}", .path.display())]
+    Read { path: PathBuf, source: io::Error },
+    #[error("Custom framework {} cannot be decoded: {reason}", .path.display())]
+    Decode { path: PathBuf, reason: String },
+    #[error("Custom framework {} contains a package without modules", .path.display())]
+    EmptyPackage { path: PathBuf },
+    #[error(
+        "Custom framework module {module} depends on {dependency}, \
+        which is neither in its bundle nor deployed before it"
+    )]
+    MissingDependency {
+        module: ModuleId,
+        dependency: ModuleId,
+    },
+    #[error("Framework modules cannot be deployed: {0}")]
+    Deploy(#[from] crate::Error),
+}
+
+/// Modules of a custom framework grouped by the address they are published to.
+type CustomFramework = Vec<(AccountAddress, Vec<(Vec<u8>, CompiledModule)>)>;
+
 const fn eth_address(value: &[u8; 20]) -> AccountAddress {
     let mut buf = [0u8; 32];
     let mut i = 0;
@@ -85,12 +113,13 @@ pub fn load_sui_framework_snapshot() -> &'static BTreeMap<ObjectID, SystemPackag
     &SUI_SYSTEM_PACKAGES
 }
 
-/// Initializes the blockchain state with Aptos and Sui frameworks.
+/// Initializes the blockchain state with Aptos and Sui frameworks, followed by the
+/// `custom_frameworks`.
 pub fn init_state(
+    custom_frameworks: &[PathBuf],
     state: &impl State<Err = PartialVMError>,
-) -> (ChangeSet, move_table_extension::TableChangeSet) {
-    let (change_set, table_change_set) =
-        deploy_framework(state).expect("All bundle modules should be valid");
+) -> Result<(ChangeSet, move_table_extension::TableChangeSet), FrameworkError> {
+    let (change_set, table_change_set) = deploy_framework(custom_frameworks, state)?;
 
     // This function converts a `TableChange` to a move table extension struct.
     // InMemoryStorage relies on this conversion to apply the storage changes correctly.
@@ -120,10 +149,24 @@ pub fn init_state(
             .collect(),
     };
 
-    (change_set, table_change_set)
+    Ok((change_set, table_change_set))
 }
 
 fn deploy_framework(
+    custom_frameworks: &[PathBuf],
+    state: &impl State<Err = PartialVMError>,
+) -> Result<(ChangeSet, TableChangeSet), FrameworkError> {
+    let custom_frameworks = custom_frameworks
+        .iter()
+        .map(|path| load_custom_framework(path))
+        .collect::<Result<Vec<_>, _>>()?;
+    check_dependencies(&custom_frameworks)?;
+
+    Ok(publish_framework(&custom_frameworks, state)?)
+}
+
+fn publish_framework(
+    custom_frameworks: &[CustomFramework],
     state: &impl State<Err = PartialVMError>,
 ) -> crate::Result<(ChangeSet, TableChangeSet)> {
     let vm = create_move_vm()?;
@@ -135,6 +178,9 @@ fn deploy_framework(
 
     deploy_aptos_framework(&mut session)?;
     deploy_sui_framework(&mut session)?;
+    for framework in custom_frameworks {
+        deploy_custom_framework(&mut session, framework)?;
+    }
     initialize_eth_token(&mut session, &mut traversal_context)?;
 
     let (change_set, mut extensions) = session.finish_with_extensions()?;
@@ -221,6 +267,111 @@ fn deploy_sui_framework(session: &mut Session) -> crate::Result<()> {
     Ok(())
 }
 
+fn deploy_custom_framework(
+    session: &mut Session,
+    framework: &CustomFramework,
+) -> crate::Result<()> {
+    for (sender, modules) in framework {
+        let code = modules.iter().map(|(code, _)| code.clone()).collect();
+        session.publish_module_bundle(code, *sender, &mut UnmeteredGasMeter)?;
+    }
+    Ok(())
+}
+
+/// Reads the modules of the custom framework at `path` grouped by the address they are published
+/// to, in publishing order. Packages of a release bundle keep their order, modules of a directory
+/// are published by ascending address.
+fn load_custom_framework(path: &Path) -> Result<CustomFramework, FrameworkError> {
+    let read_error = |source| FrameworkError::Read {
+        path: path.to_path_buf(),
+        source,
+    };
+    let decode_error = |reason: String| FrameworkError::Decode {
+        path: path.to_path_buf(),
+        reason,
+    };
+
+    if path.is_dir() {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(path).map_err(read_error)? {
+            let file = entry.map_err(read_error)?.path();
+            if file.extension().is_some_and(|ext| ext == "mv") {
+                files.push(file);
+            }
+        }
+        files.sort();
+
+        let mut modules: BTreeMap<AccountAddress, Vec<(Vec<u8>, CompiledModule)>> = BTreeMap::new();
+        for file in files {
+            let code = fs::read(&file).map_err(read_error)?;
+            let module = CompiledModule::deserialize(&code)
+                .map_err(|e| decode_error(format!("{}: {e}", file.display())))?;
+            modules
+                .entry(*module.self_id().address())
+                .or_default()
+                .push((code, module));
+        }
+        Ok(modules.into_iter().collect())
+    } else {
+        let binary = fs::read(path).map_err(read_error)?;
+        let bundle: ReleaseBundle =
+            bcs::from_bytes(&binary).map_err(|e| decode_error(e.to_string()))?;
+        bundle
+            .packages
+            .iter()
+            .map(|package| {
+                let modules = package.sorted_code_and_modules();
+                let sender = modules
+                    .first()
+                    .ok_or_else(|| FrameworkError::EmptyPackage {
+                        path: path.to_path_buf(),
+                    })?;
+                let sender = *sender.1.self_id().address();
+                let modules = modules
+                    .into_iter()
+                    .map(|(code, module)| (code.to_vec(), module.clone()))
+                    .collect();
+                Ok((sender, modules))
+            })
+            .collect()
+    }
+}
+
+/// Checks that every custom framework module only depends on modules of its own bundle, the
+/// Aptos and Sui frameworks or the custom frameworks deployed before it.
+fn check_dependencies(custom_frameworks: &[CustomFramework]) -> Result<(), FrameworkError> {
+    let mut known_modules: BTreeSet<ModuleId> = load_aptos_framework_snapshot()
+        .code_and_compiled_modules()
+        .into_iter()
+        .map(|(_, module)| module.self_id())
+        .chain(
+            load_sui_framework_snapshot()
+                .values()
+                .flat_map(|pkg| pkg.modules())
+                .map(|module| module.self_id()),
+        )
+        .collect();
+
+    for framework in custom_frameworks {
+        let modules = framework.iter().flat_map(|(_, modules)| modules);
+        known_modules.extend(modules.clone().map(|(_, module)| module.self_id()));
+
+        for (_, module) in modules {
+            let missing = module
+                .immediate_dependencies()
+                .into_iter()
+                .find(|dependency| !known_modules.contains(dependency));
+            if let Some(dependency) = missing {
+                return Err(FrameworkError::MissingDependency {
+                    module: module.self_id(),
+                    dependency,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use {super::*, crate::storage::InMemoryState};
@@ -243,7 +394,43 @@ mod tests {
         assert_eq!(sui_framework_len, SUI_MODULES_LEN);
 
         let state = InMemoryState::new();
-        let (change_set, _) = deploy_framework(&state).unwrap();
+        let (change_set, _) = deploy_framework(&[], &state).unwrap();
         assert_eq!(change_set.modules().count(), TOTAL_MODULES_LEN);
     }
+
+    #[test]
+    fn test_deploy_framework_rejects_missing_dependency() {
+        // Moving `coin` away from the framework address leaves its framework dependencies behind
+        let custom_address = small_account_address(0x77);
+        let mut module = load_aptos_framework_snapshot()
+            .code_and_compiled_modules()
+            .into_iter()
+            .map(|(_, module)| module.clone())
+            .find(|module| {
+                module.self_id() == ModuleId::new(FRAMEWORK_ADDRESS, ident_str!("coin").into())
+            })
+            .unwrap();
+        for address in &mut module.address_identifiers {
+            if *address == FRAMEWORK_ADDRESS {
+                *address = custom_address;
+            }
+        }
+        let mut code = Vec::new();
+        module.serialize(&mut code).unwrap();
+        let framework_dir = std::env::temp_dir().join("moved_test_missing_dependency");
+        fs::create_dir_all(&framework_dir).unwrap();
+        fs::write(framework_dir.join("coin.mv"), code).unwrap();
+
+        let state = InMemoryState::new();
+        let err = deploy_framework(&[framework_dir], &state).unwrap_err();
+
+        match err {
+            FrameworkError::MissingDependency { module, dependency } => {
+                assert_eq!(module.address(), &custom_address);
+                assert_eq!(dependency.address(), &custom_address);
+                assert_ne!(module, dependency);
+            }
+            other => panic!("Unexpected error: {other}"),
+        }
+    }
 }
@@ -1,49 +1,66 @@
 use {
-    self::config::GenesisConfig, crate::storage::State, move_binary_format::errors::PartialVMError,
-    move_core_types::effects::ChangeSet, move_table_extension::TableChangeSet,
+    self::config::{GenesisConfig, PLACEHOLDER_TREASURY},
+    crate::{primitives::B256, storage::State},
+    move_binary_format::errors::PartialVMError,
+    move_core_types::effects::ChangeSet,
+    move_table_extension::TableChangeSet,
 };
 
-pub use framework::FRAMEWORK_ADDRESS;
+pub use framework::{FrameworkError, FRAMEWORK_ADDRESS};
 
 pub mod config;
 mod framework;
 mod l2_contracts;
 
+/// Computes the genesis changes of the chain configured by `config`.
+///
+/// Fails if a custom framework cannot be loaded or depends on modules that are not deployed
+/// before it. Panics if [`GenesisConfig::treasury`] is left at the placeholder outside of dev mode.
 pub fn init(
     config: &GenesisConfig,
     state: &impl State<Err = PartialVMError>,
-) -> (ChangeSet, TableChangeSet) {
-    // Read L2 contract data
-    let l2_genesis_file = std::fs::File::open(&config.l2_contract_genesis)
-        .expect("L2 contracts genesis file must exist");
-    let l2_contract_genesis =
-        serde_json::from_reader(l2_genesis_file).expect("L2 genesis file must parse successfully");
+) -> Result<(ChangeSet, TableChangeSet), FrameworkError> {
+    assert!(
+        config.dev || config.treasury != PLACEHOLDER_TREASURY,
+        "Fatal Error: Genesis treasury must be set outside of dev mode"
+    );
 
     let mut changes = ChangeSet::new();
 
-    // Deploy Move/Aptos/Sui frameworks
-    let (changes_framework, table_changes) = framework::init_state(state);
-
-    // Deploy OP stack L2 contracts
-    let changes_l2 = l2_contracts::init_state(l2_contract_genesis, state);
+    // Deploy Move/Aptos/Sui frameworks and any custom ones
+    let (changes_framework, table_changes) =
+        framework::init_state(&config.custom_frameworks, state)?;
 
     changes
         .squash(changes_framework)
         .expect("Framework changes should not be in conflict");
 
-    changes
-        .squash(changes_l2)
-        .expect("L2 contract changes should not be in conflict");
+    // Deploy OP stack L2 contracts, which only run in the EVM
+    if config.evm_native {
+        let l2_genesis_file = std::fs::File::open(&config.l2_contract_genesis)
+            .expect("L2 contracts genesis file must exist");
+        let l2_contract_genesis = serde_json::from_reader(l2_genesis_file)
+            .expect("L2 genesis file must parse successfully");
+        let changes_l2 = l2_contracts::init_state(l2_contract_genesis, state);
 
-    (changes, table_changes)
+        changes
+            .squash(changes_l2)
+            .expect("L2 contract changes should not be in conflict");
+    }
+
+    Ok((changes, table_changes))
 }
 
+/// Applies the genesis `changes` to the `state` and returns the resulting state root.
+///
+/// Panics if the state root does not match [`GenesisConfig::initial_state_root`], unless
+/// [`GenesisConfig::verify_state_root`] is disabled.
 pub fn apply(
     changes: ChangeSet,
     table_changes: TableChangeSet,
     config: &GenesisConfig,
     state: &mut impl State<Err = PartialVMError>,
-) {
+) -> B256 {
     state
         .apply_with_tables(changes, table_changes)
         .expect("Changes should be applicable");
@@ -52,13 +69,64 @@ pub fn apply(
     let actual_state_root = state.state_root();
     let expected_state_root = config.initial_state_root;
 
-    assert_eq!(
-        actual_state_root, expected_state_root,
-        "Fatal Error: Genesis state root mismatch"
-    );
+    if config.verify_state_root {
+        assert_eq!(
+            actual_state_root, expected_state_root,
+            "Fatal Error: Genesis state root mismatch"
+        );
+    } else {
+        println!("WARN: Accepting unverified genesis state root: {actual_state_root}");
+    }
+
+    actual_state_root
 }
 
+/// Computes and applies the genesis changes in one go.
+///
+/// Panics if [`init`] fails or [`apply`] panics.
 pub fn init_and_apply(config: &GenesisConfig, state: &mut impl State<Err = PartialVMError>) {
-    let (changes, table_changes) = init(config, state);
+    let (changes, table_changes) = init(config, state).expect("Genesis frameworks should deploy");
     apply(changes, table_changes, config, state);
 }
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::storage::InMemoryState};
+
+    #[test]
+    #[should_panic(expected = "Genesis state root mismatch")]
+    fn test_apply_rejects_unexpected_state_root() {
+        let config = GenesisConfig {
+            initial_state_root: B256::ZERO,
+            ..GenesisConfig::dev()
+        };
+        let mut state = InMemoryState::new();
+        init_and_apply(&config, &mut state);
+    }
+
+    #[test]
+    #[should_panic(expected = "Genesis treasury must be set outside of dev mode")]
+    fn test_init_rejects_placeholder_treasury_outside_of_dev_mode() {
+        let config = GenesisConfig::default();
+        let state = InMemoryState::new();
+        init(&config, &state).unwrap();
+    }
+
+    #[test]
+    fn test_apply_accepts_computed_state_root_without_verification() {
+        let config = GenesisConfig {
+            initial_state_root: B256::ZERO,
+            verify_state_root: false,
+            ..GenesisConfig::dev()
+        };
+        let mut state = InMemoryState::new();
+        let (changes, table_changes) = init(&config, &state).unwrap();
+        let actual_state_root = apply(changes, table_changes, &config, &mut state);
+
+        assert_eq!(actual_state_root, state.state_root());
+        assert_eq!(
+            actual_state_root,
+            GenesisConfig::default().initial_state_root
+        );
+    }
+}
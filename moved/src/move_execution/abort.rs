@@ -0,0 +1,140 @@
+use {
+    crate::{genesis::FRAMEWORK_ADDRESS, MoveAbort},
+    move_binary_format::{
+        access::ModuleAccess,
+        errors::{Location, PartialVMError, VMError},
+        file_format::FunctionDefinitionIndex,
+        CompiledModule,
+    },
+    move_core_types::{
+        identifier::Identifier, language_storage::ModuleId, resolver::ModuleResolver,
+        vm_status::StatusCode,
+    },
+};
+
+/// Categories of abort codes in the upper bits of the code, see `std::error`.
+const INVALID_ARGUMENT: u64 = 0x1 << 16;
+const OUT_OF_RANGE: u64 = 0x2 << 16;
+const INVALID_STATE: u64 = 0x3 << 16;
+const PERMISSION_DENIED: u64 = 0x5 << 16;
+const NOT_FOUND: u64 = 0x6 << 16;
+const ABORTED: u64 = 0x7 << 16;
+
+/// Descriptions of the aborts raised by the framework modules by module name and abort code.
+const FRAMEWORK_ABORTS: &[(&str, u64, &str)] = &[
+    (
+        "eth_token",
+        PERMISSION_DENIED | 1,
+        "Only the admin may manage the ETH token",
+    ),
+    (
+        "evm",
+        PERMISSION_DENIED | 1,
+        "Only the admin may deploy EVM contracts",
+    ),
+    (
+        "evm",
+        INVALID_ARGUMENT | 1,
+        "Solidity fixed bytes must have length between 1 and 32",
+    ),
+    (
+        "fungible_asset_u256",
+        INVALID_ARGUMENT | 1,
+        "Amount cannot be zero",
+    ),
+    (
+        "fungible_asset_u256",
+        PERMISSION_DENIED | 3,
+        "Store is frozen for this fungible asset",
+    ),
+    (
+        "fungible_asset_u256",
+        INVALID_ARGUMENT | 4,
+        "Insufficient balance to withdraw or transfer",
+    ),
+    (
+        "fungible_asset_u256",
+        OUT_OF_RANGE | 5,
+        "Maximum supply of the fungible asset exceeded",
+    ),
+    (
+        "fungible_asset_u256",
+        INVALID_ARGUMENT | 6,
+        "Fungible assets do not match",
+    ),
+    (
+        "fungible_asset_u256",
+        PERMISSION_DENIED | 8,
+        "Account is not the owner of the store",
+    ),
+    (
+        "fungible_asset_u256",
+        PERMISSION_DENIED | 14,
+        "Cannot delete a store with a non-zero balance",
+    ),
+    (
+        "fungible_asset_u256",
+        INVALID_STATE | 20,
+        "Supply of the fungible asset would be negative",
+    ),
+    (
+        "fungible_asset_u256",
+        NOT_FOUND | 23,
+        "Fungible store does not exist",
+    ),
+    (
+        "primary_fungible_store_u256",
+        ABORTED | 1,
+        "Recipient did not receive the expected amount",
+    ),
+];
+
+/// Decodes `error` into a [`MoveAbort`] if it is raised by an `abort` in a module.
+///
+/// The name of the aborting function is read from the module in `resolver`.
+pub fn decode_abort(
+    error: &VMError,
+    resolver: &impl ModuleResolver<Error = PartialVMError>,
+) -> Option<MoveAbort> {
+    if error.major_status() != StatusCode::ABORTED {
+        return None;
+    }
+    let Location::Module(module) = error.location() else {
+        return None;
+    };
+    let code = error.sub_status()?;
+    let function = error
+        .offsets()
+        .first()
+        .and_then(|(index, _)| function_name(resolver, module, *index));
+
+    Some(MoveAbort {
+        module: module.clone(),
+        function,
+        code,
+        reason: known_reason(module, code),
+    })
+}
+
+fn function_name(
+    resolver: &impl ModuleResolver<Error = PartialVMError>,
+    module: &ModuleId,
+    index: FunctionDefinitionIndex,
+) -> Option<Identifier> {
+    let bytes = resolver.get_module(module).ok()??;
+    let module = CompiledModule::deserialize(&bytes).ok()?;
+    let definition = module.function_defs().get(index.0 as usize)?;
+    let handle = module.function_handle_at(definition.function);
+
+    Some(module.identifier_at(handle.name).to_owned())
+}
+
+fn known_reason(module: &ModuleId, code: u64) -> Option<&'static str> {
+    if module.address() != &FRAMEWORK_ADDRESS {
+        return None;
+    }
+    FRAMEWORK_ABORTS
+        .iter()
+        .find(|(name, known_code, _)| module.name().as_str() == *name && *known_code == code)
+        .map(|(_, _, reason)| *reason)
+}
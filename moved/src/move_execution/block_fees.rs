@@ -0,0 +1,80 @@
+use {
+    crate::{
+        block::HeaderForExecution,
+        genesis::config::GenesisConfig,
+        move_execution::{create_move_vm_for, create_vm_session, eth_token::BaseTokenAccounts},
+        primitives::{Address, ToMoveAddress, U256},
+        types::session_id::SessionId,
+        EthToken, InvariantViolation,
+    },
+    alloy::primitives::address,
+    aptos_table_natives::TableResolver,
+    move_binary_format::errors::PartialVMError,
+    move_core_types::{effects::ChangeSet, resolver::MoveResolver},
+    move_vm_runtime::module_traversal::{TraversalContext, TraversalStorage},
+};
+
+/// OP stack predeploy collecting the base fee instead of burning it.
+pub const BASE_FEE_VAULT: Address = address!("4200000000000000000000000000000000000019");
+/// OP stack predeploy collecting the fee for publishing transactions to L1.
+pub const L1_FEE_VAULT: Address = address!("420000000000000000000000000000000000001a");
+
+/// Gas costs collected by the treasury while executing the transactions of a block.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BlockFees {
+    /// Sum of the priority fees, paid to the block fee recipient.
+    pub tip: U256,
+    /// Sum of the base fees, paid to the [`BASE_FEE_VAULT`].
+    pub base_fee: U256,
+    /// Sum of the L1 data fees, paid to the [`L1_FEE_VAULT`].
+    pub l1_fee: U256,
+}
+
+impl BlockFees {
+    pub fn is_zero(&self) -> bool {
+        self.tip.is_zero() && self.base_fee.is_zero() && self.l1_fee.is_zero()
+    }
+}
+
+/// Pays out the `fees` collected by the treasury during block execution to their recipients
+/// following the OP stack rules.
+pub fn pay_block_fees(
+    fees: &BlockFees,
+    fee_recipient: &Address,
+    state: &(impl MoveResolver<PartialVMError> + TableResolver),
+    genesis_config: &GenesisConfig,
+    base_token: &impl BaseTokenAccounts,
+    block_header: HeaderForExecution,
+) -> crate::Result<ChangeSet> {
+    let move_vm = create_move_vm_for(genesis_config)?;
+    let session_id = SessionId::new_from_block(genesis_config, block_header);
+    let mut session = create_vm_session(&move_vm, state, session_id);
+    let traversal_storage = TraversalStorage::new();
+    let mut traversal_context = TraversalContext::new(&traversal_storage);
+
+    let payouts = [
+        (fee_recipient, fees.tip),
+        (&BASE_FEE_VAULT, fees.base_fee),
+        (&L1_FEE_VAULT, fees.l1_fee),
+    ];
+    for (to, amount) in payouts {
+        if amount.is_zero() {
+            continue;
+        }
+        base_token
+            .pay_fee(
+                &to.to_move_address(),
+                amount,
+                &mut session,
+                &mut traversal_context,
+            )
+            .map_err(|_| {
+                crate::Error::InvariantViolation(InvariantViolation::EthToken(
+                    EthToken::PayFeeAlwaysSucceeds,
+                ))
+            })?;
+    }
+
+    let changes = session.finish()?;
+    Ok(changes)
+}
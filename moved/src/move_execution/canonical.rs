@@ -4,7 +4,7 @@ use {
         block::HeaderForExecution,
         genesis::config::GenesisConfig,
         move_execution::{
-            create_move_vm, create_vm_session,
+            create_move_vm_for, create_vm_session,
             eth_token::{BaseTokenAccounts, TransferArgs},
             evm_native,
             execute::{deploy_module, execute_entry_function, execute_l2_contract, execute_script},
@@ -23,9 +23,10 @@ use {
         Error::{InvalidTransaction, User},
         EthToken, InvalidTransactionCause, InvariantViolation,
     },
+    aptos_framework::natives::event::NativeEventContext,
     aptos_gas_meter::{AptosGasMeter, StandardGasAlgebra, StandardGasMeter},
     aptos_table_natives::TableResolver,
-    move_binary_format::errors::PartialVMError,
+    move_binary_format::{errors::PartialVMError, CompiledModule},
     move_core_types::resolver::MoveResolver,
     move_vm_runtime::{
         module_traversal::{TraversalContext, TraversalStorage},
@@ -33,6 +34,58 @@ use {
     },
 };
 
+/// Rejects transactions that reach the EVM when it is disabled in the `genesis_config`.
+///
+/// Scripts are not inspected, any EVM function they call fails during execution.
+pub(super) fn check_evm_enabled(
+    tx_data: &TransactionData,
+    genesis_config: &GenesisConfig,
+) -> crate::Result<()> {
+    if genesis_config.evm_native {
+        return Ok(());
+    }
+    let reaches_evm = match tx_data {
+        TransactionData::L2Contract(_) => true,
+        TransactionData::EntryFunction(entry_fn) => {
+            let module = entry_fn.module();
+            module.address() == &evm_native::EVM_NATIVE_ADDRESS
+                && module.name() == evm_native::EVM_NATIVE_MODULE
+        }
+        _ => false,
+    };
+    if reaches_evm {
+        Err(InvalidTransactionCause::EvmDisabled)?;
+    }
+    Ok(())
+}
+
+/// Rejects module deployments exceeding the size or dependency limits of the `genesis_config`.
+pub(super) fn check_module_limits(
+    tx_data: &TransactionData,
+    genesis_config: &GenesisConfig,
+) -> crate::Result<()> {
+    let TransactionData::ScriptOrModule(ScriptOrModule::Module(module)) = tx_data else {
+        return Ok(());
+    };
+    let code = module.code();
+    if code.len() > genesis_config.max_module_size {
+        Err(InvalidTransactionCause::ModuleTooLarge {
+            size: code.len(),
+            max: genesis_config.max_module_size,
+        })?;
+    }
+    let count = CompiledModule::deserialize(code)?
+        .immediate_dependencies()
+        .len();
+    if count > genesis_config.max_module_dependencies {
+        Err(InvalidTransactionCause::TooManyModuleDependencies {
+            count,
+            max: genesis_config.max_module_dependencies,
+        })?;
+    }
+    Ok(())
+}
+
 #[allow(clippy::too_many_arguments)]
 pub(super) fn verify_transaction(
     tx: &NormalizedEthTransaction,
@@ -106,20 +159,30 @@ pub(super) fn execute_canonical_transaction(
     l2_input: L2GasFeeInput,
     base_token: &impl BaseTokenAccounts,
     block_header: HeaderForExecution,
+    struct_logger: Option<evm_native::StructLoggerConfig>,
 ) -> crate::Result<TransactionExecutionOutcome> {
     let sender_move_address = tx.signer.to_move_address();
 
+    if tx.chain_id.is_none() && !genesis_config.allow_unprotected_txs {
+        Err(InvalidTransactionCause::UnprotectedTransaction)?;
+    }
+
     let tx_data = TransactionData::parse_from(tx)?;
+    check_evm_enabled(&tx_data, genesis_config)?;
+    check_module_limits(&tx_data, genesis_config)?;
 
-    let move_vm = create_move_vm()?;
-    let session_id = SessionId::new_from_canonical(
-        tx,
-        tx_data.maybe_entry_fn(),
-        tx_hash,
-        genesis_config,
-        block_header,
-        tx_data.script_hash(),
-    );
+    let move_vm = create_move_vm_for(genesis_config)?;
+    let session_id = SessionId {
+        struct_logger,
+        ..SessionId::new_from_canonical(
+            tx,
+            tx_data.maybe_entry_fn(),
+            tx_hash,
+            genesis_config,
+            block_header,
+            tx_data.script_hash(),
+        )
+    };
     let mut session = create_vm_session(&move_vm, state, session_id);
     let traversal_storage = TraversalStorage::new();
     let mut traversal_context = TraversalContext::new(&traversal_storage);
@@ -128,7 +191,8 @@ pub(super) fn execute_canonical_transaction(
     let mut deployment = None;
     // Using l2 input here as test transactions don't set the max limit directly on itself
     let l2_cost = l2_fee.l2_fee(l2_input.clone()).saturating_to();
-    let mut evm_logs = Vec::new();
+    let mut logs = Vec::new();
+    let mut move_events = Vec::new();
 
     // TODO: use free gas meter for things that shouldn't fail due to
     // insufficient gas limit, impose a lower bound on the latter
@@ -177,7 +241,12 @@ pub(super) fn execute_canonical_transaction(
             base_token.transfer(args, &mut session, &mut traversal_context, &mut gas_meter)
         }
         TransactionData::L2Contract(contract) => {
-            evm_logs = execute_l2_contract(
+            // Move events emitted so far precede the EVM logs, while the ones
+            // emitted from this point on (e.g. burning the value) follow them.
+            let extensions = session.get_native_extensions();
+            logs = extensions.logs(&mut move_events);
+            extensions.add(NativeEventContext::default());
+            let evm_logs = execute_l2_contract(
                 &sender_move_address,
                 &contract.to_move_address(),
                 tx.value,
@@ -186,6 +255,7 @@ pub(super) fn execute_canonical_transaction(
                 &mut traversal_context,
                 &mut gas_meter,
             )?;
+            logs.extend(evm_logs);
             Ok(())
         }
     };
@@ -209,20 +279,21 @@ pub(super) fn execute_canonical_transaction(
         })?;
 
     let (mut changes, mut extensions) = session.finish_with_extensions()?;
-    let mut logs = extensions.logs();
-    logs.extend(evm_logs);
+    logs.extend(extensions.logs(&mut move_events));
     let evm_changes = evm_native::extract_evm_changes(&extensions);
     changes
         .squash(evm_changes)
         .expect("EVM changes must merge with other session changes");
+    let struct_logger = evm_native::take_struct_logger(&mut extensions);
 
-    match vm_outcome {
+    let outcome = match vm_outcome {
         Ok(_) => Ok(TransactionExecutionOutcome::new(
             Ok(()),
             changes,
             gas_used,
             l2_input.effective_gas_price,
             logs,
+            move_events,
             deployment,
         )),
         // User error still generates a receipt and consumes gas
@@ -232,8 +303,10 @@ pub(super) fn execute_canonical_transaction(
             gas_used,
             l2_input.effective_gas_price,
             logs,
+            move_events,
             None,
         )),
         Err(e) => Err(e),
-    }
+    };
+    outcome.map(|outcome| outcome.with_struct_logger(struct_logger))
 }
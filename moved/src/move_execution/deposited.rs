@@ -4,14 +4,14 @@ use {
         error::UserError,
         genesis::config::GenesisConfig,
         move_execution::{
-            create_move_vm, create_vm_session, eth_token, evm_native,
+            create_move_vm, create_move_vm_for, create_vm_session, eth_token, evm_native,
             gas::{new_gas_meter, total_gas_used},
             ADDRESS_LAYOUT, U256_LAYOUT,
         },
         primitives::{ToMoveAddress, ToMoveU256, B256},
         types::{
             session_id::SessionId,
-            transactions::{DepositedTx, TransactionExecutionOutcome},
+            transactions::{DepositedTx, TransactionExecutionOutcome, L1_ATTRIBUTES_DEPOSITOR},
         },
     },
     alloy::{hex, primitives::U256},
@@ -36,14 +36,23 @@ pub(super) fn execute_deposited_transaction(
     state: &(impl MoveResolver<PartialVMError> + TableResolver),
     genesis_config: &GenesisConfig,
     block_header: HeaderForExecution,
+    struct_logger: Option<evm_native::StructLoggerConfig>,
 ) -> crate::Result<TransactionExecutionOutcome> {
+    // Without the EVM there is no bridge contract to finalize the deposit
+    if !genesis_config.evm_native {
+        return move_only_deposit(tx, tx_hash, state, genesis_config, block_header);
+    }
+
     #[cfg(any(feature = "test-doubles", test))]
     if tx.data.is_empty() {
         return direct_mint(tx, tx_hash, state, genesis_config, block_header);
     }
 
     let move_vm = create_move_vm()?;
-    let session_id = SessionId::new_from_deposited(tx, tx_hash, genesis_config, block_header);
+    let session_id = SessionId {
+        struct_logger,
+        ..SessionId::new_from_deposited(tx, tx_hash, genesis_config, block_header)
+    };
     let mut session = create_vm_session(&move_vm, state, session_id);
     let traversal_storage = TraversalStorage::new();
     let mut traversal_context = TraversalContext::new(&traversal_storage);
@@ -119,12 +128,13 @@ pub(super) fn execute_deposited_transaction(
         Err(e) => (Vec::new(), Err(e)),
     };
 
-    let (mut changes, extensions) = session.finish_with_extensions()?;
+    let (mut changes, mut extensions) = session.finish_with_extensions()?;
     let gas_used = total_gas_used(&gas_meter, genesis_config);
     let evm_changes = evm_native::extract_evm_changes(&extensions);
     changes
         .squash(evm_changes)
         .expect("EVM changes must merge with other session changes");
+    let struct_logger = evm_native::take_struct_logger(&mut extensions);
 
     Ok(TransactionExecutionOutcome::new(
         vm_outcome,
@@ -133,12 +143,95 @@ pub(super) fn execute_deposited_transaction(
         // No L2 gas for deposited txs
         U256::ZERO,
         logs,
+        Vec::new(),
+        None,
+    )
+    .with_struct_logger(struct_logger))
+}
+
+/// Executes a deposit on a chain without the EVM.
+///
+/// Like the OP deposit semantics, `mint` is credited to `from` even when the
+/// deposit fails, and `value` is then transferred from `from` to `to`. There is
+/// no contract to call, so deposits carrying data fail, except for the L1
+/// attributes deposit which is only read by the node.
+fn move_only_deposit(
+    tx: &DepositedTx,
+    tx_hash: &B256,
+    state: &(impl MoveResolver<PartialVMError> + TableResolver),
+    genesis_config: &GenesisConfig,
+    block_header: HeaderForExecution,
+) -> crate::Result<TransactionExecutionOutcome> {
+    use crate::move_execution::Logs;
+
+    let from = tx.from.to_move_address();
+    let to = tx.to.to_move_address();
+
+    let move_vm = create_move_vm_for(genesis_config)?;
+    let session_id = SessionId::new_from_deposited(tx, tx_hash, genesis_config, block_header);
+    let mut session = create_vm_session(&move_vm, state, session_id);
+    let traversal_storage = TraversalStorage::new();
+    let mut traversal_context = TraversalContext::new(&traversal_storage);
+    // The type of `tx.gas` is essentially `[u64; 1]` so taking the 0th element
+    // is a 1:1 mapping to `u64`.
+    let mut gas_meter = new_gas_meter(genesis_config, tx.gas.as_limbs()[0]);
+
+    if !tx.mint.is_zero() {
+        eth_token::mint_eth(
+            &from,
+            tx.mint,
+            &mut session,
+            &mut traversal_context,
+            &mut gas_meter,
+        )?;
+    }
+
+    let vm_outcome = if !tx.data.is_empty() && tx.from != L1_ATTRIBUTES_DEPOSITOR {
+        Err(UserError::DepositFailure(Vec::new()))
+    } else if tx.value.is_zero() {
+        Ok(())
+    } else {
+        let balance = eth_token::get_eth_balance(
+            &from,
+            &mut session,
+            &mut traversal_context,
+            &mut gas_meter,
+        )?;
+        if balance < tx.value {
+            Err(UserError::DepositFailure(Vec::new()))
+        } else {
+            eth_token::transfer_eth(
+                eth_token::TransferArgs {
+                    from: &from,
+                    to: &to,
+                    amount: tx.value,
+                },
+                &mut session,
+                &mut traversal_context,
+                &mut gas_meter,
+            )?;
+            Ok(())
+        }
+    };
+
+    let (changes, mut extensions) = session.finish_with_extensions()?;
+    let gas_used = total_gas_used(&gas_meter, genesis_config);
+    let mut move_events = Vec::new();
+    let logs = extensions.logs(&mut move_events);
+
+    Ok(TransactionExecutionOutcome::new(
+        vm_outcome,
+        changes,
+        gas_used,
+        // No L2 gas for deposited txs
+        U256::ZERO,
+        logs,
+        move_events,
         None,
     ))
 }
 
-/// This function is only used in tests.
-/// It allows us to mint ETH directly without going through the EVM.
+/// Mints ETH directly without going through the EVM.
 #[cfg(any(feature = "test-doubles", test))]
 fn direct_mint(
     tx: &DepositedTx,
@@ -152,7 +245,7 @@ fn direct_mint(
     let amount = tx.mint.saturating_add(tx.value);
     let to = tx.to.to_move_address();
 
-    let move_vm = create_move_vm()?;
+    let move_vm = create_move_vm_for(genesis_config)?;
     let session_id = SessionId::new_from_deposited(tx, tx_hash, genesis_config, block_header);
     let mut session = create_vm_session(&move_vm, state, session_id);
     let traversal_storage = TraversalStorage::new();
@@ -177,7 +270,8 @@ fn direct_mint(
 
     let (changes, mut extensions) = session.finish_with_extensions()?;
     let gas_used = total_gas_used(&gas_meter, genesis_config);
-    let logs = extensions.logs();
+    let mut move_events = Vec::new();
+    let logs = extensions.logs(&mut move_events);
 
     Ok(TransactionExecutionOutcome::new(
         Ok(()),
@@ -186,6 +280,7 @@ fn direct_mint(
         // No L2 gas for deposited txs
         U256::ZERO,
         logs,
+        move_events,
         None,
     ))
 }
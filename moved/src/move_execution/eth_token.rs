@@ -61,6 +61,15 @@ pub trait BaseTokenAccounts {
         traversal_context: &mut TraversalContext,
         gas_meter: &mut G,
     ) -> Result<(), crate::Error>;
+
+    /// Pays out `amount` of the collected gas costs to `to`.
+    fn pay_fee(
+        &self,
+        to: &AccountAddress,
+        amount: U256,
+        session: &mut Session,
+        traversal_context: &mut TraversalContext,
+    ) -> Result<(), crate::Error>;
 }
 
 #[derive(Debug)]
@@ -124,6 +133,26 @@ impl BaseTokenAccounts for MovedBaseTokenAccounts {
     ) -> Result<(), crate::Error> {
         transfer_eth(args, session, traversal_context, gas_meter)
     }
+
+    fn pay_fee(
+        &self,
+        to: &AccountAddress,
+        amount: U256,
+        session: &mut Session,
+        traversal_context: &mut TraversalContext,
+    ) -> Result<(), crate::Error> {
+        let mut gas_meter = UnmeteredGasMeter;
+        transfer_eth(
+            TransferArgs {
+                from: &self.eth_treasury,
+                to,
+                amount,
+            },
+            session,
+            traversal_context,
+            &mut gas_meter,
+        )
+    }
 }
 
 pub fn mint_eth<G: GasMeter>(
@@ -271,7 +300,15 @@ pub fn quick_get_eth_balance(
     account: &AccountAddress,
     state: &(impl MoveResolver<PartialVMError> + TableResolver),
 ) -> U256 {
-    let move_vm = super::create_move_vm().unwrap();
+    try_quick_get_eth_balance(account, state).unwrap()
+}
+
+/// Same as [`quick_get_eth_balance`], but returns an error if the `state` cannot be read.
+pub fn try_quick_get_eth_balance(
+    account: &AccountAddress,
+    state: &(impl MoveResolver<PartialVMError> + TableResolver),
+) -> crate::Result<U256> {
+    let move_vm = super::create_move_vm()?;
     let mut session = super::create_vm_session(&move_vm, state, SessionId::default());
     let traversal_storage = TraversalStorage::new();
     let mut traversal_context = TraversalContext::new(&traversal_storage);
@@ -282,7 +319,6 @@ pub fn quick_get_eth_balance(
         &mut traversal_context,
         &mut gas_meter,
     )
-    .unwrap()
 }
 
 #[cfg(any(feature = "test-doubles", test))]
@@ -320,5 +356,15 @@ mod tests {
         ) -> Result<(), crate::Error> {
             Ok(())
         }
+
+        fn pay_fee(
+            &self,
+            _to: &AccountAddress,
+            _amount: U256,
+            _session: &mut Session,
+            _traversal_context: &mut TraversalContext,
+        ) -> Result<(), crate::Error> {
+            Ok(())
+        }
     }
 }
@@ -2,6 +2,9 @@ pub use self::{
     native_evm_context::{NativeEVMContext, ResolverBackedDB},
     native_impl::{append_evm_natives, EVM_CALL_FN_NAME},
     state_changes::{extract_evm_changes, genesis_state_changes},
+    struct_logger::{
+        take_struct_logger, StructLog, StructLogger, StructLoggerConfig, TransactionTrace,
+    },
     type_utils::extract_evm_result,
 };
 
@@ -12,7 +15,10 @@ use {
         identifier::IdentStr,
         value::{MoveStructLayout, MoveTypeLayout},
     },
-    revm::primitives::Log,
+    revm::{
+        precompile::{PrecompileSpecId, Precompiles},
+        primitives::{Address, Log, SpecId},
+    },
     std::sync::LazyLock,
 };
 
@@ -21,6 +27,7 @@ mod native_evm_context;
 mod native_impl;
 mod solidity_abi;
 mod state_changes;
+mod struct_logger;
 pub mod type_utils;
 
 #[cfg(test)]
@@ -32,6 +39,22 @@ pub const EVM_NATIVE_ADDRESS: AccountAddress = AccountAddress::ONE;
 /// Module name to access the EVM native
 pub const EVM_NATIVE_MODULE: &IdentStr = ident_str!("evm");
 
+/// EVM hard fork used to execute contracts through the native. It determines which
+/// opcodes and precompiles are available to the contracts. For Cancun the precompiles are
+/// ecrecover (0x01), sha256 (0x02), ripemd160 (0x03), identity (0x04), modexp (0x05),
+/// BN254 add (0x06), mul (0x07) and pairing (0x08), blake2f (0x09) and the KZG point
+/// evaluation (0x0a).
+pub const EVM_SPEC_ID: SpecId = SpecId::CANCUN;
+
+/// Bytes of memory a single EVM native call may expand to, unless configured otherwise in the
+/// [`NativeEVMContext`]. The output of a call is copied from memory, so it is bounded as well.
+pub const DEFAULT_EVM_MEMORY_LIMIT: u64 = 32 * 1024 * 1024;
+
+/// Addresses of the precompiles available to EVM contracts executed through the native.
+pub fn supported_precompiles() -> impl Iterator<Item = &'static Address> {
+    Precompiles::new(PrecompileSpecId::from_spec_id(EVM_SPEC_ID)).addresses()
+}
+
 /// Layout for elements in EVM account storage (they are simply U256 since EVM models the storage
 /// as a map (Address, U256) -> U256).
 const ACCOUNT_STORAGE_LAYOUT: MoveTypeLayout = MoveTypeLayout::U256;
@@ -1,12 +1,17 @@
 use {
     super::{
+        struct_logger::StructLogger,
         type_utils::{
             account_info_struct_tag, account_storage_struct_tag, code_hash_struct_tag,
             move_value_to_account_info,
         },
-        ACCOUNT_INFO_LAYOUT, ACCOUNT_STORAGE_LAYOUT, CODE_LAYOUT, EVM_NATIVE_ADDRESS,
+        ACCOUNT_INFO_LAYOUT, ACCOUNT_STORAGE_LAYOUT, CODE_LAYOUT, DEFAULT_EVM_MEMORY_LIMIT,
+        EVM_NATIVE_ADDRESS,
+    },
+    crate::{
+        block::{BlockHashLookup, HeaderForExecution},
+        primitives::ToU256,
     },
-    crate::{block::HeaderForExecution, primitives::ToU256},
     alloy::primitives::map::HashMap,
     aptos_types::vm_status::StatusCode,
     better_any::{Tid, TidAble},
@@ -19,6 +24,7 @@ use {
             utilities::KECCAK_EMPTY, Account, AccountInfo, Address, Bytecode, B256, U256,
         },
     },
+    std::sync::Arc,
 };
 
 #[derive(Tid)]
@@ -27,6 +33,12 @@ pub struct NativeEVMContext<'a> {
     pub db: CacheDB<ResolverBackedDB<'a>>,
     pub state_changes: Vec<HashMap<Address, Account>>,
     pub block_header: HeaderForExecution,
+    /// Records every executed opcode when set, used for tracing transactions.
+    pub struct_logger: Option<StructLogger>,
+    /// Bytes of memory an EVM call may expand to, exceeding it halts the call as out of gas.
+    pub memory_limit: u64,
+    /// Opcodes reverting the call executing them instead of running.
+    pub disabled_opcodes: Vec<u8>,
 }
 
 impl<'a> NativeEVMContext<'a> {
@@ -34,24 +46,46 @@ impl<'a> NativeEVMContext<'a> {
         state: &'a impl MoveResolver<PartialVMError>,
         block_header: HeaderForExecution,
     ) -> Self {
-        let inner_db = ResolverBackedDB::new(state);
+        let inner_db = ResolverBackedDB::new(state)
+            .with_block_hashes(block_header.number, block_header.block_hashes.clone());
         let db = CacheDB::new(inner_db);
         Self {
             resolver: state,
             db,
             state_changes: Vec::new(),
             block_header,
+            struct_logger: None,
+            memory_limit: DEFAULT_EVM_MEMORY_LIMIT,
+            disabled_opcodes: Vec::new(),
         }
     }
 }
 
 pub struct ResolverBackedDB<'a> {
     resolver: &'a dyn MoveResolver<PartialVMError>,
+    /// Number of the block being executed, `BLOCKHASH` reads are relative to it.
+    block_number: u64,
+    block_hashes: Arc<BlockHashLookup>,
 }
 
 impl<'a> ResolverBackedDB<'a> {
     pub fn new(resolver: &'a impl MoveResolver<PartialVMError>) -> Self {
-        Self { resolver }
+        Self {
+            resolver,
+            block_number: 0,
+            block_hashes: Arc::default(),
+        }
+    }
+
+    /// Serves `BLOCKHASH` in block `block_number` from `block_hashes`, otherwise it reads zero.
+    pub fn with_block_hashes(
+        mut self,
+        block_number: u64,
+        block_hashes: Arc<BlockHashLookup>,
+    ) -> Self {
+        self.block_number = block_number;
+        self.block_hashes = block_hashes;
+        self
     }
 }
 
@@ -111,11 +145,7 @@ impl<'a> DatabaseRef for ResolverBackedDB<'a> {
         Ok(value)
     }
 
-    fn block_hash_ref(&self, _number: u64) -> Result<B256, Self::Error> {
-        // Complication: Move doesn't support this API out of the box.
-        // We could build it out ourselves, but maybe it's not needed
-        // for the contracts we want to support?
-
-        unimplemented!("EVM block hash API not implemented")
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        Ok(self.block_hashes.get(number, self.block_number))
     }
 }
@@ -3,7 +3,7 @@ use {
         native_evm_context::NativeEVMContext,
         solidity_abi::{abi_decode_params, abi_encode_params},
         type_utils::evm_result_to_move_value,
-        EVM_NATIVE_ADDRESS, EVM_NATIVE_MODULE,
+        EVM_NATIVE_ADDRESS, EVM_NATIVE_MODULE, EVM_SPEC_ID,
     },
     crate::primitives::{ToEthAddress, ToU256},
     aptos_gas_algebra::{GasExpression, GasQuantity, InternalGasUnit},
@@ -17,8 +17,11 @@ use {
     move_vm_types::{loaded_data::runtime_types::Type, values::Value},
     revm::{
         db::DatabaseCommit,
+        handler::register::EvmHandler,
+        inspector_handle_register,
+        interpreter::{Host, InstructionResult, Interpreter},
         primitives::{Address, BlobExcessGasAndPrice, BlockEnv, EVMError, TxEnv, TxKind, U256},
-        Evm,
+        Database, Evm,
     },
     smallvec::SmallVec,
     std::collections::VecDeque,
@@ -104,10 +107,15 @@ fn evm_transact_inner(
     data: Vec<u8>,
 ) -> SafeNativeResult<SmallVec<[Value; 1]>> {
     // TODO: does it make sense for EVM gas to be 1:1 with MoveVM gas?
+    // Note: nested calls are still subject to the EVM call depth limit (1024)
+    // and the 63/64 gas forwarding rule (EIP-150), both enforced by revm.
+    // Nested calls transferring value also grant the callee the 2300 gas stipend.
     let gas_limit: u64 = context.gas_balance().into();
 
     let evm_native_ctx = context.extensions_mut().get_mut::<NativeEVMContext>();
-    let mut evm = Evm::builder()
+    let memory_limit = evm_native_ctx.memory_limit;
+    let disabled_opcodes = evm_native_ctx.disabled_opcodes.clone();
+    let builder = Evm::builder()
         .with_db(&mut evm_native_ctx.db)
         .with_tx_env(TxEnv {
             caller,
@@ -144,20 +152,47 @@ fn evm_transact_inner(
                 blob_gasprice: 0,
             }),
         })
+        .with_spec_id(EVM_SPEC_ID)
         .modify_cfg_env(|env| {
             // We can safely disable the transaction-level check because
             // the Move side ensures the funds for `value` were present.
             env.disable_balance_check = true;
-        })
-        .build();
-
-    let outcome = evm.transact().map_err(|e| match e {
+            env.memory_limit = memory_limit;
+        });
+
+    let outcome = match evm_native_ctx.struct_logger.as_mut() {
+        Some(struct_logger) => {
+            // Opcodes are disabled first so the inspector also wraps their reverting instruction
+            let mut evm = builder
+                .with_external_context(std::mem::take(struct_logger))
+                .append_handler_register_box(Box::new(move |handler: &mut EvmHandler<'_, _, _>| {
+                    disable_opcodes(handler, &disabled_opcodes)
+                }))
+                .append_handler_register(inspector_handle_register)
+                .build();
+            let outcome = evm.transact();
+            *struct_logger = std::mem::take(&mut evm.context.external);
+            if let Ok(outcome) = &outcome {
+                let output = outcome.result.output().cloned().unwrap_or_default();
+                struct_logger.set_return_value(output);
+            }
+            outcome
+        }
+        None => {
+            let mut evm = builder
+                .append_handler_register_box(Box::new(move |handler: &mut EvmHandler<'_, _, _>| {
+                    disable_opcodes(handler, &disabled_opcodes)
+                }))
+                .build();
+            evm.transact()
+        }
+    }
+    .map_err(|e| match e {
         EVMError::Database(e) => SafeNativeError::InvariantViolation(e),
         other => SafeNativeError::InvariantViolation(
             PartialVMError::new(StatusCode::ABORTED).with_message(format!("EVM Error: {other:?}")),
         ),
     })?;
-    drop(evm);
 
     // Capture changes in native context so that they can be
     // converted into Move changes when the session is finalized
@@ -174,6 +209,19 @@ fn evm_transact_inner(
     Ok(smallvec::smallvec![evm_result_to_move_value(result)])
 }
 
+/// Replaces the instructions of `opcodes` with one reverting the call, keeping its remaining gas.
+fn disable_opcodes<EXT, DB: Database>(handler: &mut EvmHandler<'_, EXT, DB>, opcodes: &[u8]) {
+    for opcode in opcodes {
+        handler
+            .instruction_table
+            .insert(*opcode, revert_disabled_opcode);
+    }
+}
+
+fn revert_disabled_opcode<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+    interpreter.instruction_result = InstructionResult::Revert;
+}
+
 struct EvmGasUsed {
     amount: u64,
 }
@@ -119,12 +119,6 @@ fn add_account_changes(
         "Untouched accounts are filtered out before calling this function."
     );
 
-    if account.is_selfdestructed() {
-        unimplemented!("EVM account self-destruct is not implemented");
-    }
-
-    let code_hash = get_account_code_hash(&account.info);
-
     let resource_exists = |struct_tag: &StructTag| {
         let exists_in_prior_changes = prior_changes.resources().contains_key(struct_tag);
         // Early exit since we don't need to check the resolver if it's in the prior changes.
@@ -138,6 +132,21 @@ fn add_account_changes(
             .unwrap_or(false)
     };
 
+    // Since Cancun (EIP-6780) only accounts created in the same transaction are destroyed, so
+    // their code and storage were never written. The account info can still exist if the address
+    // was funded before the contract was created.
+    if account.is_selfdestructed() {
+        let struct_tag = account_info_struct_tag(address);
+        if resource_exists(&struct_tag) {
+            result
+                .add_resource_op(struct_tag, Op::Delete)
+                .expect("Resource cannot already exist in result");
+        }
+        return;
+    }
+
+    let code_hash = get_account_code_hash(&account.info);
+
     // Push AccountInfo resource
     let struct_tag = account_info_struct_tag(address);
     let account_info = account_info_to_move_value(&account.info, code_hash)
@@ -0,0 +1,164 @@
+use {
+    super::NativeEVMContext,
+    move_vm_runtime::native_extensions::NativeContextExtensions,
+    revm::{
+        interpreter::{opcode, Interpreter, OpCode},
+        primitives::{Address, Bytes, B256, U256},
+        Database, EvmContext, Inspector,
+    },
+    serde::{Deserialize, Serialize},
+    std::collections::{BTreeMap, HashMap},
+};
+
+/// Options for the default (struct logger) tracer. The names match the ones used by geth.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StructLoggerConfig {
+    pub disable_memory: bool,
+    pub disable_stack: bool,
+    pub disable_storage: bool,
+}
+
+/// State of the EVM right before executing an opcode.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLog {
+    pub pc: u64,
+    pub op: String,
+    pub gas: u64,
+    pub gas_cost: u64,
+    pub depth: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stack: Option<Vec<U256>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory: Option<Vec<B256>>,
+    /// Storage slots of the executing contract accessed so far (includes the current opcode).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage: Option<BTreeMap<U256, U256>>,
+}
+
+/// Output of the default tracer for a whole transaction, in the format returned by geth.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionTrace {
+    pub gas: u64,
+    pub failed: bool,
+    pub return_value: Bytes,
+    pub struct_logs: Vec<StructLog>,
+}
+
+/// [`Inspector`] that records a [`StructLog`] for every executed opcode.
+#[derive(Debug, Default)]
+pub struct StructLogger {
+    config: StructLoggerConfig,
+    logs: Vec<StructLog>,
+    /// Output of the last finished EVM call
+    return_value: Bytes,
+    storage: HashMap<Address, BTreeMap<U256, U256>>,
+    /// Key of the `SLOAD` currently being executed
+    pending_sload: Option<U256>,
+}
+
+impl StructLogger {
+    pub fn new(config: StructLoggerConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    pub fn logs(&self) -> &[StructLog] {
+        &self.logs
+    }
+
+    pub fn into_logs(self) -> Vec<StructLog> {
+        self.logs
+    }
+
+    pub(super) fn set_return_value(&mut self, return_value: Bytes) {
+        self.return_value = return_value;
+    }
+
+    /// Completes the trace with the `gas` used by the whole transaction and whether it `failed`.
+    pub fn into_trace(self, gas: u64, failed: bool) -> TransactionTrace {
+        TransactionTrace {
+            gas,
+            failed,
+            return_value: self.return_value,
+            struct_logs: self.logs,
+        }
+    }
+}
+
+/// Takes the [`StructLogger`] out of the EVM native context once the session is finished, `None`
+/// if the session was not traced.
+pub fn take_struct_logger(extensions: &mut NativeContextExtensions) -> Option<StructLogger> {
+    extensions
+        .get_mut::<NativeEVMContext>()
+        .struct_logger
+        .take()
+}
+
+impl<DB: Database> Inspector<DB> for StructLogger {
+    fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        let opcode = interp.current_opcode();
+        let stack = interp.stack.data();
+        self.pending_sload = None;
+
+        let storage = (!self.config.disable_storage).then(|| {
+            let contract_storage = self
+                .storage
+                .entry(interp.contract.target_address)
+                .or_default();
+            match (opcode, stack.as_slice()) {
+                (opcode::SSTORE, [.., value, key]) => {
+                    contract_storage.insert(*key, *value);
+                }
+                // The loaded value is only known after the opcode is executed
+                (opcode::SLOAD, [.., key]) => self.pending_sload = Some(*key),
+                _ => (),
+            }
+            contract_storage.clone()
+        });
+        let memory = (!self.config.disable_memory).then(|| {
+            interp
+                .shared_memory
+                .context_memory()
+                .chunks(32)
+                .map(B256::left_padding_from)
+                .collect()
+        });
+
+        self.logs.push(StructLog {
+            pc: interp.program_counter() as u64,
+            op: OpCode::new(opcode)
+                .map(OpCode::as_str)
+                .unwrap_or("INVALID")
+                .into(),
+            gas: interp.gas.remaining(),
+            gas_cost: 0,
+            depth: context.journaled_state.depth(),
+            stack: (!self.config.disable_stack).then(|| stack.clone()),
+            memory,
+            storage,
+        });
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter, _context: &mut EvmContext<DB>) {
+        let Some(log) = self.logs.last_mut() else {
+            return;
+        };
+        log.gas_cost = log.gas.saturating_sub(interp.gas.remaining());
+
+        // After `SLOAD` the key on the stack is replaced by the loaded value
+        let (Some(key), Ok(value)) = (self.pending_sload.take(), interp.stack.peek(0)) else {
+            return;
+        };
+        let contract_storage = self
+            .storage
+            .entry(interp.contract.target_address)
+            .or_default();
+        contract_storage.insert(key, value);
+        log.storage = Some(contract_storage.clone());
+    }
+}
@@ -1,19 +1,28 @@
 use {
     super::{
-        state_changes::extract_evm_changes, type_utils::extract_evm_result, EvmNativeOutcome,
-        CODE_LAYOUT, EVM_NATIVE_ADDRESS, EVM_NATIVE_MODULE,
+        state_changes::{extract_evm_changes, genesis_state_changes},
+        supported_precompiles, take_struct_logger,
+        type_utils::extract_evm_result,
+        EvmNativeOutcome, ResolverBackedDB, StructLog, StructLoggerConfig, CODE_LAYOUT,
+        EVM_NATIVE_ADDRESS, EVM_NATIVE_MODULE,
     },
     crate::{
-        block::HeaderForExecution,
-        move_execution::{create_move_vm, create_vm_session, execute_transaction, tests::*},
+        block::{BlockHashLookup, HeaderForExecution},
+        genesis::config::GenesisConfig,
+        move_execution::{
+            create_move_vm, create_vm_session, execute_transaction, simulate::call_transaction,
+            tests::*,
+        },
         primitives::{ToEthAddress, ToMoveAddress, ToMoveU256},
         storage::{InMemoryState, State},
         tests::{ALT_EVM_ADDRESS, EVM_ADDRESS},
         types::{session_id::SessionId, transactions::TransactionData},
     },
     alloy::{
-        primitives::utils::parse_ether,
+        primitives::{keccak256, utils::parse_ether, Address},
         providers::{self, network::AnyNetwork},
+        rpc::types::{TransactionInput, TransactionRequest},
+        signers::SignerSync,
         sol,
     },
     aptos_table_natives::TableResolver,
@@ -23,6 +32,7 @@ use {
         account_address::AccountAddress,
         effects::ChangeSet,
         ident_str,
+        identifier::Identifier,
         language_storage::ModuleId,
         resolver::MoveResolver,
         value::{MoveStructLayout, MoveTypeLayout, MoveValue},
@@ -35,7 +45,11 @@ use {
         gas::UnmeteredGasMeter,
         values::{Struct, Value},
     },
-    revm::primitives::{TxKind, U256},
+    revm::{
+        db::DatabaseRef,
+        primitives::{TxKind, B256, U256},
+    },
+    std::sync::Arc,
 };
 
 sol!(
@@ -44,6 +58,17 @@ sol!(
     "../server/src/tests/res/ERC20.json"
 );
 
+/// Contract that returns the previous RANDAO value.
+#[rustfmt::skip]
+const PREVRANDAO_CODE: [u8; 9] = [
+    0x44,       // PREVRANDAO (formerly DIFFICULTY)
+    0x60, 0x00, // PUSH1 0
+    0x52,       // MSTORE
+    0x60, 0x20, // PUSH1 32
+    0x60, 0x00, // PUSH1 0
+    0xf3,       // RETURN
+];
+
 /// Tests that EVM native works by deploying an ERC-20 contract and
 /// then having a user transfer some tokens between accounts.
 #[test]
@@ -199,6 +224,653 @@ fn test_solidity_fixed_bytes() {
     }
 }
 
+/// Tests that EVM contracts can use precompiles by deploying contracts which
+/// forward their input to a precompile and return the precompile's output.
+#[test]
+fn test_evm_precompiles() {
+    let mut ctx = TestContext::new();
+    let ecrecover_forwarder = deploy_precompile_forwarder(&mut ctx.state, 0x01, 0);
+    let modexp_forwarder = deploy_precompile_forwarder(&mut ctx.state, 0x05, 1);
+
+    // ecrecover returns the (left-padded) address that signed the message hash
+    let message_hash = keccak256("precompile test");
+    let signature = ctx.signer.inner.sign_hash_sync(&message_hash).unwrap();
+    let signature = signature.as_bytes();
+    let mut input = message_hash.to_vec();
+    input.extend(U256::from(signature[64]).to_be_bytes::<32>());
+    input.extend_from_slice(&signature[..64]);
+    let (outcome, _, _) = evm_quick_call(
+        EVM_NATIVE_ADDRESS,
+        ecrecover_forwarder,
+        input,
+        ctx.state.resolver(),
+    );
+    assert!(outcome.is_success, "ecrecover call must succeed");
+    assert_eq!(outcome.output, EVM_ADDRESS.into_word().to_vec());
+
+    // modexp computes 3^5 mod 7 with each number being one byte long
+    let mut input = Vec::new();
+    for length in [1u8, 1, 1] {
+        input.extend(U256::from(length).to_be_bytes::<32>());
+    }
+    input.extend([3, 5, 7]);
+    let (outcome, _, _) = evm_quick_call(
+        EVM_NATIVE_ADDRESS,
+        modexp_forwarder,
+        input,
+        ctx.state.resolver(),
+    );
+    assert!(outcome.is_success, "modexp call must succeed");
+    assert_eq!(outcome.output, vec![5]);
+}
+
+#[test]
+fn test_supported_precompiles() {
+    let precompiles: Vec<Address> = supported_precompiles().copied().collect();
+    for n in 0x01..=0x0a {
+        assert!(
+            precompiles.contains(&Address::with_last_byte(n)),
+            "Precompile {n:#04x} must be supported"
+        );
+    }
+}
+
+/// Tests that a contract deployed with CREATE2 from within an EVM call ends up
+/// at the address determined by the deployer, the salt and the init code hash.
+#[test]
+fn test_evm_create2_address() {
+    const SALT: u8 = 0x2a;
+
+    let mut ctx = TestContext::new();
+    // Factory contract that deploys its call data as init code with CREATE2
+    // and returns the address of the new contract.
+    #[rustfmt::skip]
+    let factory_code = [
+        0x36,       // CALLDATASIZE
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0x37,       // CALLDATACOPY
+        0x60, SALT, // PUSH1 salt
+        0x36,       // CALLDATASIZE (size)
+        0x60, 0x00, // PUSH1 0 (offset)
+        0x60, 0x00, // PUSH1 0 (value)
+        0xf5,       // CREATE2
+        0x60, 0x00, // PUSH1 0
+        0x52,       // MSTORE
+        0x60, 0x20, // PUSH1 32
+        0x60, 0x00, // PUSH1 0
+        0xf3,       // RETURN
+    ];
+    let factory = deploy_runtime_code(&mut ctx.state, &factory_code, 0);
+
+    // The deployed contract consists of a single STOP instruction
+    let child_init_code = init_code(&[0x00]);
+    let expected_address = factory
+        .to_eth_address()
+        .create2_from_code(B256::with_last_byte(SALT), &child_init_code);
+
+    let (outcome, mut changes, extensions) = evm_quick_call(
+        EVM_NATIVE_ADDRESS,
+        factory,
+        child_init_code.clone(),
+        ctx.state.resolver(),
+    );
+    assert!(outcome.is_success, "CREATE2 deploy must succeed");
+    assert_eq!(outcome.output, expected_address.into_word().to_vec());
+
+    let evm_changes = extract_evm_changes(&extensions);
+    changes.squash(evm_changes).unwrap();
+    drop(extensions);
+    ctx.state.apply(changes).unwrap();
+
+    // Deploying the same init code with the same salt collides with the
+    // existing contract, so CREATE2 returns the zero address.
+    let (outcome, _, _) = evm_quick_call(
+        EVM_NATIVE_ADDRESS,
+        factory,
+        child_init_code,
+        ctx.state.resolver(),
+    );
+    assert!(outcome.is_success, "Factory call must succeed");
+    assert_eq!(outcome.output, B256::ZERO.to_vec());
+}
+
+/// Tests that a contract which recursively calls itself stops at the EVM call
+/// depth limit instead of failing the whole transaction.
+#[test]
+fn test_evm_call_depth_limit() {
+    let mut ctx = TestContext::new();
+    // Contract that increments the value in storage slot 0 and then calls itself
+    #[rustfmt::skip]
+    let recursive_code = [
+        0x60, 0x00, // PUSH1 0
+        0x54,       // SLOAD
+        0x60, 0x01, // PUSH1 1
+        0x01,       // ADD
+        0x60, 0x00, // PUSH1 0
+        0x55,       // SSTORE
+        0x60, 0x00, // PUSH1 0 (retSize)
+        0x60, 0x00, // PUSH1 0 (retOffset)
+        0x60, 0x00, // PUSH1 0 (argsSize)
+        0x60, 0x00, // PUSH1 0 (argsOffset)
+        0x60, 0x00, // PUSH1 0 (value)
+        0x30,       // ADDRESS
+        0x5a,       // GAS
+        0xf1,       // CALL
+        0x00,       // STOP
+    ];
+    let contract = deploy_runtime_code(&mut ctx.state, &recursive_code, 0);
+
+    let (outcome, mut changes, extensions) = evm_quick_call(
+        EVM_NATIVE_ADDRESS,
+        contract,
+        Vec::new(),
+        ctx.state.resolver(),
+    );
+    assert!(outcome.is_success, "Recursive call must succeed");
+
+    let evm_changes = extract_evm_changes(&extensions);
+    changes.squash(evm_changes).unwrap();
+    drop(extensions);
+    ctx.state.apply(changes).unwrap();
+
+    // The outermost call plus 1024 nested calls executed, the next call was too deep.
+    let db = ResolverBackedDB::new(ctx.state.resolver());
+    let call_count = db
+        .storage_ref(contract.to_eth_address(), U256::ZERO)
+        .unwrap();
+    assert_eq!(call_count, U256::from(1025));
+}
+
+/// Tests that the struct logger records every opcode executed by the EVM native.
+#[test]
+fn test_evm_struct_logger() {
+    let mut ctx = TestContext::new();
+    // Contract that stores 2 + 3 in storage slot 0
+    #[rustfmt::skip]
+    let code = [
+        0x60, 0x02, // PUSH1 2
+        0x60, 0x03, // PUSH1 3
+        0x01,       // ADD
+        0x60, 0x00, // PUSH1 0
+        0x55,       // SSTORE
+        0x00,       // STOP
+    ];
+    let contract = deploy_runtime_code(&mut ctx.state, &code, 0);
+
+    let logs = evm_traced_call(
+        contract,
+        ctx.state.resolver(),
+        StructLoggerConfig::default(),
+    );
+    let ops: Vec<&str> = logs.iter().map(|log| log.op.as_str()).collect();
+    assert_eq!(ops, ["PUSH1", "PUSH1", "ADD", "PUSH1", "SSTORE", "STOP"]);
+
+    let pcs: Vec<u64> = logs.iter().map(|log| log.pc).collect();
+    assert_eq!(pcs, [0, 2, 4, 5, 7, 8]);
+    assert!(logs.iter().all(|log| log.depth == 1));
+    assert_eq!(logs[1].gas_cost, 3);
+
+    // Stack right before executing ADD
+    assert_eq!(
+        logs[2].stack.as_deref(),
+        Some([U256::from(2), U256::from(3)].as_slice())
+    );
+    // Storage includes the slot written by SSTORE
+    let storage = logs[4].storage.as_ref().unwrap();
+    assert_eq!(storage.get(&U256::ZERO), Some(&U256::from(5)));
+
+    // Nothing is captured when disabled
+    let config = StructLoggerConfig {
+        disable_memory: true,
+        disable_stack: true,
+        disable_storage: true,
+    };
+    let logs = evm_traced_call(contract, ctx.state.resolver(), config);
+    assert_eq!(logs.len(), 6);
+    assert!(logs
+        .iter()
+        .all(|log| log.stack.is_none() && log.memory.is_none() && log.storage.is_none()));
+}
+
+/// Tests that transient storage (EIP-1153) works as a reentrancy guard and is cleared between
+/// transactions.
+#[test]
+fn test_evm_transient_storage() {
+    let mut ctx = TestContext::new();
+    // Contract that sets a transient guard and calls itself, the nested call reverts because the
+    // guard is set. Slot 0 stores if the nested call succeeded, slot 1 counts the calls.
+    #[rustfmt::skip]
+    let guarded_code = [
+        0x60, 0x00, // PUSH1 0
+        0x5c,       // TLOAD
+        0x60, 0x25, // PUSH1 37 (revert)
+        0x57,       // JUMPI
+        0x60, 0x01, // PUSH1 1
+        0x60, 0x00, // PUSH1 0
+        0x5d,       // TSTORE
+        0x60, 0x00, // PUSH1 0 (retSize)
+        0x60, 0x00, // PUSH1 0 (retOffset)
+        0x60, 0x00, // PUSH1 0 (argsSize)
+        0x60, 0x00, // PUSH1 0 (argsOffset)
+        0x60, 0x00, // PUSH1 0 (value)
+        0x30,       // ADDRESS
+        0x5a,       // GAS
+        0xf1,       // CALL
+        0x60, 0x00, // PUSH1 0
+        0x55,       // SSTORE
+        0x60, 0x01, // PUSH1 1
+        0x54,       // SLOAD
+        0x60, 0x01, // PUSH1 1
+        0x01,       // ADD
+        0x60, 0x01, // PUSH1 1
+        0x55,       // SSTORE
+        0x00,       // STOP
+        0x5b,       // JUMPDEST (revert)
+        0x60, 0x00, // PUSH1 0
+        0x60, 0x00, // PUSH1 0
+        0xfd,       // REVERT
+    ];
+    let contract = deploy_runtime_code(&mut ctx.state, &guarded_code, 0);
+
+    // The guard of the first transaction does not block the second one
+    for _ in 0..2 {
+        let (outcome, mut changes, extensions) = evm_quick_call(
+            EVM_NATIVE_ADDRESS,
+            contract,
+            Vec::new(),
+            ctx.state.resolver(),
+        );
+        assert!(outcome.is_success, "Guarded call must succeed");
+        let evm_changes = extract_evm_changes(&extensions);
+        changes.squash(evm_changes).unwrap();
+        drop(extensions);
+        ctx.state.apply(changes).unwrap();
+    }
+
+    let db = ResolverBackedDB::new(ctx.state.resolver());
+    let contract = contract.to_eth_address();
+    let nested_call_succeeded = db.storage_ref(contract, U256::ZERO).unwrap();
+    assert_eq!(nested_call_succeeded, U256::ZERO);
+    let call_count = db.storage_ref(contract, U256::from(1)).unwrap();
+    assert_eq!(call_count, U256::from(2));
+}
+
+/// Tests that a contract destroyed in the transaction creating it is deleted (EIP-6780).
+#[test]
+fn test_evm_selfdestruct_of_new_contract_deletes_it() {
+    let mut ctx = TestContext::new();
+    // Init code that destroys the contract right away: CALLER, SELFDESTRUCT
+    let (outcome, mut changes, extensions) =
+        evm_quick_create(vec![0x33, 0xff], ctx.state.resolver());
+    assert!(outcome.is_success, "Contract deploy must succeed");
+    let evm_changes = extract_evm_changes(&extensions);
+    changes.squash(evm_changes).unwrap();
+    drop(extensions);
+    ctx.state.apply(changes).unwrap();
+
+    let contract = EVM_NATIVE_ADDRESS.to_eth_address().create(0);
+    let db = ResolverBackedDB::new(ctx.state.resolver());
+    assert_eq!(db.basic_ref(contract).unwrap(), None);
+}
+
+/// Tests that an existing contract only sends its balance away when self-destructing (EIP-6780).
+#[test]
+fn test_evm_selfdestruct_of_existing_contract_keeps_it() {
+    let mut ctx = TestContext::new();
+    // Contract that destroys itself when called: CALLER, SELFDESTRUCT
+    let code = vec![0x33, 0xff];
+    let contract = Address::repeat_byte(0x77);
+    let mut genesis = alloy::genesis::Genesis::default();
+    genesis.alloc.insert(
+        contract,
+        alloy::genesis::GenesisAccount {
+            balance: U256::from(100),
+            code: Some(code.clone().into()),
+            ..Default::default()
+        },
+    );
+    let changes = genesis_state_changes(genesis, ctx.state.resolver());
+    ctx.state.apply(changes).unwrap();
+
+    let (outcome, mut changes, extensions) = evm_quick_call(
+        EVM_NATIVE_ADDRESS,
+        contract.to_move_address(),
+        Vec::new(),
+        ctx.state.resolver(),
+    );
+    assert!(outcome.is_success, "Self-destruct must succeed");
+    let evm_changes = extract_evm_changes(&extensions);
+    changes.squash(evm_changes).unwrap();
+    drop(extensions);
+    ctx.state.apply(changes).unwrap();
+
+    let db = ResolverBackedDB::new(ctx.state.resolver());
+    let info = db
+        .basic_ref(contract)
+        .unwrap()
+        .expect("Contract must still exist");
+    assert_eq!(info.balance, U256::ZERO);
+    let stored_code = db.code_by_hash_ref(info.code_hash).unwrap();
+    assert_eq!(stored_code.original_bytes(), code);
+}
+
+/// Tests that calls expanding the EVM memory past the limit, e.g. to return a huge output, halt.
+#[test]
+fn test_evm_memory_limit() {
+    let mut ctx = TestContext::new();
+    // Contracts that return `size` bytes of (empty) memory: PUSH2 size, PUSH1 0, RETURN
+    let return_code = |size: u16| {
+        let [high, low] = size.to_be_bytes();
+        [0x61, high, low, 0x60, 0x00, 0xf3]
+    };
+    let at_limit = deploy_runtime_code(&mut ctx.state, &return_code(1024), 0);
+    let past_limit = deploy_runtime_code(&mut ctx.state, &return_code(1025), 1);
+
+    let outcome = evm_memory_limited_call(at_limit, ctx.state.resolver(), 1024);
+    assert!(outcome.is_success, "Call within the limit must succeed");
+    assert_eq!(outcome.output, vec![0; 1024]);
+
+    let outcome = evm_memory_limited_call(past_limit, ctx.state.resolver(), 1024);
+    assert!(!outcome.is_success, "Call past the limit must halt");
+    assert!(outcome.output.is_empty());
+}
+
+/// Tests that a contract using a disabled opcode reverts, while it works with the opcode enabled.
+#[test]
+fn test_evm_disabled_opcode_reverts() {
+    let mut ctx = TestContext::new();
+    let contract = deploy_runtime_code(&mut ctx.state, &PREVRANDAO_CODE, 0);
+
+    let outcome = evm_opcode_restricted_call(contract, ctx.state.resolver(), Vec::new());
+    assert!(
+        outcome.is_success,
+        "Call with the opcode enabled must succeed"
+    );
+    assert_eq!(outcome.output.len(), 32);
+
+    let outcome = evm_opcode_restricted_call(contract, ctx.state.resolver(), vec![0x44]);
+    assert!(
+        !outcome.is_success,
+        "Call with the opcode disabled must revert"
+    );
+    assert!(outcome.output.is_empty());
+}
+
+/// Tests that the struct logger records the step of a disabled opcode reverting the call.
+#[test]
+fn test_evm_disabled_opcode_is_traced() {
+    let mut ctx = TestContext::new();
+    let contract = deploy_runtime_code(&mut ctx.state, &PREVRANDAO_CODE, 0);
+    let enabled_logs = evm_traced_call(
+        contract,
+        ctx.state.resolver(),
+        StructLoggerConfig::default(),
+    );
+
+    let session_id = SessionId {
+        struct_logger: Some(StructLoggerConfig::default()),
+        disabled_evm_opcodes: vec![0x44],
+        ..Default::default()
+    };
+    let (outcome, mut extensions) =
+        evm_session_call_with_extensions(contract, ctx.state.resolver(), session_id);
+    let logs = take_struct_logger(&mut extensions).unwrap().into_logs();
+
+    assert!(
+        !outcome.is_success,
+        "Call with the opcode disabled must revert"
+    );
+    assert_eq!(logs.len(), 1, "Only the disabled opcode must be executed");
+    assert_eq!(logs[0].pc, 0);
+    assert_eq!(logs[0].op, enabled_logs[0].op);
+}
+
+/// Tests that calls made through `eth_call` respect the disabled opcodes like transactions do.
+#[test]
+fn test_evm_disabled_opcode_reverts_in_call() {
+    let mut ctx = TestContext::new();
+    let contract = deploy_runtime_code(&mut ctx.state, &PREVRANDAO_CODE, 0);
+    let request = call_succeeds_request(&mut ctx, contract);
+    let config = |disabled_evm_opcodes| GenesisConfig {
+        disabled_evm_opcodes,
+        ..ctx.genesis_config.clone()
+    };
+
+    assert!(eth_call_succeeds(&ctx, &request, &config(Vec::new())));
+    assert!(!eth_call_succeeds(&ctx, &request, &config(vec![0x44])));
+}
+
+/// Tests that calls made through `eth_call` respect the EVM memory limit of the genesis config.
+#[test]
+fn test_evm_memory_limit_in_call() {
+    let mut ctx = TestContext::new();
+    // Contract that returns 1025 bytes of (empty) memory: PUSH2 1025, PUSH1 0, RETURN
+    let contract = deploy_runtime_code(&mut ctx.state, &[0x61, 0x04, 0x01, 0x60, 0x00, 0xf3], 0);
+    let request = call_succeeds_request(&mut ctx, contract);
+    let config = |evm_memory_limit| GenesisConfig {
+        evm_memory_limit,
+        ..ctx.genesis_config.clone()
+    };
+
+    assert!(eth_call_succeeds(&ctx, &request, &config(2048)));
+    assert!(!eth_call_succeeds(&ctx, &request, &config(1024)));
+}
+
+/// Tests that `BLOCKHASH` reads the hashes of the 256 blocks before the current one, and zero for
+/// older blocks, the current block and the blocks after it.
+#[test]
+fn test_evm_block_hash_window() {
+    let mut ctx = TestContext::new();
+    let current = 300;
+    let hash_of = |height: u64| B256::left_padding_from(&(height + 1).to_be_bytes());
+    let mut block_hashes = BlockHashLookup::default();
+    for height in 0..current {
+        block_hashes.push(height, hash_of(height));
+    }
+    let block_header = HeaderForExecution {
+        number: current,
+        block_hashes: Arc::new(block_hashes),
+        ..Default::default()
+    };
+    let cases = [
+        (current - 1, hash_of(current - 1)),
+        (current - 256, hash_of(current - 256)),
+        (current - 257, B256::ZERO),
+        (current, B256::ZERO),
+        (current + 1, B256::ZERO),
+    ];
+
+    for (nonce, (number, expected)) in cases.into_iter().enumerate() {
+        // Contract that returns the hash of block `number`
+        let mut code = vec![0x67]; // PUSH8 number
+        code.extend_from_slice(&number.to_be_bytes());
+        #[rustfmt::skip]
+        let return_hash = [
+            0x40,       // BLOCKHASH
+            0x60, 0x00, // PUSH1 0
+            0x52,       // MSTORE
+            0x60, 0x20, // PUSH1 32
+            0x60, 0x00, // PUSH1 0
+            0xf3,       // RETURN
+        ];
+        code.extend_from_slice(&return_hash);
+        let contract = deploy_runtime_code(&mut ctx.state, &code, nonce as u64);
+        let session_id = SessionId {
+            block_header: block_header.clone(),
+            ..Default::default()
+        };
+
+        let outcome = evm_session_call(contract, ctx.state.resolver(), session_id);
+        assert!(
+            outcome.is_success,
+            "Call of BLOCKHASH({number}) must succeed"
+        );
+        assert_eq!(
+            B256::from_slice(&outcome.output),
+            expected,
+            "Unexpected BLOCKHASH({number})"
+        );
+    }
+}
+
+/// Tests that a value-bearing call forwards the 2300 gas stipend, which covers a fallback
+/// emitting a log.
+#[test]
+fn test_evm_value_call_stipend_covers_minimal_fallback() {
+    // Receiver that emits an empty log: PUSH1 0, PUSH1 0, LOG0
+    let (call_succeeded, received) = evm_stipend_call(vec![0x60, 0x00, 0x60, 0x00, 0xa0]);
+    assert!(call_succeeded, "Fallback within the stipend must succeed");
+    assert_eq!(received, U256::from(1));
+}
+
+/// Tests that a fallback writing storage runs out of gas on the stipend alone (EIP-2200), so the
+/// value stays with the caller.
+#[test]
+fn test_evm_value_call_stipend_does_not_cover_storage_write() {
+    // Receiver that writes a storage slot: PUSH1 1, PUSH1 0, SSTORE
+    let (call_succeeded, received) = evm_stipend_call(vec![0x60, 0x01, 0x60, 0x00, 0x55]);
+    assert!(!call_succeeded, "Fallback exceeding the stipend must fail");
+    assert_eq!(received, U256::ZERO);
+}
+
+/// Calls a contract which sends 1 wei to a receiver with `receiver_code` without forwarding any
+/// gas, so the receiver only has the stipend. Returns if the nested call succeeded and the
+/// balance of the receiver afterwards.
+fn evm_stipend_call(receiver_code: Vec<u8>) -> (bool, U256) {
+    let mut ctx = TestContext::new();
+    let caller = Address::repeat_byte(0x55);
+    let receiver = Address::repeat_byte(0x66);
+    #[rustfmt::skip]
+    let mut caller_code = vec![
+        0x60, 0x00, // PUSH1 0 (retSize)
+        0x60, 0x00, // PUSH1 0 (retOffset)
+        0x60, 0x00, // PUSH1 0 (argsSize)
+        0x60, 0x00, // PUSH1 0 (argsOffset)
+        0x60, 0x01, // PUSH1 1 (value)
+        0x73,       // PUSH20 receiver
+    ];
+    caller_code.extend_from_slice(receiver.as_slice());
+    #[rustfmt::skip]
+    let call_code = [
+        0x60, 0x00, // PUSH1 0 (gas)
+        0xf1,       // CALL
+        0x60, 0x00, // PUSH1 0
+        0x55,       // SSTORE
+        0x00,       // STOP
+    ];
+    caller_code.extend_from_slice(&call_code);
+    let mut genesis = alloy::genesis::Genesis::default();
+    genesis.alloc.insert(
+        caller,
+        alloy::genesis::GenesisAccount {
+            balance: U256::from(100),
+            code: Some(caller_code.into()),
+            ..Default::default()
+        },
+    );
+    genesis.alloc.insert(
+        receiver,
+        alloy::genesis::GenesisAccount {
+            code: Some(receiver_code.into()),
+            ..Default::default()
+        },
+    );
+    let changes = genesis_state_changes(genesis, ctx.state.resolver());
+    ctx.state.apply(changes).unwrap();
+
+    let (outcome, mut changes, extensions) = evm_quick_call(
+        EVM_NATIVE_ADDRESS,
+        caller.to_move_address(),
+        Vec::new(),
+        ctx.state.resolver(),
+    );
+    assert!(outcome.is_success, "Outer call must succeed");
+    let evm_changes = extract_evm_changes(&extensions);
+    changes.squash(evm_changes).unwrap();
+    drop(extensions);
+    ctx.state.apply(changes).unwrap();
+
+    let db = ResolverBackedDB::new(ctx.state.resolver());
+    let call_succeeded = db.storage_ref(caller, U256::ZERO).unwrap() == U256::from(1);
+    let received = db
+        .basic_ref(receiver)
+        .unwrap()
+        .map_or(U256::ZERO, |info| info.balance);
+    (call_succeeded, received)
+}
+
+/// Deploy a contract which calls the precompile at `precompile` with its call data
+/// and returns whatever the precompile returned. The contract is deployed by the
+/// EVM native address using the given `nonce` which determines the contract address.
+fn deploy_precompile_forwarder(
+    state: &mut InMemoryState,
+    precompile: u8,
+    nonce: u64,
+) -> AccountAddress {
+    #[rustfmt::skip]
+    let runtime_code = [
+        0x36,             // CALLDATASIZE
+        0x60, 0x00,       // PUSH1 0
+        0x60, 0x00,       // PUSH1 0
+        0x37,             // CALLDATACOPY
+        0x60, 0x00,       // PUSH1 0 (retSize)
+        0x60, 0x00,       // PUSH1 0 (retOffset)
+        0x36,             // CALLDATASIZE (argsSize)
+        0x60, 0x00,       // PUSH1 0 (argsOffset)
+        0x60, precompile, // PUSH1 precompile
+        0x5a,             // GAS
+        0xfa,             // STATICCALL
+        0x50,             // POP
+        0x3d,             // RETURNDATASIZE
+        0x60, 0x00,       // PUSH1 0
+        0x60, 0x00,       // PUSH1 0
+        0x3e,             // RETURNDATACOPY
+        0x3d,             // RETURNDATASIZE
+        0x60, 0x00,       // PUSH1 0
+        0xf3,             // RETURN
+    ];
+    deploy_runtime_code(state, &runtime_code, nonce)
+}
+
+/// Deploy `runtime_code` from the EVM native address. The `nonce` must be the
+/// current nonce of the EVM native address since it determines the contract address.
+fn deploy_runtime_code(
+    state: &mut InMemoryState,
+    runtime_code: &[u8],
+    nonce: u64,
+) -> AccountAddress {
+    let (outcome, mut changes, extensions) =
+        evm_quick_create(init_code(runtime_code), state.resolver());
+    assert!(outcome.is_success, "Contract deploy must succeed");
+    let evm_changes = extract_evm_changes(&extensions);
+    changes.squash(evm_changes).unwrap();
+    drop(extensions);
+    state.apply(changes).unwrap();
+
+    EVM_NATIVE_ADDRESS
+        .to_eth_address()
+        .create(nonce)
+        .to_move_address()
+}
+
+/// Wrap `runtime_code` into init code that simply returns it as the code to deploy.
+fn init_code(runtime_code: &[u8]) -> Vec<u8> {
+    let code_len = u8::try_from(runtime_code.len()).expect("Runtime code must be short");
+    #[rustfmt::skip]
+    let mut init_code = vec![
+        0x60, code_len, // PUSH1 code_len
+        0x60, 0x0c,     // PUSH1 12 (length of this init code)
+        0x60, 0x00,     // PUSH1 0
+        0x39,           // CODECOPY
+        0x60, code_len, // PUSH1 code_len
+        0x60, 0x00,     // PUSH1 0
+        0xf3,           // RETURN
+    ];
+    init_code.extend_from_slice(runtime_code);
+    init_code
+}
+
 /// Create MoveVM instance and invoke EVM create native.
 /// For tests only since it does not use an existing session or charge gas.
 fn evm_quick_create(
@@ -291,6 +963,156 @@ fn evm_quick_call(
     (outcome, changes, extensions)
 }
 
+/// Invoke EVM call native (with empty call data) while recording the executed opcodes.
+/// For tests only since it does not use an existing session or charge gas.
+fn evm_traced_call(
+    to: AccountAddress,
+    resolver: &(impl MoveResolver<PartialVMError> + TableResolver),
+    config: StructLoggerConfig,
+) -> Vec<StructLog> {
+    let session_id = SessionId {
+        struct_logger: Some(config),
+        ..Default::default()
+    };
+    let (_, mut extensions) = evm_session_call_with_extensions(to, resolver, session_id);
+    take_struct_logger(&mut extensions).unwrap().into_logs()
+}
+
+/// Invoke EVM call native (with empty call data) allowing the EVM memory to expand to at most
+/// `memory_limit` bytes. For tests only since it does not use an existing session or charge gas.
+fn evm_memory_limited_call(
+    to: AccountAddress,
+    resolver: &(impl MoveResolver<PartialVMError> + TableResolver),
+    memory_limit: u64,
+) -> EvmNativeOutcome {
+    let session_id = SessionId {
+        evm_memory_limit: Some(memory_limit),
+        ..Default::default()
+    };
+    evm_session_call(to, resolver, session_id)
+}
+
+/// Invoke EVM call native (with empty call data) in a session where `disabled_opcodes` revert.
+/// For tests only since it does not use an existing session or charge gas.
+fn evm_opcode_restricted_call(
+    to: AccountAddress,
+    resolver: &(impl MoveResolver<PartialVMError> + TableResolver),
+    disabled_opcodes: Vec<u8>,
+) -> EvmNativeOutcome {
+    let session_id = SessionId {
+        disabled_evm_opcodes: disabled_opcodes,
+        ..Default::default()
+    };
+    evm_session_call(to, resolver, session_id)
+}
+
+/// Invoke EVM call native (with empty call data) in a session with the given `session_id`.
+/// For tests only since it does not use an existing session or charge gas.
+fn evm_session_call(
+    to: AccountAddress,
+    resolver: &(impl MoveResolver<PartialVMError> + TableResolver),
+    session_id: SessionId,
+) -> EvmNativeOutcome {
+    evm_session_call_with_extensions(to, resolver, session_id).0
+}
+
+/// Like [`evm_session_call`], also returning the extensions of the finished session.
+fn evm_session_call_with_extensions(
+    to: AccountAddress,
+    resolver: &(impl MoveResolver<PartialVMError> + TableResolver),
+    session_id: SessionId,
+) -> (EvmNativeOutcome, NativeContextExtensions) {
+    let move_vm = create_move_vm().unwrap();
+    let mut session = create_vm_session(&move_vm, resolver, session_id);
+    let traversal_storage = TraversalStorage::new();
+    let mut traversal_context = TraversalContext::new(&traversal_storage);
+    let mut gas_meter = UnmeteredGasMeter;
+
+    let module_id = ModuleId::new(EVM_NATIVE_ADDRESS, EVM_NATIVE_MODULE.into());
+    let args = vec![
+        // From
+        Value::address(EVM_NATIVE_ADDRESS)
+            .simple_serialize(&MoveTypeLayout::Address)
+            .unwrap(),
+        // to
+        Value::address(to)
+            .simple_serialize(&MoveTypeLayout::Address)
+            .unwrap(),
+        // Value
+        serialize_fungible_asset_value(0),
+        // Data
+        Value::vector_u8(Vec::new())
+            .simple_serialize(&CODE_LAYOUT)
+            .unwrap(),
+    ];
+
+    let outcome = session
+        .execute_function_bypass_visibility(
+            &module_id,
+            ident_str!("evm_call"),
+            Vec::new(),
+            args,
+            &mut gas_meter,
+            &mut traversal_context,
+        )
+        .unwrap();
+
+    let outcome = extract_evm_result(outcome);
+    let (_, extensions) = session.finish_with_extensions().unwrap();
+    (outcome, extensions)
+}
+
+/// Request of an `eth_call` to `evm_caller::call_succeeds`, which calls `contract` and returns
+/// whether the EVM call succeeded.
+fn call_succeeds_request(ctx: &mut TestContext, contract: AccountAddress) -> TransactionRequest {
+    let module_id = ctx.deploy_contract("evm_caller");
+    let entry_fn = EntryFunction::new(
+        module_id,
+        Identifier::new("call_succeeds").unwrap(),
+        Vec::new(),
+        vec![
+            bcs::to_bytes(&MoveValue::Signer(EVM_ADDRESS.to_move_address())).unwrap(),
+            bcs::to_bytes(&contract).unwrap(),
+        ],
+    );
+    TransactionRequest {
+        from: Some(EVM_ADDRESS),
+        to: Some(TxKind::Call(EVM_ADDRESS)),
+        input: TransactionInput::new(
+            bcs::to_bytes(&TransactionData::EntryFunction(entry_fn))
+                .unwrap()
+                .into(),
+        ),
+        ..Default::default()
+    }
+}
+
+/// Makes the `eth_call` of a [`call_succeeds_request`] with `genesis_config`, returning whether
+/// the EVM call succeeded.
+fn eth_call_succeeds(
+    ctx: &TestContext,
+    request: &TransactionRequest,
+    genesis_config: &GenesisConfig,
+) -> bool {
+    let output = call_transaction(
+        request.clone(),
+        ctx.state.resolver(),
+        genesis_config,
+        &(),
+        HeaderForExecution::default(),
+    )
+    .unwrap();
+    let call_response = |is_success: bool| {
+        let return_values = vec![(bcs::to_bytes(&is_success).unwrap(), MoveTypeLayout::Bool)];
+        bcs::to_bytes(&return_values).unwrap()
+    };
+    assert!(
+        output == call_response(true) || output == call_response(false),
+        "Unexpected call output {output:?}"
+    );
+    output == call_response(true)
+}
+
 /// Serialize a number as a Move fungible asset type.
 /// This is needed to directly call the EVM natives which
 /// take `value` as a fungible asset.
@@ -1,5 +1,10 @@
 pub use {
-    eth_token::{mint_eth, quick_get_eth_balance, BaseTokenAccounts, MovedBaseTokenAccounts},
+    abort::decode_abort,
+    block_fees::{pay_block_fees, BlockFees, BASE_FEE_VAULT, L1_FEE_VAULT},
+    eth_token::{
+        mint_eth, quick_get_eth_balance, try_quick_get_eth_balance, BaseTokenAccounts,
+        MovedBaseTokenAccounts,
+    },
     evm_native::genesis_state_changes,
     gas::{
         CreateEcotoneL1GasFee, CreateL1GasFee, CreateL2GasFee, CreateMovedL2GasFee, EcotoneGasFee,
@@ -16,7 +21,7 @@ use {
         primitives::{ToEthAddress, B256},
         types::{
             session_id::SessionId,
-            transactions::{NormalizedExtendedTxEnvelope, TransactionExecutionOutcome},
+            transactions::{MoveEvent, NormalizedExtendedTxEnvelope, TransactionExecutionOutcome},
         },
     },
     alloy::primitives::{Bloom, Keccak256, Log, LogData},
@@ -46,6 +51,8 @@ use {
     std::ops::Deref,
 };
 
+mod abort;
+mod block_fees;
 mod canonical;
 mod deposited;
 mod eth_token;
@@ -57,12 +64,21 @@ pub(crate) mod simulate;
 mod tag_validation;
 
 #[cfg(test)]
-mod tests;
+pub(crate) mod tests;
 
 const ADDRESS_LAYOUT: MoveTypeLayout = MoveTypeLayout::Address;
 const U256_LAYOUT: MoveTypeLayout = MoveTypeLayout::U256;
 
 pub fn create_move_vm() -> crate::Result<MoveVM> {
+    new_move_vm(true)
+}
+
+/// Creates a [`MoveVM`] with the EVM natives only if they are enabled in the `genesis_config`.
+pub fn create_move_vm_for(genesis_config: &GenesisConfig) -> crate::Result<MoveVM> {
+    new_move_vm(genesis_config.evm_native)
+}
+
+fn new_move_vm(evm_native: bool) -> crate::Result<MoveVM> {
     let mut builder = SafeNativeBuilder::new(
         LATEST_GAS_FEATURE_VERSION,
         NativeGasParameters::zeros(),
@@ -71,7 +87,9 @@ pub fn create_move_vm() -> crate::Result<MoveVM> {
         Features::default(),
     );
     let mut natives = aptos_natives_with_builder(&mut builder);
-    evm_native::append_evm_natives(&mut natives, &builder);
+    if evm_native {
+        evm_native::append_evm_natives(&mut natives, &builder);
+    }
     let vm = MoveVM::new(natives)?;
     Ok(vm)
 }
@@ -108,10 +126,13 @@ where
     native_extensions.add(NativeTableContext::new(txn_hash, state));
 
     // EVM native extension
-    native_extensions.add(evm_native::NativeEVMContext::new(
-        state,
-        session_id.block_header,
-    ));
+    let mut evm_native_ctx = evm_native::NativeEVMContext::new(state, session_id.block_header);
+    evm_native_ctx.disabled_opcodes = session_id.disabled_evm_opcodes;
+    if let Some(memory_limit) = session_id.evm_memory_limit {
+        evm_native_ctx.memory_limit = memory_limit;
+    }
+    evm_native_ctx.struct_logger = session_id.struct_logger.map(evm_native::StructLogger::new);
+    native_extensions.add(evm_native_ctx);
 
     vm.new_session_with_extensions(state, native_extensions)
 }
@@ -127,11 +148,72 @@ pub fn execute_transaction(
     l2_input: L2GasFeeInput,
     base_token: &impl BaseTokenAccounts,
     block_header: HeaderForExecution,
+) -> crate::Result<TransactionExecutionOutcome> {
+    execute_with_struct_logger(
+        tx,
+        tx_hash,
+        state,
+        genesis_config,
+        l1_cost,
+        l2_fee,
+        l2_input,
+        base_token,
+        block_header,
+        None,
+    )
+}
+
+/// Same as [`execute_transaction`], but the opcodes executed by the EVM are recorded in the
+/// [`TransactionExecutionOutcome::struct_logger`] according to `config`.
+#[allow(clippy::too_many_arguments)]
+pub fn trace_transaction(
+    tx: &NormalizedExtendedTxEnvelope,
+    tx_hash: &B256,
+    state: &(impl MoveResolver<PartialVMError> + TableResolver),
+    genesis_config: &GenesisConfig,
+    l1_cost: u64,
+    l2_fee: impl L2GasFee,
+    l2_input: L2GasFeeInput,
+    base_token: &impl BaseTokenAccounts,
+    block_header: HeaderForExecution,
+    config: evm_native::StructLoggerConfig,
+) -> crate::Result<TransactionExecutionOutcome> {
+    execute_with_struct_logger(
+        tx,
+        tx_hash,
+        state,
+        genesis_config,
+        l1_cost,
+        l2_fee,
+        l2_input,
+        base_token,
+        block_header,
+        Some(config),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn execute_with_struct_logger(
+    tx: &NormalizedExtendedTxEnvelope,
+    tx_hash: &B256,
+    state: &(impl MoveResolver<PartialVMError> + TableResolver),
+    genesis_config: &GenesisConfig,
+    l1_cost: u64,
+    l2_fee: impl L2GasFee,
+    l2_input: L2GasFeeInput,
+    base_token: &impl BaseTokenAccounts,
+    block_header: HeaderForExecution,
+    struct_logger: Option<evm_native::StructLoggerConfig>,
 ) -> crate::Result<TransactionExecutionOutcome> {
     match tx {
-        NormalizedExtendedTxEnvelope::DepositedTx(tx) => {
-            execute_deposited_transaction(tx, tx_hash, state, genesis_config, block_header)
-        }
+        NormalizedExtendedTxEnvelope::DepositedTx(tx) => execute_deposited_transaction(
+            tx,
+            tx_hash,
+            state,
+            genesis_config,
+            block_header,
+            struct_logger,
+        ),
         NormalizedExtendedTxEnvelope::Canonical(tx) => execute_canonical_transaction(
             tx,
             tx_hash,
@@ -142,6 +224,7 @@ pub fn execute_transaction(
             l2_input,
             base_token,
             block_header,
+            struct_logger,
         ),
     }
 }
@@ -160,20 +243,41 @@ impl<'a, I: Iterator<Item = &'a Log>> LogsBloom for I {
 }
 
 trait Logs {
-    fn logs(&mut self) -> Vec<Log>;
+    /// Converts the emitted Move events to Ethereum logs. The original events, except the ones
+    /// carrying EVM logs, are appended to `move_events`.
+    fn logs(&mut self, move_events: &mut Vec<MoveEvent>) -> Vec<Log>;
 }
 
 impl Logs for NativeContextExtensions<'_> {
-    fn logs(&mut self) -> Vec<Log> {
+    fn logs(&mut self, move_events: &mut Vec<MoveEvent>) -> Vec<Log> {
         let mut result = Vec::new();
         let events = self.remove::<NativeEventContext>().into_events();
         for (event, _) in events {
+            move_events.extend(to_move_event(&event));
             push_logs(&event, &mut result);
         }
         result
     }
 }
 
+fn to_move_event(event: &ContractEvent) -> Option<MoveEvent> {
+    let (type_tag, event_data) = match event {
+        ContractEvent::V1(v1) => (v1.type_tag(), v1.event_data()),
+        ContractEvent::V2(v2) => (v2.type_tag(), v2.event_data()),
+    };
+
+    if let TypeTag::Struct(struct_tag) = type_tag {
+        if struct_tag.as_ref() == EVM_LOGS_EVENT_TAG.deref() {
+            return None;
+        }
+    }
+
+    Some(MoveEvent {
+        type_tag: type_tag.to_canonical_string(),
+        data: event_data.to_vec().into(),
+    })
+}
+
 fn push_logs(event: &ContractEvent, dest: &mut Vec<Log<LogData>>) {
     let (type_tag, event_data) = match event {
         ContractEvent::V1(v1) => (v1.type_tag(), v1.event_data()),
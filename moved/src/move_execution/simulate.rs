@@ -1,11 +1,13 @@
 use {
     super::{CreateL2GasFee, CreateMovedL2GasFee, L2GasFeeInput},
     crate::{
-        block::HeaderForExecution,
+        block::{BlockHashLookup, HeaderForExecution},
         genesis::config::GenesisConfig,
         move_execution::{
-            canonical::verify_transaction, create_move_vm, create_vm_session, execute_transaction,
-            gas::new_gas_meter, quick_get_nonce, BaseTokenAccounts,
+            canonical::{check_evm_enabled, check_module_limits, verify_transaction},
+            create_move_vm_for, create_vm_session, decode_abort, execute_transaction,
+            gas::new_gas_meter,
+            quick_get_nonce, BaseTokenAccounts,
         },
         primitives::{ToMoveAddress, B256, U256},
         types::{
@@ -15,15 +17,18 @@ use {
                 TransactionData, TransactionExecutionOutcome,
             },
         },
-        Error::InvalidTransaction,
-        InvalidTransactionCause,
+        Error::{self, InvalidTransaction},
+        InvalidTransactionCause, UserError,
     },
     alloy::rpc::types::TransactionRequest,
     move_binary_format::errors::PartialVMError,
     move_core_types::resolver::MoveResolver,
     move_table_extension::TableResolver,
     move_vm_runtime::module_traversal::{TraversalContext, TraversalStorage},
-    std::time::{SystemTime, UNIX_EPOCH},
+    std::{
+        sync::Arc,
+        time::{SystemTime, UNIX_EPOCH},
+    },
 };
 
 pub fn simulate_transaction(
@@ -32,11 +37,15 @@ pub fn simulate_transaction(
     genesis_config: &GenesisConfig,
     base_token: &impl BaseTokenAccounts,
     block_height: u64,
+    block_hashes: Arc<BlockHashLookup>,
 ) -> crate::Result<TransactionExecutionOutcome> {
     let mut tx = NormalizedEthTransaction::from(request.clone());
     if request.from.is_some() && request.nonce.is_none() {
         tx.nonce = quick_get_nonce(&tx.signer.to_move_address(), state);
     }
+    // Respect the gas limit of the request if there is one so that the
+    // estimate reflects what the real execution is capped at.
+    let l2_input = L2GasFeeInput::new(tx.gas_limit(), U256::ZERO);
     let tx = NormalizedExtendedTxEnvelope::Canonical(tx);
 
     let block_header = HeaderForExecution {
@@ -46,9 +55,9 @@ pub fn simulate_transaction(
             .expect("Should get current time")
             .as_secs(),
         prev_randao: B256::random(),
+        block_hashes,
     };
 
-    let l2_input = L2GasFeeInput::new(u64::MAX, U256::ZERO);
     let l2_fee = CreateMovedL2GasFee.with_default_gas_fee_multiplier();
 
     execute_transaction(
@@ -64,20 +73,53 @@ pub fn simulate_transaction(
     )
 }
 
+/// Executes the `request` in the block described by `block_header` without changing the `state`
+/// and returns the BCS-encoded return values.
+///
+/// Aborts raised in modules are returned as [`UserError::MoveAbort`].
 pub fn call_transaction(
     request: TransactionRequest,
     state: &(impl MoveResolver<PartialVMError> + TableResolver),
     genesis_config: &GenesisConfig,
     base_token: &impl BaseTokenAccounts,
+    block_header: HeaderForExecution,
+) -> crate::Result<Vec<u8>> {
+    inner_call_transaction(request, state, genesis_config, base_token, block_header).map_err(|e| {
+        match e {
+            Error::User(UserError::Vm(e)) => match decode_abort(&e, state) {
+                Some(abort) => UserError::MoveAbort(abort).into(),
+                None => UserError::Vm(e).into(),
+            },
+            e => e,
+        }
+    })
+}
+
+fn inner_call_transaction(
+    request: TransactionRequest,
+    state: &(impl MoveResolver<PartialVMError> + TableResolver),
+    genesis_config: &GenesisConfig,
+    base_token: &impl BaseTokenAccounts,
+    block_header: HeaderForExecution,
 ) -> crate::Result<Vec<u8>> {
     let mut tx = NormalizedEthTransaction::from(request.clone());
     if request.from.is_some() && request.nonce.is_none() {
         tx.nonce = quick_get_nonce(&tx.signer.to_move_address(), state);
     }
     let tx_data = TransactionData::parse_from(&tx)?;
+    check_evm_enabled(&tx_data, genesis_config)?;
+    check_module_limits(&tx_data, genesis_config)?;
 
-    let move_vm = create_move_vm()?;
-    let session_id = SessionId::default();
+    let move_vm = create_move_vm_for(genesis_config)?;
+    // Calls run with the chain settings of real transactions, e.g. the disabled EVM opcodes
+    let session_id = SessionId::new_from_canonical(
+        &tx,
+        tx_data.maybe_entry_fn(),
+        &B256::random(),
+        genesis_config,
+        block_header,
+        tx_data.script_hash(),
+    );
     let mut session = create_vm_session(&move_vm, state, session_id);
     let traversal_storage = TraversalStorage::new();
     let mut traversal_context = TraversalContext::new(&traversal_storage);
@@ -60,3 +60,29 @@ fn test_l2_contracts() {
     let mut ctx = TestContext::new();
     ctx.run_script("l2_script", &[], vec![]);
 }
+
+#[test]
+fn test_genesis_deploys_custom_framework() {
+    let module_address = EVM_ADDRESS.to_move_address();
+    let module_bytes = ModuleCompileJob::new("counter", &module_address)
+        .compile()
+        .unwrap();
+    let framework_dir = std::env::temp_dir().join("moved_test_custom_framework");
+    std::fs::create_dir_all(&framework_dir).unwrap();
+    std::fs::write(framework_dir.join("counter.mv"), module_bytes).unwrap();
+
+    let ctx = TestContext::with_genesis_config(GenesisConfig {
+        custom_frameworks: vec![framework_dir],
+        verify_state_root: false,
+        ..GenesisConfig::dev()
+    });
+
+    let module_id = ModuleId::new(module_address, Identifier::new("counter").unwrap());
+    let module = ctx.state.resolver().get_module(&module_id).unwrap();
+    assert!(module.is_some(), "Custom framework module must be deployed");
+    assert_ne!(
+        ctx.state.state_root(),
+        GenesisConfig::default().initial_state_root,
+        "Custom framework must be part of the genesis state root"
+    );
+}
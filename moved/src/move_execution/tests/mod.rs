@@ -10,9 +10,10 @@ use {
         storage::{InMemoryState, State},
         tests::{signer::Signer, ALT_EVM_ADDRESS, ALT_PRIVATE_KEY, EVM_ADDRESS, PRIVATE_KEY},
         types::transactions::{DepositedTx, ExtendedTxEnvelope, ScriptOrModule, TransactionData},
+        UserError,
     },
     alloy::{
-        consensus::{transaction::TxEip1559, SignableTransaction, TxEnvelope},
+        consensus::{transaction::TxEip1559, SignableTransaction, TxEnvelope, TxLegacy},
         network::TxSignerSync,
         primitives::{address, hex, keccak256, Address, Bytes, FixedBytes, TxKind},
         rlp::Encodable,
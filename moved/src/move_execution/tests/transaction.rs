@@ -142,3 +142,240 @@ fn test_out_of_gas() {
     let err = ctx.execute_tx(&transaction).unwrap_err();
     assert_eq!(err.to_string(), "Insufficient intrinsic gas");
 }
+
+#[test]
+fn test_move_only_chain_rejects_evm_transactions() {
+    let mut ctx = TestContext::with_genesis_config(GenesisConfig {
+        evm_native: false,
+        // The default state root includes the L2 contracts
+        verify_state_root: false,
+        ..GenesisConfig::dev()
+    });
+
+    // Move transactions are executed as usual
+    let module_id = ctx.deploy_contract("counter");
+    let signer_arg = MoveValue::Signer(ctx.move_address);
+    ctx.execute(&module_id, "publish", vec![&signer_arg, &MoveValue::U64(7)]);
+    let resource: u64 = ctx.get_resource("counter", "Counter", ctx.move_address);
+    assert_eq!(resource, 7);
+
+    // Deposits are minted without the bridge contract
+    let outcome = execute_move_only_deposit(
+        &mut ctx,
+        EVM_ADDRESS,
+        EVM_ADDRESS,
+        U256::from(1_000_000),
+        U256::ZERO,
+    );
+    outcome.vm_outcome.unwrap();
+    ctx.state.apply(outcome.changes).unwrap();
+    assert_eq!(ctx.get_balance(EVM_ADDRESS), U256::from(1_000_000));
+
+    // Calls to the L2 contracts are rejected, they are not deployed
+    let l2_parser = address!("4200000000000000000000000000000000000016");
+    let err = ctx
+        .transfer(l2_parser, U256::from(1_000), 0, u64::MAX, U256::ZERO)
+        .unwrap_err();
+    assert_eq!(err.to_string(), "EVM is disabled on this chain");
+    ctx.signer.nonce -= 1;
+
+    // Entry functions of the EVM module are rejected too
+    let evm_module = ModuleId::new(AccountAddress::ONE, Identifier::new("evm").unwrap());
+    let to_arg = MoveValue::Address(l2_parser.to_move_address());
+    let data_arg = MoveValue::vector_u8(Vec::new());
+    let err = ctx.execute_err(
+        &evm_module,
+        "entry_evm_call",
+        vec![&signer_arg, &to_arg, &data_arg],
+    );
+    assert_eq!(err.to_string(), "EVM is disabled on this chain");
+    ctx.signer.nonce -= 1;
+
+    // EVM bytecode cannot be deployed
+    let evm_bytecode = hex!("6080604052348015600f57600080fd5b50").to_vec();
+    let (tx_hash, tx) = create_transaction(&mut ctx.signer, TxKind::Create, evm_bytecode);
+    let err = ctx
+        .execute_tx(&TestTransaction::new(tx, tx_hash))
+        .unwrap_err();
+    assert!(matches!(err, crate::Error::InvalidTransaction(_)));
+}
+
+#[test]
+fn test_move_only_deposit_value_is_not_created() {
+    let mut ctx = TestContext::with_genesis_config(GenesisConfig {
+        evm_native: false,
+        verify_state_root: false,
+        ..GenesisConfig::dev()
+    });
+
+    // The value of a deposit must be covered by the sender's balance
+    let outcome = execute_move_only_deposit(
+        &mut ctx,
+        EVM_ADDRESS,
+        ALT_EVM_ADDRESS,
+        U256::from(100),
+        U256::from(1_000),
+    );
+    assert!(matches!(
+        outcome.vm_outcome,
+        Err(UserError::DepositFailure(_))
+    ));
+    ctx.state.apply(outcome.changes).unwrap();
+    // Only the mint is kept when the deposit fails
+    assert_eq!(ctx.get_balance(EVM_ADDRESS), U256::from(100));
+    assert_eq!(ctx.get_balance(ALT_EVM_ADDRESS), U256::ZERO);
+
+    // The value is taken from the minted amount
+    let outcome = execute_move_only_deposit(
+        &mut ctx,
+        EVM_ADDRESS,
+        ALT_EVM_ADDRESS,
+        U256::from(1_000),
+        U256::from(600),
+    );
+    outcome.vm_outcome.unwrap();
+    ctx.state.apply(outcome.changes).unwrap();
+    assert_eq!(ctx.get_balance(EVM_ADDRESS), U256::from(500));
+    assert_eq!(ctx.get_balance(ALT_EVM_ADDRESS), U256::from(600));
+}
+
+fn execute_move_only_deposit(
+    ctx: &mut TestContext,
+    from: Address,
+    to: Address,
+    mint: U256,
+    value: U256,
+) -> TransactionExecutionOutcome {
+    let tx = DepositedTx {
+        source_hash: FixedBytes::default(),
+        from,
+        to,
+        mint,
+        value,
+        gas: U64::from(u64::MAX),
+        is_system_tx: false,
+        data: Vec::new().into(),
+    };
+    let tx_hash = ExtendedTxEnvelope::DepositedTx(tx.clone()).compute_hash();
+    let test_tx = TestTransaction::new(NormalizedExtendedTxEnvelope::DepositedTx(tx), tx_hash);
+    ctx.execute_tx(&test_tx).unwrap()
+}
+
+fn unprotected_transaction(signer: &mut Signer) -> TestTransaction {
+    let mut tx = TxLegacy {
+        chain_id: None,
+        nonce: signer.nonce,
+        gas_price: 0,
+        gas_limit: u64::MAX,
+        to: TxKind::Call(ALT_EVM_ADDRESS),
+        value: U256::ZERO,
+        input: Default::default(),
+    };
+    signer.nonce += 1;
+    let signature = signer.inner.sign_transaction_sync(&mut tx).unwrap();
+    let signed_tx = TxEnvelope::Legacy(tx.into_signed(signature));
+    let tx_hash = *signed_tx.tx_hash();
+    let tx = NormalizedExtendedTxEnvelope::Canonical(signed_tx.try_into().unwrap());
+    TestTransaction::new(tx, tx_hash)
+}
+
+#[test]
+fn test_unprotected_transaction_is_accepted_when_allowed() {
+    let mut ctx = TestContext::with_genesis_config(GenesisConfig {
+        allow_unprotected_txs: true,
+        ..GenesisConfig::dev()
+    });
+
+    let transaction = unprotected_transaction(&mut ctx.signer);
+    let outcome = ctx.execute_tx(&transaction).unwrap();
+    outcome.vm_outcome.unwrap();
+}
+
+#[test]
+fn test_unprotected_transaction_is_rejected_when_not_allowed() {
+    let mut ctx = TestContext::with_genesis_config(GenesisConfig {
+        allow_unprotected_txs: false,
+        ..GenesisConfig::dev()
+    });
+
+    let transaction = unprotected_transaction(&mut ctx.signer);
+    let err = ctx.execute_tx(&transaction).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Transactions without a chain id are not accepted"
+    );
+
+    // Transactions signed with the chain id are still accepted
+    ctx.transfer(ALT_EVM_ADDRESS, U256::ZERO, 0, u64::MAX, U256::ZERO)
+        .unwrap();
+}
+
+fn deploy_module_bytes(
+    ctx: &mut TestContext,
+    module_bytes: Vec<u8>,
+) -> crate::Result<TransactionExecutionOutcome> {
+    let tx_data = module_bytes_to_tx_data(module_bytes);
+    let (tx_hash, tx) = create_transaction(&mut ctx.signer, TxKind::Create, tx_data);
+    ctx.execute_tx(&TestTransaction::new(tx, tx_hash))
+}
+
+#[test]
+fn test_module_at_size_limit_is_deployed() {
+    let module_bytes = ModuleCompileJob::new("counter", &EVM_ADDRESS.to_move_address())
+        .compile()
+        .unwrap();
+    let mut ctx = TestContext::with_genesis_config(GenesisConfig {
+        max_module_size: module_bytes.len(),
+        ..GenesisConfig::dev()
+    });
+
+    let outcome = deploy_module_bytes(&mut ctx, module_bytes).unwrap();
+    outcome.vm_outcome.unwrap();
+}
+
+#[test]
+fn test_module_over_size_limit_is_rejected() {
+    let module_bytes = ModuleCompileJob::new("counter", &EVM_ADDRESS.to_move_address())
+        .compile()
+        .unwrap();
+    let max_module_size = module_bytes.len();
+    let mut ctx = TestContext::with_genesis_config(GenesisConfig {
+        max_module_size,
+        ..GenesisConfig::dev()
+    });
+
+    // Pad the module with an unused identifier to push it over the limit
+    let mut compiled_module = CompiledModule::deserialize(&module_bytes).unwrap();
+    compiled_module
+        .identifiers
+        .push(Identifier::new("padding".repeat(16)).unwrap());
+    let mut oversized_bytes = Vec::new();
+    compiled_module.serialize(&mut oversized_bytes).unwrap();
+    let size = oversized_bytes.len();
+    assert!(size > max_module_size);
+
+    let err = deploy_module_bytes(&mut ctx, oversized_bytes).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!("Module size {size} exceeds the maximum of {max_module_size} bytes")
+    );
+}
+
+#[test]
+fn test_module_over_dependency_limit_is_rejected() {
+    let module_bytes = ModuleCompileJob::new("natives", &EVM_ADDRESS.to_move_address())
+        .compile()
+        .unwrap();
+    let mut ctx = TestContext::with_genesis_config(GenesisConfig {
+        max_module_dependencies: 1,
+        ..GenesisConfig::dev()
+    });
+
+    let err = deploy_module_bytes(&mut ctx, module_bytes).unwrap_err();
+    assert!(matches!(
+        err,
+        crate::Error::InvalidTransaction(
+            crate::InvalidTransactionCause::TooManyModuleDependencies { max: 1, .. }
+        )
+    ));
+}
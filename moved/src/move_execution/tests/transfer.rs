@@ -56,6 +56,39 @@ fn test_initiate_withdrawal() {
     assert_eq!(new_balance, mint_amount - withdraw_amount);
 }
 
+#[test]
+fn test_initiate_withdrawal_logs_are_in_emission_order() {
+    let mut ctx = TestContext::new();
+    let mint_amount = U256::from(1_000_000);
+    ctx.deposit_eth(EVM_ADDRESS, mint_amount);
+
+    let withdraw_amount = U256::from(1_000);
+    let l2_parser = address!("4200000000000000000000000000000000000016");
+    let outcome = ctx
+        .transfer(l2_parser, withdraw_amount, 0, u64::MAX, U256::ZERO)
+        .unwrap();
+    outcome.vm_outcome.unwrap();
+
+    // Topic signature of MessagePassed event emitted by the EVM
+    let message_passed_topic = B256::new(hex!(
+        "02a52367d10742d8032712c1bb8e0144ff1ec5ffda1ed7d70bb05a2744955054"
+    ));
+    let message_passed_index = outcome
+        .logs
+        .iter()
+        .position(|l| l.topics()[0] == message_passed_topic)
+        .expect("Outcome must have the MessagePassed log");
+    // The withdrawn ETH is burned after the EVM call which emits a Move event
+    let burn_event_index = outcome
+        .logs
+        .iter()
+        .rposition(|l| l.address == AccountAddress::ONE.to_eth_address())
+        .expect("Outcome must have the burn event");
+
+    assert_eq!(outcome.logs[message_passed_index].address, l2_parser);
+    assert!(message_passed_index < burn_event_index);
+}
+
 #[test]
 fn test_initiate_withdrawal_zero_balance() {
     let mut ctx = TestContext::new();
@@ -130,3 +163,35 @@ fn test_eoa_base_token_transfer() {
     assert_eq!(sender_balance, mint_amount - transfer_amount);
     assert_eq!(receiver_balance, transfer_amount);
 }
+
+#[test]
+fn test_insufficient_balance_abort_is_decoded() {
+    let mut ctx = TestContext::new();
+    ctx.deposit_eth(EVM_ADDRESS, U256::from(123));
+
+    let outcome = ctx
+        .transfer(ALT_EVM_ADDRESS, U256::from(124), 0, 100, U256::ZERO)
+        .unwrap();
+    let Err(UserError::Vm(error)) = outcome.vm_outcome else {
+        panic!("Transfer over the balance should abort");
+    };
+    let abort = decode_abort(&error, ctx.state.resolver()).expect("Error should be an abort");
+
+    assert_eq!(
+        abort.module,
+        ModuleId::new(
+            AccountAddress::ONE,
+            Identifier::new("fungible_asset_u256").unwrap()
+        )
+    );
+    assert!(abort.function.is_some());
+    assert_eq!(abort.code, 0x1_0004);
+    assert_eq!(abort.category(), Some("INVALID_ARGUMENT"));
+    assert_eq!(
+        abort.reason,
+        Some("Insufficient balance to withdraw or transfer")
+    );
+    assert!(abort
+        .to_string()
+        .ends_with("with code 0x10004: Insufficient balance to withdraw or transfer"));
+}
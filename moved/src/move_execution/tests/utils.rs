@@ -81,7 +81,11 @@ pub struct TestContext {
 impl TestContext {
     /// Creates a new test context with initialized state and default signer
     pub fn new() -> Self {
-        let genesis_config = GenesisConfig::default();
+        Self::with_genesis_config(GenesisConfig::dev())
+    }
+
+    /// Creates a new test context with state initialized according to `genesis_config`
+    pub fn with_genesis_config(genesis_config: GenesisConfig) -> Self {
         let mut state = InMemoryState::new();
         init_and_apply(&genesis_config, &mut state);
 
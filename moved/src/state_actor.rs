@@ -1,56 +1,85 @@
 pub use {
-    payload::{NewPayloadId, NewPayloadIdInput, StatePayloadId},
+    ordering::{MemPoolTx, TransactionOrdering},
+    payload::{InvalidPayloadAttributes, NewPayloadId, NewPayloadIdInput, StatePayloadId},
     queries::{
-        Balance, BlockHeight, InMemoryStateQueries, Nonce, StateMemory, StateQueries, Version,
+        Balance, BlockHeight, InMemoryStateQueries, Nonce, ProofError, ReadRetry, StateMemory,
+        StateQueries, StateRetention, StateUnavailable, Version,
     },
+    summary::BlockSummary,
 };
 
 use {
     crate::{
         block::{
-            BaseGasFee, Block, BlockHash, BlockQueries, BlockRepository, ExtendedBlock, Header,
-            HeaderForExecution,
+            BaseGasFee, Block, BlockHash, BlockHashLookup, BlockQueries, BlockRepository,
+            ExtendedBlock, Header, HeaderForExecution,
         },
         genesis::config::GenesisConfig,
         move_execution::{
-            execute_transaction,
+            evm_native::{StructLogger, StructLoggerConfig, TransactionTrace},
+            execute_transaction, pay_block_fees,
             simulate::{call_transaction, simulate_transaction},
-            BaseTokenAccounts, CreateL1GasFee, CreateL2GasFee, L1GasFee, L1GasFeeInput,
-            L2GasFeeInput, LogsBloom,
+            trace_transaction, BaseTokenAccounts, CreateL1GasFee, CreateL2GasFee, L1GasFee,
+            L1GasFeeInput, L2GasFeeInput,
         },
-        primitives::{self, Address, ToEthAddress, ToMoveAddress, ToSaturatedU64, B256, U256, U64},
-        storage::State,
+        primitives::{self, Address, ToMoveAddress, ToSaturatedU64, B256, U256, U64},
+        storage::{insert_change_set, State, ToTreeValues},
         types::{
-            queries::ProofResponse,
+            queries::{L1FeeParams, ProofResponse},
             state::{
-                Command, ExecutionOutcome, Payload, PayloadId, PayloadResponse, Query,
-                StateMessage, ToPayloadIdInput, TransactionReceipt, TransactionWithReceipt,
-                WithExecutionOutcome, WithPayloadAttributes,
+                to_rpc_transaction, BlockResponse, Command, DepositInclusion, ExecutionOutcome,
+                ExecutionPayload, ExtendedTransactionReceipt, NodeSettings, Payload, PayloadId,
+                PayloadResponse, Query, RpcTransaction, StateMessage, ToPayloadIdInput,
+                TransactionReceipt, TransactionWithReceipt, WithExecutionOutcome,
+                WithPayloadAttributes,
+            },
+            transactions::{
+                ExtendedTxEnvelope, NormalizedExtendedTxEnvelope, TransactionExecutionOutcome,
+                L1_ATTRIBUTES_DEPOSITOR,
             },
-            transactions::{ExtendedTxEnvelope, NormalizedExtendedTxEnvelope},
         },
         Error::{InvalidTransaction, InvariantViolation, User},
+        UserError,
     },
     alloy::{
-        consensus::Receipt,
+        consensus::TxEnvelope,
         eips::{
             eip2718::Encodable2718,
             BlockId,
             BlockNumberOrTag::{self, *},
         },
-        primitives::{keccak256, Bloom},
+        primitives::keccak256,
         rlp::{Decodable, Encodable},
-        rpc::types::{FeeHistory, TransactionReceipt as AlloyTxReceipt},
+        rpc::types::{FeeHistory, TransactionReceipt as AlloyTxReceipt, TransactionRequest},
     },
+    aptos_table_natives::TableResolver,
+    eth_trie::{EthTrie, Trie},
     move_binary_format::errors::PartialVMError,
-    move_core_types::effects::ChangeSet,
+    move_core_types::{effects::ChangeSet, resolver::MoveResolver},
+    op_alloy::consensus::OpTxEnvelope,
+    queries::PendingResolver,
+    receipts::BlockReceipts,
     revm::primitives::TxKind,
-    std::collections::HashMap,
+    speculation::{speculate, Speculation, SpeculationInput},
+    std::{
+        cell::RefCell,
+        collections::{HashMap, HashSet},
+        future::Future,
+        sync::Arc,
+        time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+    },
     tokio::{sync::mpsc::Receiver, task::JoinHandle},
 };
 
+/// Number of storage slots a single account proof covers at most, unless configured otherwise.
+pub const DEFAULT_MAX_PROOF_SLOTS: usize = 256;
+
+mod ordering;
 mod payload;
 mod queries;
+mod receipts;
+mod speculation;
+mod summary;
 
 #[cfg(any(feature = "test-doubles", test))]
 pub type InMemStateActor = StateActor<
@@ -67,6 +96,22 @@ pub type InMemStateActor = StateActor<
     InMemoryStateQueries,
 >;
 
+/// Limits for the gas price suggested by `eth_gasPrice`, so that wallets don't overpay during base
+/// fee spikes. The ceiling takes precedence if it is lower than the floor.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GasPriceBounds {
+    pub floor: Option<U256>,
+    pub ceiling: Option<U256>,
+}
+
+impl GasPriceBounds {
+    pub fn clamp(&self, gas_price: U256) -> U256 {
+        let gas_price = self.floor.map_or(gas_price, |floor| gas_price.max(floor));
+        self.ceiling
+            .map_or(gas_price, |ceiling| gas_price.min(ceiling))
+    }
+}
+
 /// A function invoked on a completion of new transaction execution batch.
 type OnTxBatch<S> =
     Box<dyn Fn() -> Box<dyn Fn(&mut S) + Send + Sync + 'static> + Send + Sync + 'static>;
@@ -97,7 +142,10 @@ pub struct StateActor<
     gas_fee: G,
     execution_payloads: HashMap<B256, PayloadResponse>,
     pending_payload: Option<(PayloadId, PayloadResponse)>,
-    mem_pool: HashMap<B256, (ExtendedTxEnvelope, L1GasFeeInput)>,
+    mem_pool: HashMap<B256, MemPoolTx>,
+    /// Number of transactions ever added to the mem-pool, used as their arrival sequence number.
+    mem_pool_arrivals: u64,
+    transaction_ordering: TransactionOrdering,
     state: S,
     block_repository: R,
     block_queries: Q,
@@ -107,9 +155,55 @@ pub struct StateActor<
     block_memory: M,
     state_queries: SQ,
     // tx_hash -> (tx_with_receipt, block_hash)
+    // Receipts of every block are kept regardless of the `StateRetention` of `state_queries`.
     tx_receipts: HashMap<B256, (TransactionWithReceipt, B256)>,
+    // sender -> (tx_hash, block_hash) in the order of inclusion, pruned of orphaned blocks
+    txs_by_sender: HashMap<Address, Vec<(B256, B256)>>,
+    // source_hash -> inclusion of the deposit
+    deposits: HashMap<B256, DepositInclusion>,
+    /// The head block indexed by `include_transactions`, cleared whenever a new head is added.
+    latest_block_cache: RefCell<[Option<BlockResponse>; 2]>,
+    /// Hashes of the head block and its ancestors read by `BLOCKHASH`, following the head.
+    block_hashes: Arc<BlockHashLookup>,
     on_tx_batch: OnTxBatch<Self>,
     on_tx: OnTx<Self>,
+    /// Replaces the `suggested_fee_recipient` of the payload attributes when building blocks.
+    fee_recipient: Option<Address>,
+    /// Time after which no more mem-pool transactions are included in the block being built.
+    build_deadline: Option<Duration>,
+    /// Senders allowed to submit deposits besides the L1 attributes depositor, any sender is
+    /// allowed if `None`.
+    deposit_senders: Option<HashSet<Address>>,
+    /// Gas limit deposits may be sent with at most, deposits above it are rejected.
+    max_deposit_gas: Option<u64>,
+    /// Number of transactions a block holds at most, transactions of the payload attributes are
+    /// always included even past the limit.
+    max_block_transactions: Option<usize>,
+    /// Number of blocks below the latest one the head may be moved back to at most.
+    max_reorg_depth: Option<u64>,
+    max_timestamp_drift: Option<Duration>,
+    /// Number of head updates to a block not built on top of the previous head.
+    reorg_count: u64,
+    /// Head update last refused for being too deep, so repeated requests are only reported once.
+    refused_head: Option<B256>,
+    gas_price_bounds: GasPriceBounds,
+    /// Number of storage slots a single `GetProof` query may request.
+    max_proof_slots: usize,
+    /// Executes calls against the `pending` block on top of the mem-pool transactions.
+    pending_state_calls: bool,
+    /// Executes block transactions concurrently before committing them in order.
+    parallel_execution: bool,
+    /// Adds the original Move events to transaction receipts.
+    receipt_move_events: bool,
+    /// Rejects deposits that fail [`DepositedTx::check_well_formed`] instead of executing them.
+    /// Rejected deposits are included with a failed receipt.
+    check_deposits: bool,
+    /// Logs a [`BlockSummary`] for every built block.
+    block_summary: bool,
+    /// Gas a single `EstimateGas` query simulates and returns at most.
+    max_estimate_gas: Option<u64>,
+    /// Gas a single `Call` or `EstimateGas` query executes at most, regardless of the request.
+    rpc_gas_cap: Option<u64>,
 }
 
 impl<
@@ -126,16 +220,43 @@ impl<
         SQ: StateQueries + Send + Sync + 'static,
     > StateActor<S, P, H, R, G, L1G, L2G, B, Q, M, SQ>
 {
-    pub fn spawn(mut self) -> JoinHandle<()> {
+    pub fn spawn(self) -> JoinHandle<()> {
+        self.spawn_with_shutdown(std::future::pending())
+    }
+
+    /// Spawns the actor that runs until all senders are dropped or the `shutdown` future resolves.
+    ///
+    /// On shutdown no new messages are accepted, while those already queued are still processed.
+    pub fn spawn_with_shutdown(
+        mut self,
+        shutdown: impl Future<Output = ()> + Send + 'static,
+    ) -> JoinHandle<()> {
         tokio::spawn(async move {
+            tokio::pin!(shutdown);
+            loop {
+                tokio::select! {
+                    biased;
+                    () = &mut shutdown => break,
+                    msg = self.rx.recv() => match msg {
+                        Some(msg) => self.handle_message(msg),
+                        None => return,
+                    },
+                }
+            }
+
+            self.rx.close();
             while let Some(msg) = self.rx.recv().await {
-                match msg {
-                    StateMessage::Command(msg) => self.handle_command(msg),
-                    StateMessage::Query(msg) => self.handle_query(msg),
-                };
+                self.handle_message(msg);
             }
         })
     }
+
+    fn handle_message(&mut self, msg: StateMessage) {
+        match msg {
+            StateMessage::Command(msg) => self.handle_command(msg),
+            StateMessage::Query(msg) => self.handle_query(msg),
+        };
+    }
 }
 
 impl<
@@ -145,8 +266,8 @@ impl<
         R: BlockRepository<Storage = M>,
         G: BaseGasFee,
         L1G: CreateL1GasFee,
-        L2G: CreateL2GasFee,
-        B: BaseTokenAccounts,
+        L2G: CreateL2GasFee + Sync,
+        B: BaseTokenAccounts + Sync,
         Q: BlockQueries<Storage = M>,
         M,
         SQ: StateQueries,
@@ -172,7 +293,7 @@ impl<
         on_tx: OnTx<Self>,
         on_tx_batch: OnTxBatch<Self>,
     ) -> Self {
-        Self {
+        let mut state_actor = Self {
             genesis_config,
             rx,
             head,
@@ -181,6 +302,15 @@ impl<
             execution_payloads: HashMap::new(),
             pending_payload: None,
             mem_pool: HashMap::new(),
+            mem_pool_arrivals: 0,
+            transaction_ordering: TransactionOrdering::default(),
+            pending_state_calls: false,
+            parallel_execution: false,
+            receipt_move_events: false,
+            check_deposits: false,
+            block_summary: true,
+            max_estimate_gas: None,
+            rpc_gas_cap: None,
             state,
             block_hash,
             block_repository,
@@ -192,8 +322,180 @@ impl<
             block_memory,
             state_queries,
             tx_receipts: HashMap::new(),
+            txs_by_sender: HashMap::new(),
+            deposits: HashMap::new(),
+            latest_block_cache: RefCell::default(),
+            block_hashes: Arc::default(),
             on_tx,
             on_tx_batch,
+            fee_recipient: None,
+            build_deadline: None,
+            deposit_senders: None,
+            max_deposit_gas: None,
+            max_block_transactions: None,
+            max_reorg_depth: None,
+            max_timestamp_drift: None,
+            reorg_count: 0,
+            refused_head: None,
+            gas_price_bounds: GasPriceBounds::default(),
+            max_proof_slots: DEFAULT_MAX_PROOF_SLOTS,
+        };
+        // Blocks built right after a restart read the hashes already in the repository
+        state_actor.sync_block_hashes();
+        state_actor
+    }
+
+    /// Builds all blocks with `fee_recipient` as the beneficiary, ignoring the one suggested by
+    /// the consensus layer.
+    pub fn with_fee_recipient(self, fee_recipient: Address) -> Self {
+        Self {
+            fee_recipient: Some(fee_recipient),
+            ..self
+        }
+    }
+
+    /// Seals blocks with the transactions executed within `build_deadline`, deferring the rest of
+    /// the mem-pool to the next block. Transactions of the payload attributes are always included.
+    pub fn with_build_deadline(self, build_deadline: Duration) -> Self {
+        Self {
+            build_deadline: Some(build_deadline),
+            ..self
+        }
+    }
+
+    /// Rejects deposits unless they are sent by the L1 attributes depositor or one of
+    /// `deposit_senders`. Rejected deposits are included with a failed receipt and no state
+    /// changes, as derived deposits cannot be left out of a block.
+    pub fn with_deposit_senders(self, deposit_senders: impl IntoIterator<Item = Address>) -> Self {
+        Self {
+            deposit_senders: Some(deposit_senders.into_iter().collect()),
+            ..self
+        }
+    }
+
+    /// Rejects deposits with a gas limit above `max_deposit_gas` instead of executing them,
+    /// including them with a failed receipt.
+    pub fn with_max_deposit_gas(self, max_deposit_gas: u64) -> Self {
+        Self {
+            max_deposit_gas: Some(max_deposit_gas),
+            ..self
+        }
+    }
+
+    /// Limits blocks to `max_block_transactions`, deferring the rest of the mem-pool to the next
+    /// block. Transactions of the payload attributes are always included.
+    pub fn with_max_block_transactions(self, max_block_transactions: usize) -> Self {
+        Self {
+            max_block_transactions: Some(max_block_transactions),
+            ..self
+        }
+    }
+
+    /// Refuses head updates to blocks more than `max_reorg_depth` blocks below the latest one,
+    /// such deep reorgs likely indicate a problem with L1.
+    pub fn with_max_reorg_depth(self, max_reorg_depth: u64) -> Self {
+        Self {
+            max_reorg_depth: Some(max_reorg_depth),
+            ..self
+        }
+    }
+
+    /// Refuses to build blocks with a timestamp more than `max_timestamp_drift` ahead of the local
+    /// clock or not after the timestamp of the parent block.
+    pub fn with_max_timestamp_drift(self, max_timestamp_drift: Duration) -> Self {
+        Self {
+            max_timestamp_drift: Some(max_timestamp_drift),
+            ..self
+        }
+    }
+
+    /// Orders mem-pool transactions of different accounts in built blocks by
+    /// `transaction_ordering` instead of by tip.
+    pub fn with_transaction_ordering(self, transaction_ordering: TransactionOrdering) -> Self {
+        Self {
+            transaction_ordering,
+            ..self
+        }
+    }
+
+    /// Executes `eth_call` against the `pending` block on top of the latest state with the
+    /// mem-pool transactions applied. Every such call executes the whole mem-pool.
+    pub fn with_pending_state_calls(self) -> Self {
+        Self {
+            pending_state_calls: true,
+            ..self
+        }
+    }
+
+    /// Executes the canonical transactions of built blocks concurrently against the state at the
+    /// start of the block. Transactions are still committed in order, those that read state
+    /// written by an earlier transaction of the block are executed again.
+    ///
+    /// All transactions are taken from the mem-pool before execution starts, the build deadline
+    /// stops committing them instead and returns the rest to the mem-pool.
+    pub fn with_parallel_execution(self) -> Self {
+        Self {
+            parallel_execution: true,
+            ..self
+        }
+    }
+
+    /// Adds the Move events emitted by a transaction to its receipt, next to the Ethereum logs
+    /// approximating them.
+    pub fn with_receipt_move_events(self) -> Self {
+        Self {
+            receipt_move_events: true,
+            ..self
+        }
+    }
+
+    /// Rejects deposits with a missing source hash, a zero sender or recipient, or a value above
+    /// the minted amount instead of executing them, including them with a failed receipt.
+    pub fn with_deposit_checks(self) -> Self {
+        Self {
+            check_deposits: true,
+            ..self
+        }
+    }
+
+    /// Stops gas estimation after `max_estimate_gas`, estimates are also bounded by the gas limit
+    /// of the latest block.
+    pub fn with_max_estimate_gas(self, max_estimate_gas: u64) -> Self {
+        Self {
+            max_estimate_gas: Some(max_estimate_gas),
+            ..self
+        }
+    }
+
+    /// Stops calls and gas estimation after `rpc_gas_cap`, even if the request asks for more.
+    pub fn with_rpc_gas_cap(self, rpc_gas_cap: u64) -> Self {
+        Self {
+            rpc_gas_cap: Some(rpc_gas_cap),
+            ..self
+        }
+    }
+
+    /// Stops logging a [`BlockSummary`] for every built block.
+    pub fn without_block_summary(self) -> Self {
+        Self {
+            block_summary: false,
+            ..self
+        }
+    }
+
+    /// Clamps the gas price suggested by `eth_gasPrice` to `gas_price_bounds`.
+    pub fn with_gas_price_bounds(self, gas_price_bounds: GasPriceBounds) -> Self {
+        Self {
+            gas_price_bounds,
+            ..self
+        }
+    }
+
+    /// Rejects proof queries for more than `max_proof_slots` storage slots.
+    pub fn with_max_proof_slots(self, max_proof_slots: usize) -> Self {
+        Self {
+            max_proof_slots,
+            ..self
         }
     }
 
@@ -207,127 +509,351 @@ impl<
 
     pub fn handle_query(&self, msg: Query) {
         match msg {
-            Query::ChainId { response_channel } => response_channel.send(self.genesis_config.chain_id).ok(),
+            Query::ChainId { response_channel } => {
+                response_channel.send(self.genesis_config.chain_id).ok()
+            }
+            Query::NodeSettings { response_channel } => response_channel
+                .send(NodeSettings {
+                    chain_id: self.genesis_config.chain_id,
+                    state_retention: self.state_queries.retention(),
+                    dev: self.genesis_config.dev,
+                })
+                .ok(),
             Query::BalanceByHeight {
                 address,
                 response_channel,
                 height,
             } => response_channel
-                .send(self.state_queries.balance_at(self.state.db(), address.to_move_address(), self.resolve_height(height)))
+                .send(self.try_query_state_at(height, |height| {
+                    self.state_queries.balance_at(
+                        self.state.db(),
+                        address.to_move_address(),
+                        height,
+                    )
+                }))
                 .ok(),
             Query::NonceByHeight {
                 address,
                 response_channel,
                 height,
             } => response_channel
-                .send(self.state_queries.nonce_at(self.state.db(), address.to_move_address(), self.resolve_height(height)))
+                .send(self.try_query_state_at(height, |height| {
+                    self.state_queries
+                        .nonce_at(self.state.db(), address.to_move_address(), height)
+                }))
+                .ok(),
+            Query::AccountByHeight {
+                address,
+                response_channel,
+                height,
+            } => response_channel
+                .send(self.try_query_state_at(height, |height| {
+                    self.state_queries.account_at(
+                        self.state.db(),
+                        address.to_move_address(),
+                        height,
+                    )
+                }))
+                .ok(),
+            Query::ResourceExists {
+                address,
+                resource,
+                height,
+                response_channel,
+            } => response_channel
+                .send(self.try_query_state_at(height, |height| {
+                    self.state_queries.resource_exists_at(
+                        self.state.db(),
+                        address,
+                        &resource,
+                        height,
+                    )
+                }))
                 .ok(),
             Query::BlockByHash {
                 hash,
                 response_channel,
                 include_transactions,
             } => response_channel
-                .send(self.block_queries.by_hash(&self.block_memory, hash, include_transactions))
+                .send(
+                    self.block_queries
+                        .by_hash(&self.block_memory, hash, include_transactions),
+                )
                 .ok(),
             Query::BlockByHeight {
                 height,
                 response_channel,
                 include_transactions,
             } => response_channel
-                .send(self.block_queries.by_height(&self.block_memory, self.resolve_height(height), include_transactions))
+                .send(self.block_by_height(self.resolve_height(height), include_transactions))
                 .ok(),
-            Query::BlockNumber {
+            Query::ExtendedBlock {
+                block_id,
                 response_channel,
-            } => response_channel
-                .send(self.height)
-                .ok(),
+            } => response_channel.send(self.extended_block(block_id)).ok(),
+            Query::L1FeeParams { response_channel } => {
+                response_channel.send(self.l1_fee_params()).ok()
+            }
+            Query::BlockNumber { response_channel } => response_channel.send(self.height).ok(),
+            Query::GasPrice { response_channel } => response_channel.send(self.gas_price()).ok(),
             Query::FeeHistory {
+                block_count,
+                block_number,
+                reward_percentiles,
                 response_channel,
-                ..
-                // TODO: Respond with a real fee history
-            } => response_channel.send(FeeHistory::default()).ok(),
+            } => response_channel
+                .send(self.fee_history(block_count, block_number, reward_percentiles))
+                .ok(),
             Query::EstimateGas {
-                transaction,
+                mut transaction,
                 block_number,
                 response_channel,
             } => {
-                // TODO: Support gas estimation from arbitrary blocks
-                let block_height = match block_number {
-                    Number(height) => height,
-                    Finalized | Pending | Latest | Safe => self.height,
-                    Earliest => 0,
-                };
-                // TODO: simulation should account for gas from non-zero L1 fee
-                let outcome = simulate_transaction(transaction, self.state.resolver(), &self.genesis_config, &self.base_token, block_height);
-                match outcome {
-                    Ok(outcome) => response_channel.send(Ok(1000 * outcome.gas_used)).ok(),
-                    Err(e) => response_channel.send(Err(e)).ok(),
+                let gas_cap = self.estimate_gas_cap();
+                if let Some(gas_cap) = gas_cap {
+                    // The simulation stops at the cap instead of running as long as requested
+                    transaction.gas = Some(transaction.gas.map_or(gas_cap, |gas| gas.min(gas_cap)));
                 }
+                // TODO: simulation should account for gas from non-zero L1 fee
+                let outcome = self
+                    .query_state_at(block_number, |height| {
+                        let block_hashes = self.block_hashes_at(height);
+                        // Table entries are only available in the latest state
+                        if height == self.height {
+                            return Some(simulate_transaction(
+                                transaction,
+                                self.state.resolver(),
+                                &self.genesis_config,
+                                &self.base_token,
+                                height,
+                                block_hashes,
+                            ));
+                        }
+                        let resolver = self.state_queries.resolver_at(self.state.db(), height)?;
+                        Some(simulate_transaction(
+                            transaction,
+                            &resolver,
+                            &self.genesis_config,
+                            &self.base_token,
+                            height,
+                            block_hashes,
+                        ))
+                    })
+                    .unwrap_or_else(|e| Err(e.into()));
+                let estimate = outcome.and_then(|outcome| {
+                    let estimate = 1000 * outcome.gas_used;
+                    match gas_cap {
+                        None => Ok(estimate),
+                        // Using up the whole cap means the execution was cut short
+                        Some(gas_cap) if outcome.gas_used >= gas_cap => {
+                            Err(UserError::GasCapExceeded(gas_cap).into())
+                        }
+                        Some(gas_cap) => Ok(estimate.min(gas_cap)),
+                    }
+                });
+                response_channel.send(estimate).ok()
             }
             Query::Call {
-                transaction,
+                mut transaction,
+                block_number,
                 response_channel,
-                ..
             } => {
-                // TODO: Support transaction call from arbitrary blocks
-                let outcome = call_transaction(transaction, self.state.resolver(), &self.genesis_config, &self.base_token);
+                if let Some(gas_cap) = self.rpc_gas_cap {
+                    // Requests without a gas limit would otherwise run with `u64::MAX`
+                    transaction.gas = Some(transaction.gas.map_or(gas_cap, |gas| gas.min(gas_cap)));
+                }
+                let outcome = if block_number == Pending && self.pending_state_calls {
+                    self.call_pending(transaction)
+                } else {
+                    self.query_state_at(block_number, |height| {
+                        let block_header = self.header_for_call(height);
+                        // Table entries are only available in the latest state
+                        if height == self.height {
+                            return Some(call_transaction(
+                                transaction,
+                                self.state.resolver(),
+                                &self.genesis_config,
+                                &self.base_token,
+                                block_header,
+                            ));
+                        }
+                        let resolver = self.state_queries.resolver_at(self.state.db(), height)?;
+                        Some(call_transaction(
+                            transaction,
+                            &resolver,
+                            &self.genesis_config,
+                            &self.base_token,
+                            block_header,
+                        ))
+                    })
+                    .unwrap_or_else(|e| Err(e.into()))
+                };
                 response_channel.send(outcome).ok()
             }
-            Query::TransactionReceipt { tx_hash, response_channel } => {
-                response_channel.send(self.query_transaction_receipt(tx_hash)).ok()
-            }
-            Query::GetProof { address, storage_slots, height, response_channel } => {
-                response_channel.send(
-                    self.get_proof(
-                        address,
-                        storage_slots,
-                        height,
-                    )
-                ).ok()
-            }
+            Query::TransactionReceipt {
+                tx_hash,
+                response_channel,
+            } => response_channel
+                .send(self.query_extended_transaction_receipt(tx_hash))
+                .ok(),
+            Query::TransactionsBySender {
+                address,
+                offset,
+                limit,
+                response_channel,
+            } => response_channel
+                .send(self.transactions_by_sender(address, offset, limit))
+                .ok(),
+            Query::TransactionByHash {
+                tx_hash,
+                response_channel,
+            } => response_channel
+                .send(self.query_transaction_by_hash(tx_hash))
+                .ok(),
+            Query::DepositStatus {
+                source_hash,
+                response_channel,
+            } => response_channel
+                .send(self.deposits.get(&source_hash).copied())
+                .ok(),
+            Query::GetProof {
+                address,
+                storage_slots,
+                height,
+                response_channel,
+            } => response_channel
+                .send(self.get_proof(address, storage_slots, height))
+                .ok(),
+            Query::TraceTransaction {
+                tx_hash,
+                config,
+                response_channel,
+            } => response_channel
+                .send(self.trace_transaction(tx_hash, config))
+                .ok(),
+            Query::ExecutePayload {
+                payload,
+                response_channel,
+            } => response_channel.send(self.execute_payload(&payload)).ok(),
         };
     }
 
+    /// Runs `query` against the state at `height` unless that state has been pruned.
+    fn query_state_at<T>(
+        &self,
+        height: BlockNumberOrTag,
+        query: impl FnOnce(BlockHeight) -> Option<T>,
+    ) -> Result<T, StateUnavailable> {
+        self.try_query_state_at(height, |height| {
+            query(height).ok_or(StateUnavailable::BlockNotFound)
+        })
+    }
+
+    /// Same as [`Self::query_state_at`], but the `query` reports why the state is unavailable.
+    fn try_query_state_at<T>(
+        &self,
+        height: BlockNumberOrTag,
+        query: impl FnOnce(BlockHeight) -> Result<T, StateUnavailable>,
+    ) -> Result<T, StateUnavailable> {
+        let height = self.resolve_height(height);
+        if self.state_queries.is_pruned(height) {
+            return Err(StateUnavailable::Pruned(height));
+        }
+        query(height)
+    }
+
     fn get_proof(
         &self,
         address: Address,
         storage_slots: Vec<U256>,
         height: BlockId,
-    ) -> Option<ProofResponse> {
+    ) -> Result<ProofResponse, ProofError> {
+        if storage_slots.len() > self.max_proof_slots {
+            return Err(ProofError::TooManyStorageSlots {
+                requested: storage_slots.len(),
+                max: self.max_proof_slots,
+            });
+        }
         let height = match height {
-            BlockId::Number(n) => self.resolve_height(n),
-            BlockId::Hash(h) => {
-                self.block_queries
-                    .by_hash(&self.block_memory, h.block_hash, false)?
-                    .0
-                    .header
-                    .number
-            }
+            BlockId::Number(n) => n,
+            BlockId::Hash(h) => self
+                .block_queries
+                .by_hash(&self.block_memory, h.block_hash, false)
+                .ok_or(StateUnavailable::BlockNotFound)?
+                .0
+                .header
+                .number
+                .into(),
         };
-        self.state_queries.get_proof(
-            self.state.db(),
-            address.to_move_address(),
-            &storage_slots,
-            height,
-        )
+        let proof = self.query_state_at(height, |height| {
+            self.state_queries.get_proof(
+                self.state.db(),
+                address.to_move_address(),
+                &storage_slots,
+                height,
+            )
+        })?;
+        Ok(proof)
     }
 
     pub fn handle_command(&mut self, msg: Command) {
         match msg {
             Command::UpdateHead { block_hash } => {
+                // The consensus layer repeats the current head until a new block is built
+                if block_hash == self.head {
+                    return;
+                }
+                if let Some(depth) = self.reorg_depth_past_limit(block_hash) {
+                    if self.refused_head.replace(block_hash) != Some(block_hash) {
+                        println!(
+                            "ERROR: Refusing to reorg {depth} blocks back to {block_hash}, deeper \
+                            than the configured limit"
+                        );
+                    }
+                    return;
+                }
+                let is_reorg = self
+                    .block_repository
+                    .by_hash(&self.block_memory, block_hash)
+                    .is_some_and(|block| block.block.header.parent_hash != self.head);
+                if is_reorg {
+                    self.reorg_count += 1;
+                    println!(
+                        "WARN: Reorg #{} from head {} to {block_hash}",
+                        self.reorg_count, self.head
+                    );
+                    let orphaned = self.orphaned_blocks(block_hash);
+                    for tx_hashes in self.txs_by_sender.values_mut() {
+                        tx_hashes.retain(|(_, block_hash)| !orphaned.contains(block_hash));
+                    }
+                    self.txs_by_sender
+                        .retain(|_, tx_hashes| !tx_hashes.is_empty());
+                }
                 self.head = block_hash;
+                self.sync_block_hashes();
             }
             Command::StartBlockBuild {
                 payload_attributes,
                 response_channel,
             } => {
+                if let Err(e) = self.validate_timestamp(&payload_attributes) {
+                    println!("WARN: Refusing to build block: {e}");
+                    response_channel.send(Err(e)).ok();
+                    return;
+                }
                 let input = payload_attributes.to_payload_id_input(&self.head);
                 let id = self.payload_id.new_payload_id(input);
-                response_channel.send(id).ok();
+                response_channel.send(Ok(id)).ok();
+                let build_start = Instant::now();
                 let block = self.create_block(payload_attributes);
+                if self.block_summary {
+                    let summary = self.block_summary(&block, build_start.elapsed());
+                    println!("INFO: Built block {summary}");
+                }
                 self.block_repository
                     .add(&mut self.block_memory, block.clone());
                 self.height += 1;
+                self.latest_block_cache.take();
                 self.pending_payload
                     .replace((id, PayloadResponse::from_block(block)));
             }
@@ -359,37 +885,36 @@ impl<
             }
             Command::AddTransaction { tx } => {
                 let tx_hash = tx.tx_hash().0.into();
-                let mut encoded = Vec::new();
-                tx.encode(&mut encoded);
-                let encoded = encoded.as_slice().into();
-                self.mem_pool
-                    .insert(tx_hash, (ExtendedTxEnvelope::Canonical(tx), encoded));
+                let mem_pool_tx = MemPoolTx {
+                    l1_cost_input: l1_cost_input(&tx),
+                    tx: ExtendedTxEnvelope::Canonical(tx),
+                    arrival: self.mem_pool_arrivals,
+                };
+                self.mem_pool_arrivals += 1;
+                self.mem_pool.insert(tx_hash, mem_pool_tx);
             }
             Command::GenesisUpdate { block } => {
                 self.head = block.hash;
                 self.block_repository.add(&mut self.block_memory, block);
+                self.latest_block_cache.take();
+                self.sync_block_hashes();
             }
         }
     }
 
-    fn create_block(&mut self, payload_attributes: Payload) -> ExtendedBlock {
-        // Include transactions from both `payload_attributes` and internal mem-pool
-        let transactions = payload_attributes
-            .transactions
-            .iter()
-            .filter_map(|tx_bytes| {
-                let mut slice: &[u8] = tx_bytes.as_ref();
-                let tx_hash = B256::new(keccak256(slice).0);
-                let tx = ExtendedTxEnvelope::decode(&mut slice)
-                    .inspect_err(|_| {
-                        println!("WARN: Failed to RLP decode transaction in payload_attributes")
-                    })
-                    .ok()?;
+    /// Depth of the reorg that makes `block_hash` the head if it exceeds `max_reorg_depth`.
+    fn reorg_depth_past_limit(&self, block_hash: B256) -> Option<u64> {
+        let max_reorg_depth = self.max_reorg_depth?;
+        let block = self
+            .block_repository
+            .by_hash(&self.block_memory, block_hash)?;
+        let depth = self.height.saturating_sub(block.block.header.number);
+        (depth > max_reorg_depth).then_some(depth)
+    }
 
-                Some((tx_hash, (tx, L1GasFeeInput::from(slice))))
-            })
-            .chain(self.mem_pool.drain())
-            .collect::<Vec<_>>();
+    /// Executes `transaction` on top of the latest state with the mem-pool transactions applied in
+    /// block order, without modifying the state.
+    fn call_pending(&self, transaction: TransactionRequest) -> crate::Result<Vec<u8>> {
         let parent = self
             .block_repository
             .by_hash(&self.block_memory, self.head)
@@ -399,83 +924,527 @@ impl<
             parent.block.header.gas_used,
             U256::from(parent.block.header.base_fee_per_gas.unwrap_or_default()),
         );
-
-        let header_for_execution = HeaderForExecution {
+        let block_header = HeaderForExecution {
             number: self.height + 1,
-            timestamp: payload_attributes.timestamp.as_limbs()[0],
-            prev_randao: payload_attributes.prev_randao,
+            timestamp: parent.block.header.timestamp,
+            prev_randao: parent.block.header.mix_hash,
+            block_hashes: self.block_hashes.clone(),
         };
-        let (execution_outcome, receipts) = self.execute_transactions(
-            transactions
-                .into_iter()
-                .map(|(tx_hash, (tx, bytes))| (tx_hash, tx, bytes)),
-            base_fee,
-            &header_for_execution,
-        );
-
-        let transactions_root =
-            alloy_trie::root::ordered_trie_root_with_encoder(&receipts, |rx, buf| {
-                rx.tx.encode_2718(buf)
-            });
-        // TODO: is this the correct withdrawals root calculation?
-        let withdrawals_root = alloy_trie::root::ordered_trie_root(&payload_attributes.withdrawals);
-        let total_tip = execution_outcome.total_tip;
+        let l2_fee = self.l2_fee.with_gas_fee_multiplier(U256::from(1));
 
-        let header = Header {
-            parent_hash: self.head,
-            number: header_for_execution.number,
-            transactions_root,
-            withdrawals_root: Some(withdrawals_root),
-            base_fee_per_gas: Some(base_fee.saturating_to()),
-            blob_gas_used: Some(0),
-            excess_blob_gas: Some(0),
-            ..Default::default()
+        let mut resolver = PendingResolver::new(self.state.resolver());
+        let mem_pool = self.mem_pool.clone();
+        for (tx_hash, mem_pool_tx) in self.transaction_ordering.order(mem_pool, base_fee) {
+            let Ok(tx) = NormalizedExtendedTxEnvelope::try_from(mem_pool_tx.tx) else {
+                continue;
+            };
+            let l2_gas_input = L2GasFeeInput::new(tx.gas_limit(), tx.effective_gas_price(base_fee));
+            // L1 fees are skipped, they depend on the L1 attributes of the next block. Invalid
+            // transactions are left out like they would be from the block.
+            let Ok(outcome) = execute_transaction(
+                &tx,
+                &tx_hash,
+                &resolver,
+                &self.genesis_config,
+                0,
+                l2_fee.clone(),
+                l2_gas_input,
+                &self.base_token,
+                block_header.clone(),
+            ) else {
+                continue;
+            };
+            resolver
+                .apply(outcome.changes)
+                .expect("Sequential transaction changes must merge");
         }
-        .with_payload_attributes(payload_attributes)
-        .with_execution_outcome(execution_outcome);
-
-        let hash = self.block_hash.block_hash(&header);
 
-        let transactions: Vec<_> = receipts
-            .into_iter()
-            .map(|v| {
-                let tx = v.tx.clone();
-                self.tx_receipts.insert(v.tx_hash, (v, hash));
-                tx
-            })
-            .collect();
+        call_transaction(
+            transaction,
+            &resolver,
+            &self.genesis_config,
+            &self.base_token,
+            block_header,
+        )
+    }
 
-        Block::new(header, transactions)
-            .with_hash(hash)
-            .with_value(total_tip)
+    /// Executes the transaction with `tx_hash` again on top of the state of its parent block,
+    /// recording the opcodes executed by the EVM according to `config`.
+    fn trace_transaction(
+        &self,
+        tx_hash: B256,
+        config: StructLoggerConfig,
+    ) -> crate::Result<Option<TransactionTrace>> {
+        let Some((target, block_hash)) = self.tx_receipts.get(&tx_hash) else {
+            return Ok(None);
+        };
+        let block = self
+            .block_repository
+            .by_hash(&self.block_memory, *block_hash)
+            .expect("Block of an included transaction should exist");
+        // Genesis has no transactions, so the block always has a parent
+        let parent_height = block.block.header.number - 1;
+        self.try_query_state_at(Number(parent_height), |height| {
+            // Table entries are only available in the latest state
+            if height == self.height {
+                return Ok(self.trace_in_block(&block, target, self.state.resolver(), config));
+            }
+            let resolver = self
+                .state_queries
+                .resolver_at(self.state.db(), height)
+                .ok_or(StateUnavailable::BlockNotFound)?;
+            Ok(self.trace_in_block(&block, target, &resolver, config))
+        })
+        .unwrap_or_else(|e| Err(e.into()))
+        .map(Some)
     }
 
-    fn execute_transactions(
-        &mut self,
-        transactions: impl Iterator<Item = (B256, ExtendedTxEnvelope, L1GasFeeInput)>,
-        base_fee: U256,
-        block_header: &HeaderForExecution,
+    /// Traces the `target` transaction of `block` on top of the `parent` state with the
+    /// transactions preceding it in the block applied, the same way they were when building it.
+    fn trace_in_block(
+        &self,
+        block: &ExtendedBlock,
+        target: &TransactionWithReceipt,
+        parent: &(impl MoveResolver<PartialVMError> + TableResolver),
+        config: StructLoggerConfig,
+    ) -> crate::Result<TransactionTrace> {
+        let header = &block.block.header;
+        let base_fee = U256::from(header.base_fee_per_gas.unwrap_or_default());
+        let block_header = HeaderForExecution {
+            number: header.number,
+            timestamp: header.timestamp,
+            prev_randao: header.mix_hash,
+            block_hashes: self.block_hashes_of(header.parent_hash),
+        };
+        let l2_fee = self.l2_fee.with_gas_fee_multiplier(U256::from(1));
+        // The L1 fee is the one charged when building the block
+        let l1_cost = |rx: &TransactionWithReceipt| {
+            let l1_fee = rx.l1_block_info.as_ref().and_then(|info| info.l1_fee);
+            U256::from(l1_fee.unwrap_or_default()).to_saturated_u64()
+        };
+        let l2_gas_input = |rx: &TransactionWithReceipt| {
+            L2GasFeeInput::new(
+                rx.normalized_tx.gas_limit(),
+                rx.normalized_tx.effective_gas_price(base_fee),
+            )
+        };
+
+        let mut resolver = PendingResolver::new(parent);
+        let preceding = block
+            .block
+            .transactions
+            .iter()
+            .map(|tx| B256::new(keccak256(tx.encoded_2718()).0))
+            .take_while(|tx_hash| *tx_hash != target.tx_hash)
+            .filter_map(|tx_hash| self.tx_receipts.get(&tx_hash))
+            // Rejected deposits are included without state changes
+            .filter(|(rx, _)| self.deposit_rejection(&rx.normalized_tx).is_none());
+        for (rx, _) in preceding {
+            let outcome = match execute_transaction(
+                &rx.normalized_tx,
+                &rx.tx_hash,
+                &resolver,
+                &self.genesis_config,
+                l1_cost(rx),
+                l2_fee.clone(),
+                l2_gas_input(rx),
+                &self.base_token,
+                block_header.clone(),
+            ) {
+                Ok(outcome) => outcome,
+                // Invalid forced transactions are included without state changes
+                Err(InvalidTransaction(_)) => continue,
+                Err(e) => return Err(e),
+            };
+            resolver
+                .apply(outcome.changes)
+                .expect("Sequential transaction changes must merge");
+        }
+
+        let outcome = trace_transaction(
+            &target.normalized_tx,
+            &target.tx_hash,
+            &resolver,
+            &self.genesis_config,
+            l1_cost(target),
+            l2_fee,
+            l2_gas_input(target),
+            &self.base_token,
+            block_header,
+            config,
+        )?;
+        let struct_logger = outcome
+            .struct_logger
+            .unwrap_or_else(|| StructLogger::new(config));
+        Ok(struct_logger.into_trace(outcome.gas_used, outcome.vm_outcome.is_err()))
+    }
+
+    /// Header of the block at `height` for calls executed on top of its state, only the `number`
+    /// is set if the block is unknown.
+    fn header_for_call(&self, height: u64) -> HeaderForExecution {
+        let Some(block) = self.block_by_height(height, false) else {
+            return HeaderForExecution {
+                number: height,
+                ..Default::default()
+            };
+        };
+        let header = block.0.header;
+        HeaderForExecution {
+            number: height,
+            timestamp: header.timestamp,
+            prev_randao: header.mix_hash,
+            block_hashes: self.block_hashes_of(header.hash),
+        }
+    }
+
+    /// Hashes visible to `BLOCKHASH` in the block at `height`, none if the block is unknown.
+    fn block_hashes_at(&self, height: u64) -> Arc<BlockHashLookup> {
+        self.block_by_height(height, false)
+            .map(|block| self.block_hashes_of(block.0.header.hash))
+            .unwrap_or_default()
+    }
+
+    /// Hashes of the block with `hash` and its ancestors, from the cache if it ends at the block.
+    fn block_hashes_of(&self, hash: B256) -> Arc<BlockHashLookup> {
+        if self.block_hashes.last() == Some(hash) {
+            return self.block_hashes.clone();
+        }
+        Arc::new(self.block_hashes_until(hash))
+    }
+
+    /// Brings the cached block hashes up to the head, only loading them from the block repository
+    /// if the head does not directly follow the cached blocks, e.g. on start up or a reorg.
+    fn sync_block_hashes(&mut self) {
+        let last = self.block_hashes.last();
+        if last == Some(self.head) {
+            return;
+        }
+        match self.block_repository.by_hash(&self.block_memory, self.head) {
+            Some(head) if last == Some(head.block.header.parent_hash) => {
+                Arc::make_mut(&mut self.block_hashes).push(head.block.header.number, self.head)
+            }
+            _ => self.block_hashes = Arc::new(self.block_hashes_until(self.head)),
+        }
+    }
+
+    /// Collects the hashes of the blocks from the current head down to its common ancestor with
+    /// the block with `hash`, which leave the canonical chain when `hash` becomes the head.
+    fn orphaned_blocks(&self, hash: B256) -> HashSet<B256> {
+        let mut orphaned = HashSet::new();
+        let (mut old, mut new) = (self.head, hash);
+        while old != new {
+            let (Some(old_block), Some(new_block)) = (
+                self.block_repository.by_hash(&self.block_memory, old),
+                self.block_repository.by_hash(&self.block_memory, new),
+            ) else {
+                break;
+            };
+            let old_height = old_block.block.header.number;
+            let new_height = new_block.block.header.number;
+            if old_height >= new_height {
+                orphaned.insert(old);
+                old = old_block.block.header.parent_hash;
+            }
+            if new_height >= old_height {
+                new = new_block.block.header.parent_hash;
+            }
+        }
+        orphaned
+    }
+
+    /// Collects the hashes of the block with `hash` and its ancestors, as many as the `BLOCKHASH`
+    /// window of the next block covers.
+    fn block_hashes_until(&self, mut hash: B256) -> BlockHashLookup {
+        let mut ancestors = Vec::new();
+        while ancestors.len() as u64 <= BlockHashLookup::WINDOW {
+            let Some(block) = self.block_repository.by_hash(&self.block_memory, hash) else {
+                break;
+            };
+            let height = block.block.header.number;
+            ancestors.push((height, hash));
+            if height == 0 {
+                break;
+            }
+            hash = block.block.header.parent_hash;
+        }
+        let mut block_hashes = BlockHashLookup::default();
+        for (height, hash) in ancestors.into_iter().rev() {
+            block_hashes.push(height, hash);
+        }
+        block_hashes
+    }
+
+    fn validate_timestamp(
+        &self,
+        payload_attributes: &Payload,
+    ) -> Result<(), InvalidPayloadAttributes> {
+        let Some(max_drift) = self.max_timestamp_drift else {
+            return Ok(());
+        };
+        let timestamp = payload_attributes.timestamp.as_limbs()[0];
+        if let Some(parent) = self.block_repository.by_hash(&self.block_memory, self.head) {
+            let parent_timestamp = parent.block.header.timestamp;
+            if timestamp <= parent_timestamp {
+                return Err(InvalidPayloadAttributes::TimestampNotAfterParent {
+                    timestamp,
+                    parent_timestamp,
+                });
+            }
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Local clock should be past the UNIX epoch")
+            .as_secs();
+        if timestamp > now.saturating_add(max_drift.as_secs()) {
+            return Err(InvalidPayloadAttributes::TimestampInFuture {
+                timestamp,
+                now,
+                max_drift: max_drift.as_secs(),
+            });
+        }
+        Ok(())
+    }
+
+    fn create_block(&mut self, mut payload_attributes: Payload) -> ExtendedBlock {
+        if let Some(fee_recipient) = self.fee_recipient {
+            payload_attributes.suggested_fee_recipient = fee_recipient;
+        }
+
+        let deadline = self.build_deadline.map(|timeout| Instant::now() + timeout);
+
+        // Include transactions from both `payload_attributes` and internal mem-pool. The former
+        // carry the deposits and always come first in their given order.
+        let forced_transactions = payload_attributes
+            .transactions
+            .iter()
+            .filter_map(|tx_bytes| {
+                let mut slice: &[u8] = tx_bytes.as_ref();
+                let tx_hash = B256::new(keccak256(slice).0);
+                let tx = ExtendedTxEnvelope::decode(&mut slice)
+                    .inspect_err(|_| {
+                        println!("WARN: Failed to RLP decode transaction in payload_attributes")
+                    })
+                    .ok()?;
+
+                Some((tx_hash, tx, L1GasFeeInput::from(slice), true))
+            })
+            .collect::<Vec<_>>();
+        let parent = self
+            .block_repository
+            .by_hash(&self.block_memory, self.head)
+            .expect("Parent block should exist");
+        let base_fee = self.gas_fee.base_fee_per_gas(
+            parent.block.header.gas_limit,
+            parent.block.header.gas_used,
+            U256::from(parent.block.header.base_fee_per_gas.unwrap_or_default()),
+        );
+
+        let mem_pool = if payload_attributes.no_tx_pool {
+            HashMap::new()
+        } else {
+            std::mem::take(&mut self.mem_pool)
+        };
+        let mem_pool_capacity = self.max_block_transactions.map_or(usize::MAX, |max| {
+            max.saturating_sub(forced_transactions.len())
+        });
+        let mut deferred_transactions = Vec::new();
+        // Arrival of the mem-pool transactions taken for parallel execution, to return them
+        let mut arrivals = HashMap::new();
+        let parallel_execution = self.parallel_execution;
+        let mem_pool_transactions = self
+            .transaction_ordering
+            .order(mem_pool, base_fee)
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, (tx_hash, mem_pool_tx))| {
+                // Parallel execution checks the deadline when committing the transactions
+                let past_deadline = !parallel_execution
+                    && deadline.is_some_and(|deadline| Instant::now() >= deadline);
+                if past_deadline || index >= mem_pool_capacity {
+                    deferred_transactions.push((tx_hash, mem_pool_tx));
+                    return None;
+                }
+                if parallel_execution {
+                    arrivals.insert(tx_hash, mem_pool_tx.arrival);
+                }
+                Some((tx_hash, mem_pool_tx.tx, mem_pool_tx.l1_cost_input, false))
+            });
+
+        let header_for_execution = HeaderForExecution {
+            number: self.height + 1,
+            timestamp: payload_attributes.timestamp.as_limbs()[0],
+            prev_randao: payload_attributes.prev_randao,
+            block_hashes: self.block_hashes.clone(),
+        };
+        let transactions = forced_transactions.into_iter().chain(mem_pool_transactions);
+        let (execution_outcome, receipts) = if self.parallel_execution {
+            let transactions = transactions.collect::<Vec<_>>();
+            let speculations =
+                self.speculate_transactions(&transactions, base_fee, &header_for_execution);
+            let mut transactions = transactions.into_iter().peekable();
+            // Forced transactions come first and are committed regardless of the deadline
+            let committed = std::iter::from_fn(|| {
+                let past_deadline = deadline.is_some_and(|deadline| Instant::now() >= deadline);
+                transactions.next_if(|(_, _, _, forced)| *forced || !past_deadline)
+            });
+            let result = self.execute_transactions(
+                committed,
+                speculations,
+                base_fee,
+                &header_for_execution,
+                &payload_attributes.suggested_fee_recipient,
+            );
+            deferred_transactions.extend(transactions.map(|(tx_hash, tx, l1_cost_input, _)| {
+                let mem_pool_tx = MemPoolTx {
+                    tx,
+                    l1_cost_input,
+                    arrival: arrivals[&tx_hash],
+                };
+                (tx_hash, mem_pool_tx)
+            }));
+            result
+        } else {
+            self.execute_transactions(
+                transactions,
+                Vec::new(),
+                base_fee,
+                &header_for_execution,
+                &payload_attributes.suggested_fee_recipient,
+            )
+        };
+        if !deferred_transactions.is_empty() {
+            println!(
+                "WARN: Block is full or its build deadline passed, deferring {} transactions to the next block",
+                deferred_transactions.len()
+            );
+            self.mem_pool.extend(deferred_transactions);
+        }
+
+        let transactions_root =
+            alloy_trie::root::ordered_trie_root_with_encoder(&receipts, |rx, buf| {
+                rx.tx.encode_2718(buf)
+            });
+        // Per EIP-4895 the root of the trie keyed by the RLP encoded index of each withdrawal
+        let withdrawals = payload_attributes.withdrawals.clone();
+        let withdrawals_root = alloy_trie::root::ordered_trie_root(&withdrawals);
+        let total_tip = execution_outcome.total_tip;
+
+        let header = Header {
+            parent_hash: self.head,
+            number: header_for_execution.number,
+            transactions_root,
+            withdrawals_root: Some(withdrawals_root),
+            base_fee_per_gas: Some(base_fee.saturating_to()),
+            blob_gas_used: Some(0),
+            excess_blob_gas: Some(0),
+            ..Default::default()
+        }
+        .with_payload_attributes(payload_attributes)
+        .with_execution_outcome(execution_outcome);
+
+        let hash = self.block_hash.block_hash(&header);
+
+        let transactions: Vec<_> = receipts
+            .into_iter()
+            .map(|v| {
+                let tx = v.tx.clone();
+                if let NormalizedExtendedTxEnvelope::DepositedTx(deposit) = &v.normalized_tx {
+                    let inclusion = DepositInclusion {
+                        tx_hash: v.tx_hash,
+                        block_hash: hash,
+                        block_number: header.number,
+                    };
+                    self.deposits.insert(deposit.source_hash, inclusion);
+                }
+                self.txs_by_sender
+                    .entry(v.normalized_tx.sender())
+                    .or_default()
+                    .push((v.tx_hash, hash));
+                self.tx_receipts.insert(v.tx_hash, (v, hash));
+                tx
+            })
+            .collect();
+
+        Block::new(header, transactions)
+            .with_withdrawals(withdrawals)
+            .with_hash(hash)
+            .with_value(total_tip)
+    }
+
+    /// Executes the canonical `transactions` concurrently, see [`speculate`].
+    fn speculate_transactions(
+        &self,
+        transactions: &[(B256, ExtendedTxEnvelope, L1GasFeeInput, bool)],
+        base_fee: U256,
+        block_header: &HeaderForExecution,
+    ) -> Vec<Option<Speculation>> {
+        let l1_fee = transactions
+            .first()
+            .and_then(|(_, v, _, _)| v.as_deposited())
+            .map(|tx| self.l1_fee.for_deposit(tx.data.as_ref()));
+        // Deposits are left to the in-order execution, which applies the deposit settings
+        let inputs = transactions
+            .iter()
+            .map(|(tx_hash, tx, l1_cost_input, _)| {
+                if tx.as_deposited().is_some() {
+                    return None;
+                }
+                let tx = NormalizedExtendedTxEnvelope::try_from(tx.clone()).ok()?;
+                let l2_gas_input =
+                    L2GasFeeInput::new(tx.gas_limit(), tx.effective_gas_price(base_fee));
+                let l1_cost = l1_fee
+                    .as_ref()
+                    .map(|v| v.l1_fee(l1_cost_input.clone()).to_saturated_u64())
+                    .unwrap_or(0);
+                Some(SpeculationInput {
+                    tx_hash: *tx_hash,
+                    tx,
+                    l1_cost,
+                    l2_gas_input,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        speculate(
+            &inputs,
+            self.state.db(),
+            self.state.state_root(),
+            &self.genesis_config,
+            &self.l2_fee,
+            &self.base_token,
+            block_header,
+        )
+    }
+
+    /// Executes `transactions` given as `(tx_hash, tx, l1_cost_input, forced)`.
+    ///
+    /// Invalid transactions are left out of the block unless they are `forced` by the payload
+    /// attributes, in which case they are included with a failed receipt and no state changes.
+    /// The outcome of `speculations` with the same index is used instead of executing the
+    /// transaction again if it still holds.
+    fn execute_transactions(
+        &mut self,
+        transactions: impl Iterator<Item = (B256, ExtendedTxEnvelope, L1GasFeeInput, bool)>,
+        mut speculations: Vec<Option<Speculation>>,
+        base_fee: U256,
+        block_header: &HeaderForExecution,
+        fee_recipient: &Address,
     ) -> (ExecutionOutcome, Vec<TransactionWithReceipt>) {
         let on_tx = (self.on_tx)();
-        let mut total_tip = U256::ZERO;
-        let mut receipts = Vec::new();
+        let mut receipts = BlockReceipts::default();
         let mut transactions = transactions.peekable();
-        let mut cumulative_gas_used = 0u128;
-        let mut logs_bloom = Bloom::ZERO;
-        let mut tx_index = 0;
-        let mut log_offset = 0;
+        // State keys written so far, speculations that read them are outdated
+        let mut written = HashSet::new();
 
         // https://github.com/ethereum-optimism/specs/blob/9dbc6b0/specs/protocol/deposits.md#kinds-of-deposited-transactions
         let l1_fee = transactions
             .peek()
-            .and_then(|(_, v, _)| v.as_deposited())
+            .and_then(|(_, v, _, _)| v.as_deposited())
             .map(|tx| self.l1_fee.for_deposit(tx.data.as_ref()));
         let l2_fee = self.l2_fee.with_gas_fee_multiplier(U256::from(1));
 
-        // TODO: parallel transaction processing?
-        for (tx_hash, tx, l1_cost_input) in transactions {
-            let Ok(normalized_tx): Result<NormalizedExtendedTxEnvelope, _> = tx.clone().try_into()
-            else {
+        for (index, (tx_hash, tx, l1_cost_input, forced)) in transactions.enumerate() {
+            let Ok(normalized_tx) = NormalizedExtendedTxEnvelope::try_from(tx.clone()) else {
                 continue;
             };
             // TODO: implement gas limits etc. for `ExtendedTxEnvelope` so that
@@ -484,133 +1453,515 @@ impl<
                 normalized_tx.gas_limit(),
                 normalized_tx.effective_gas_price(base_fee),
             );
-            let outcome = match execute_transaction(
-                &normalized_tx,
-                &tx_hash,
-                self.state.resolver(),
-                &self.genesis_config,
-                l1_fee
-                    .as_ref()
-                    .map(|v| v.l1_fee(l1_cost_input.clone()).to_saturated_u64())
-                    .unwrap_or(0),
-                l2_fee.clone(),
-                l2_gas_input,
-                &self.base_token,
-                block_header.clone(),
-            ) {
-                Ok(outcome) => outcome,
+            let l1_cost = l1_fee
+                .as_ref()
+                .map(|v| v.l1_fee(l1_cost_input.clone()).to_saturated_u64())
+                .unwrap_or(0);
+            let speculation = speculations
+                .get_mut(index)
+                .and_then(Option::take)
+                .filter(|speculation| speculation.holds_after(&written));
+            let result = match (self.deposit_rejection(&normalized_tx), speculation) {
+                (Some(reason), _) => {
+                    println!("ERROR: Including rejected deposit {tx_hash} as failed: {reason}");
+                    let error = UserError::RejectedDeposit(reason);
+                    Ok(unexecuted_outcome(&normalized_tx, base_fee, error))
+                }
+                (None, Some(speculation)) => Ok(speculation.outcome),
+                (None, None) => execute_transaction(
+                    &normalized_tx,
+                    &tx_hash,
+                    self.state.resolver(),
+                    &self.genesis_config,
+                    l1_cost,
+                    l2_fee.clone(),
+                    l2_gas_input,
+                    &self.base_token,
+                    block_header.clone(),
+                ),
+            };
+            let (outcome, l1_cost) = match result {
+                Ok(outcome) => (outcome, l1_cost),
                 Err(User(e)) => unreachable!("User errors are handled in execution {e:?}"),
+                Err(InvalidTransaction(e)) if forced => {
+                    println!("WARN: Including invalid forced transaction {tx_hash}: {e}");
+                    let error = UserError::InvalidForcedTransaction(e);
+                    (unexecuted_outcome(&normalized_tx, base_fee, error), 0)
+                }
                 Err(InvalidTransaction(_)) => continue,
                 Err(InvariantViolation(e)) => panic!("ERROR: execution error {e:?}"),
             };
 
             let l1_block_info = l1_fee.as_ref().and_then(|x| x.l1_block_info(l1_cost_input));
+            let changes = receipts.push(
+                tx_hash,
+                tx,
+                normalized_tx,
+                outcome,
+                l1_cost,
+                l1_block_info,
+                base_fee,
+            );
 
-            on_tx(self, outcome.changes.clone());
-
-            self.state
-                .apply(outcome.changes)
-                .unwrap_or_else(|_| panic!("ERROR: state update failed for transaction {tx:?}"));
-
-            cumulative_gas_used = cumulative_gas_used.saturating_add(outcome.gas_used as u128);
-
-            let bloom = outcome.logs.iter().logs_bloom();
-            logs_bloom.accrue_bloom(&bloom);
+            on_tx(self, changes.clone());
 
-            let tx_log_offset = log_offset;
-            log_offset += outcome.logs.len() as u64;
-            let receipt = Receipt {
-                status: outcome.vm_outcome.is_ok().into(),
-                cumulative_gas_used,
-                logs: outcome.logs,
-            };
+            if self.parallel_execution {
+                written.extend(changes.to_tree_values().into_keys());
+            }
 
-            let receipt = tx.wrap_receipt(receipt, bloom);
+            self.state
+                .apply(changes)
+                .unwrap_or_else(|_| panic!("ERROR: state update failed for transaction {tx_hash}"));
+        }
 
-            total_tip = total_tip.saturating_add(
-                U256::from(outcome.gas_used).saturating_mul(normalized_tx.tip_per_gas(base_fee)),
-            );
+        if !receipts.fees.is_zero() {
+            let changes = pay_block_fees(
+                &receipts.fees,
+                fee_recipient,
+                self.state.resolver(),
+                &self.genesis_config,
+                &self.base_token,
+                block_header.clone(),
+            )
+            .unwrap_or_else(|e| panic!("ERROR: paying block fees failed {e:?}"));
 
-            receipts.push(TransactionWithReceipt {
-                tx_hash,
-                tx: tx.into(),
-                normalized_tx,
-                receipt,
-                l1_block_info,
-                gas_used: outcome.gas_used,
-                l2_gas_price: outcome.l2_price,
-                tx_index,
-                contract_address: outcome
-                    .deployment
-                    .map(|(address, _)| address.to_eth_address()),
-                logs_offset: tx_log_offset,
-            });
+            on_tx(self, changes.clone());
 
-            tx_index += 1;
+            self.state
+                .apply(changes)
+                .unwrap_or_else(|_| panic!("ERROR: state update failed for block fees"));
         }
 
         let on_tx_batch = (self.on_tx_batch)();
         on_tx_batch(self);
 
-        // Compute the receipts root by RLP-encoding each receipt to be a leaf of
-        // a merkle trie.
-        let receipts_root =
-            alloy_trie::root::ordered_trie_root_with_encoder(&receipts, |rx, buf| {
-                rx.receipt.encode(buf)
-            });
-        let logs_bloom = logs_bloom.into();
-
-        let outcome = ExecutionOutcome {
-            state_root: self.state.state_root(),
-            gas_used: U64::from(cumulative_gas_used),
-            receipts_root,
-            logs_bloom,
-            total_tip,
+        (receipts.outcome(self.state.state_root()), receipts.receipts)
+    }
+
+    /// The reason the deposit `tx` is rejected, if it is. Derived deposits cannot be left out of
+    /// a block, so rejected ones are included with a failed receipt and no state changes.
+    fn deposit_rejection(&self, tx: &NormalizedExtendedTxEnvelope) -> Option<&'static str> {
+        let NormalizedExtendedTxEnvelope::DepositedTx(deposit) = tx else {
+            return None;
         };
-        (outcome, receipts)
+        if !self.is_allowed_deposit_sender(&deposit.from) {
+            return Some("sender is not allowed");
+        }
+        if self
+            .max_deposit_gas
+            .is_some_and(|max_deposit_gas| deposit.gas > U64::from(max_deposit_gas))
+        {
+            return Some("gas limit exceeds the maximum deposit gas");
+        }
+        if self.check_deposits {
+            return deposit.check_well_formed().err();
+        }
+        None
     }
 
-    fn query_transaction_receipt(&self, tx_hash: B256) -> Option<TransactionReceipt> {
-        let (rx, block_hash) = self.tx_receipts.get(&tx_hash)?;
-        let block = self
-            .block_queries
-            .by_hash(&self.block_memory, *block_hash, false)?;
-        let contract_address = rx.contract_address;
-        let (to, from) = match &rx.normalized_tx {
-            NormalizedExtendedTxEnvelope::Canonical(tx) => {
-                let to = match tx.to {
-                    TxKind::Call(to) => Some(to),
-                    TxKind::Create => None,
-                };
-                (to, tx.signer)
+    /// Executes the transactions of `payload` on top of the state of its parent block the same way
+    /// the forced transactions of a block being built are, without modifying the state.
+    ///
+    /// Returns `None` if the parent block is unknown.
+    fn execute_payload(
+        &self,
+        payload: &ExecutionPayload,
+    ) -> crate::Result<Option<ExecutionOutcome>> {
+        let Some(parent) = self
+            .block_repository
+            .by_hash(&self.block_memory, payload.parent_hash)
+        else {
+            return Ok(None);
+        };
+        self.try_query_state_at(Number(parent.block.header.number), |height| {
+            // Table entries are only available in the latest state
+            if height == self.height {
+                return Ok(self.execute_payload_on(
+                    payload,
+                    self.state.resolver(),
+                    self.state.state_root(),
+                    height,
+                ));
             }
-            NormalizedExtendedTxEnvelope::DepositedTx(tx) => (Some(tx.to), tx.from),
+            let resolver = self
+                .state_queries
+                .resolver_at(self.state.db(), height)
+                .ok_or(StateUnavailable::BlockNotFound)?;
+            let root = self
+                .state_queries
+                .state_root_at(height)
+                .ok_or(StateUnavailable::BlockNotFound)?;
+            Ok(self.execute_payload_on(payload, &resolver, root, height))
+        })
+        .unwrap_or_else(|e| Err(e.into()))
+        .map(Some)
+    }
+
+    /// Executes the transactions of `payload` on top of the `parent` state at `parent_height`,
+    /// computing the state root from the trie with `parent_root`.
+    ///
+    /// Every transaction is executed as forced, so invalid ones are kept with a failed receipt.
+    /// The L1 fee of canonical transactions is charged for their network encoding, the same as
+    /// for mem-pool transactions.
+    fn execute_payload_on(
+        &self,
+        payload: &ExecutionPayload,
+        parent: &(impl MoveResolver<PartialVMError> + TableResolver),
+        parent_root: B256,
+        parent_height: BlockHeight,
+    ) -> crate::Result<ExecutionOutcome> {
+        let base_fee = payload.base_fee_per_gas;
+        let block_header = HeaderForExecution {
+            number: payload.block_number.as_limbs()[0],
+            timestamp: payload.timestamp.as_limbs()[0],
+            prev_randao: payload.prev_randao,
+            block_hashes: self.block_hashes_of(payload.parent_hash),
         };
-        let logs = rx
-            .receipt
-            .logs()
+        let transactions = payload
+            .transactions
             .iter()
-            .enumerate()
-            .map(|(internal_index, log)| alloy::rpc::types::Log {
-                inner: log.clone(),
-                block_hash: Some(*block_hash),
-                block_number: Some(block.0.header.number),
-                block_timestamp: Some(block.0.header.timestamp),
-                transaction_hash: Some(tx_hash),
-                transaction_index: Some(rx.tx_index),
-                log_index: Some(rx.logs_offset + (internal_index as u64)),
-                removed: false,
+            .filter_map(|tx_bytes| {
+                let mut slice: &[u8] = tx_bytes.as_ref();
+                let tx_hash = B256::new(keccak256(slice).0);
+                let tx = ExtendedTxEnvelope::decode(&mut slice).ok()?;
+                let l1_cost_input = match &tx {
+                    ExtendedTxEnvelope::Canonical(tx) => l1_cost_input(tx),
+                    ExtendedTxEnvelope::DepositedTx(_) => L1GasFeeInput::from(slice),
+                };
+                Some((tx_hash, tx, l1_cost_input))
             })
-            .collect();
-        let receipt = primitives::with_rpc_logs(&rx.receipt, logs);
-        let result = TransactionReceipt {
-            inner: AlloyTxReceipt {
-                inner: receipt,
-                transaction_hash: tx_hash,
-                transaction_index: Some(rx.tx_index),
-                block_hash: Some(*block_hash),
-                block_number: Some(block.0.header.number),
-                gas_used: rx.gas_used as u128,
+            .collect::<Vec<_>>();
+        let l1_fee = transactions
+            .first()
+            .and_then(|(_, v, _)| v.as_deposited())
+            .map(|tx| self.l1_fee.for_deposit(tx.data.as_ref()));
+        let l2_fee = self.l2_fee.with_gas_fee_multiplier(U256::from(1));
+        let read_failed = |_| StateUnavailable::ReadFailed(parent_height);
+        let mut trie = EthTrie::from(self.state.db(), parent_root).map_err(read_failed)?;
+
+        let mut receipts = BlockReceipts::default();
+        let mut resolver = PendingResolver::new(parent);
+        for (tx_hash, tx, l1_cost_input) in transactions {
+            let Ok(normalized_tx) = NormalizedExtendedTxEnvelope::try_from(tx.clone()) else {
+                continue;
+            };
+            let l2_gas_input = L2GasFeeInput::new(
+                normalized_tx.gas_limit(),
+                normalized_tx.effective_gas_price(base_fee),
+            );
+            let l1_cost = l1_fee
+                .as_ref()
+                .map(|v| v.l1_fee(l1_cost_input.clone()).to_saturated_u64())
+                .unwrap_or(0);
+            let result = match self.deposit_rejection(&normalized_tx) {
+                Some(reason) => {
+                    let error = UserError::RejectedDeposit(reason);
+                    Ok(unexecuted_outcome(&normalized_tx, base_fee, error))
+                }
+                None => execute_transaction(
+                    &normalized_tx,
+                    &tx_hash,
+                    &resolver,
+                    &self.genesis_config,
+                    l1_cost,
+                    l2_fee.clone(),
+                    l2_gas_input,
+                    &self.base_token,
+                    block_header.clone(),
+                ),
+            };
+            let (outcome, l1_cost) = match result {
+                Ok(outcome) => (outcome, l1_cost),
+                Err(InvalidTransaction(e)) => {
+                    let error = UserError::InvalidForcedTransaction(e);
+                    (unexecuted_outcome(&normalized_tx, base_fee, error), 0)
+                }
+                Err(e) => return Err(e),
+            };
+            let l1_block_info = l1_fee.as_ref().and_then(|x| x.l1_block_info(l1_cost_input));
+            let changes = receipts.push(
+                tx_hash,
+                tx,
+                normalized_tx,
+                outcome,
+                l1_cost,
+                l1_block_info,
+                base_fee,
+            );
+            insert_change_set(&mut trie, &changes).map_err(read_failed)?;
+            resolver
+                .apply(changes)
+                .expect("Sequential transaction changes must merge");
+        }
+
+        if !receipts.fees.is_zero() {
+            let changes = pay_block_fees(
+                &receipts.fees,
+                &payload.fee_recipient,
+                &resolver,
+                &self.genesis_config,
+                &self.base_token,
+                block_header,
+            )?;
+            insert_change_set(&mut trie, &changes).map_err(read_failed)?;
+        }
+
+        let state_root = trie.root_hash().map_err(read_failed)?;
+        Ok(receipts.outcome(state_root))
+    }
+
+    /// Collects the fees of up to `block_count` blocks ending with `block_number`, where the range
+    /// is clamped to the existing blocks. The base fees include the projected one of the next block.
+    fn fee_history(
+        &self,
+        block_count: u64,
+        block_number: BlockNumberOrTag,
+        reward_percentiles: Option<Vec<f64>>,
+    ) -> FeeHistory {
+        let newest = self.resolve_height(block_number).min(self.height);
+        let oldest = (newest + 1).saturating_sub(block_count);
+        let blocks: Vec<_> = (oldest..=newest)
+            .filter_map(|height| {
+                self.block_queries
+                    .by_height(&self.block_memory, height, false)
+            })
+            .collect();
+        let Some(last) = blocks.last() else {
+            return FeeHistory::default();
+        };
+
+        let next_base_fee = self.gas_fee.base_fee_per_gas(
+            last.0.header.gas_limit,
+            last.0.header.gas_used,
+            U256::from(last.0.header.base_fee_per_gas.unwrap_or_default()),
+        );
+        let base_fee_per_gas = blocks
+            .iter()
+            .map(|block| block.0.header.base_fee_per_gas.unwrap_or_default() as u128)
+            .chain([next_base_fee.saturating_to()])
+            .collect();
+        let gas_used_ratio = blocks
+            .iter()
+            .map(|block| match block.0.header.gas_limit {
+                0 => 0.0,
+                gas_limit => block.0.header.gas_used as f64 / gas_limit as f64,
+            })
+            .collect();
+        let reward = reward_percentiles.map(|percentiles| {
+            blocks
+                .iter()
+                .map(|block| self.block_rewards(block, &percentiles))
+                .collect()
+        });
+
+        FeeHistory {
+            base_fee_per_gas,
+            gas_used_ratio,
+            oldest_block: blocks[0].0.header.number,
+            reward,
+            ..Default::default()
+        }
+    }
+
+    /// Suggests the gas price for the next block: its base fee plus the median priority fee paid
+    /// in the latest block.
+    fn gas_price(&self) -> U256 {
+        let gas_price = self
+            .block_queries
+            .by_height(&self.block_memory, self.height, false)
+            .map(|block| {
+                let header = &block.0.header;
+                let base_fee = self.gas_fee.base_fee_per_gas(
+                    header.gas_limit,
+                    header.gas_used,
+                    U256::from(header.base_fee_per_gas.unwrap_or_default()),
+                );
+                let tip = self.block_rewards(&block, &[50.0])[0];
+                base_fee.saturating_add(U256::from(tip))
+            })
+            .unwrap_or_default();
+        self.gas_price_bounds.clamp(gas_price)
+    }
+
+    /// Returns the priority fee per gas at each of the `percentiles`, weighted by the gas used by
+    /// the transactions of the `block`.
+    fn block_rewards(&self, block: &BlockResponse, percentiles: &[f64]) -> Vec<u128> {
+        let base_fee = U256::from(block.0.header.base_fee_per_gas.unwrap_or_default());
+        let mut tips: Vec<_> = block
+            .0
+            .transactions
+            .as_hashes()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|tx_hash| self.tx_receipts.get(tx_hash))
+            .map(|(rx, _)| (rx.normalized_tx.tip_per_gas(base_fee), rx.gas_used))
+            .collect();
+        tips.sort_unstable_by_key(|(tip, _)| *tip);
+        let total_gas_used: u64 = tips.iter().map(|(_, gas_used)| gas_used).sum();
+
+        percentiles
+            .iter()
+            .map(|percentile| {
+                let threshold = (total_gas_used as f64 * percentile / 100.0) as u64;
+                let mut cumulative_gas_used = 0;
+                tips.iter()
+                    .find(|(_, gas_used)| {
+                        cumulative_gas_used += gas_used;
+                        cumulative_gas_used >= threshold
+                    })
+                    .map(|(tip, _)| tip.saturating_to())
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+
+    /// The smallest of `max_estimate_gas`, `rpc_gas_cap` and the gas limit of the latest block,
+    /// if any is set.
+    fn estimate_gas_cap(&self) -> Option<u64> {
+        let block_gas_limit = self
+            .block_repository
+            .by_hash(&self.block_memory, self.head)
+            .map(|block| block.block.header.gas_limit)
+            .filter(|gas_limit| *gas_limit > 0);
+        [self.max_estimate_gas, self.rpc_gas_cap, block_gas_limit]
+            .into_iter()
+            .flatten()
+            .min()
+    }
+
+    fn block_summary(&self, block: &ExtendedBlock, build_duration: Duration) -> BlockSummary {
+        BlockSummary::new(block, build_duration, self.mem_pool.len())
+    }
+
+    fn is_allowed_deposit_sender(&self, sender: &Address) -> bool {
+        *sender == L1_ATTRIBUTES_DEPOSITOR
+            || self
+                .deposit_senders
+                .as_ref()
+                .map_or(true, |senders| senders.contains(sender))
+    }
+
+    fn block_by_height(&self, height: u64, include_transactions: bool) -> Option<BlockResponse> {
+        if height != self.height {
+            return self
+                .block_queries
+                .by_height(&self.block_memory, height, include_transactions);
+        }
+
+        let mut cache = self.latest_block_cache.borrow_mut();
+        let cached = &mut cache[usize::from(include_transactions)];
+        if cached.is_none() {
+            *cached =
+                self.block_queries
+                    .by_height(&self.block_memory, height, include_transactions);
+        }
+        cached.clone()
+    }
+
+    fn extended_block(&self, block_id: BlockId) -> Option<ExtendedBlock> {
+        let hash = match block_id {
+            BlockId::Hash(hash) => hash.block_hash,
+            BlockId::Number(height) => {
+                self.block_by_height(self.resolve_height(height), false)?
+                    .0
+                    .header
+                    .hash
+            }
+        };
+        self.block_repository.by_hash(&self.block_memory, hash)
+    }
+
+    /// Reads the parameters from the deposit the latest block starts with, the same way as when
+    /// charging the L1 fee of its transactions.
+    fn l1_fee_params(&self) -> Option<L1FeeParams> {
+        let block = self.extended_block(BlockId::Number(Latest))?;
+        let Some(OpTxEnvelope::Deposit(deposit)) = block.block.transactions.first() else {
+            return None;
+        };
+        let l1_block_info = self
+            .l1_fee
+            .for_deposit(deposit.input.as_ref())
+            .l1_block_info(L1GasFeeInput::default())?;
+        L1FeeParams::from_l1_block_info(&l1_block_info)
+    }
+
+    fn transactions_by_sender(&self, address: Address, offset: usize, limit: usize) -> Vec<B256> {
+        self.txs_by_sender
+            .get(&address)
+            .map(|tx_hashes| {
+                tx_hashes
+                    .iter()
+                    .skip(offset)
+                    .take(limit)
+                    .map(|(tx_hash, _)| *tx_hash)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn query_transaction_by_hash(&self, tx_hash: B256) -> Option<RpcTransaction> {
+        if let Some(mem_pool_tx) = self.mem_pool.get(&tx_hash) {
+            return Some(to_rpc_transaction(
+                mem_pool_tx.tx.clone().into(),
+                None,
+                None,
+                None,
+            ));
+        }
+
+        let (rx, block_hash) = self.tx_receipts.get(&tx_hash)?;
+        let block = self
+            .block_queries
+            .by_hash(&self.block_memory, *block_hash, false)?;
+        Some(to_rpc_transaction(
+            rx.tx.clone(),
+            Some(*block_hash),
+            Some(block.0.header.number),
+            Some(rx.tx_index),
+        ))
+    }
+
+    fn query_transaction_receipt(&self, tx_hash: B256) -> Option<TransactionReceipt> {
+        let (rx, block_hash) = self.tx_receipts.get(&tx_hash)?;
+        let block = self
+            .block_queries
+            .by_hash(&self.block_memory, *block_hash, false)?;
+        let contract_address = rx.contract_address;
+        let (to, from) = match &rx.normalized_tx {
+            NormalizedExtendedTxEnvelope::Canonical(tx) => {
+                let to = match tx.to {
+                    TxKind::Call(to) => Some(to),
+                    TxKind::Create => None,
+                };
+                (to, tx.signer)
+            }
+            NormalizedExtendedTxEnvelope::DepositedTx(tx) => (Some(tx.to), tx.from),
+        };
+        let logs = rx
+            .receipt
+            .logs()
+            .iter()
+            .enumerate()
+            .map(|(internal_index, log)| alloy::rpc::types::Log {
+                inner: log.clone(),
+                block_hash: Some(*block_hash),
+                block_number: Some(block.0.header.number),
+                block_timestamp: Some(block.0.header.timestamp),
+                transaction_hash: Some(tx_hash),
+                transaction_index: Some(rx.tx_index),
+                log_index: Some(rx.logs_offset + (internal_index as u64)),
+                removed: false,
+            })
+            .collect();
+        let receipt = primitives::with_rpc_logs(&rx.receipt, logs);
+        let result = TransactionReceipt {
+            inner: AlloyTxReceipt {
+                inner: receipt,
+                transaction_hash: tx_hash,
+                transaction_index: Some(rx.tx_index),
+                block_hash: Some(*block_hash),
+                block_number: Some(block.0.header.number),
+                gas_used: rx.gas_used as u128,
                 // TODO: make all gas prices bounded by u128?
                 effective_gas_price: rx.l2_gas_price.saturating_to(),
                 // Always None because we do not support eip-4844 transactions
@@ -627,6 +1978,19 @@ impl<
         Some(result)
     }
 
+    fn query_extended_transaction_receipt(
+        &self,
+        tx_hash: B256,
+    ) -> Option<ExtendedTransactionReceipt> {
+        let inner = self.query_transaction_receipt(tx_hash)?;
+        let move_events = self
+            .receipt_move_events
+            .then(|| self.tx_receipts.get(&tx_hash))
+            .flatten()
+            .map(|(rx, _)| rx.move_events.clone());
+        Some(ExtendedTransactionReceipt { inner, move_events })
+    }
+
     pub fn on_tx_batch_noop() -> OnTxBatch<Self> {
         Box::new(|| Box::new(|_| {}))
     }
@@ -664,14 +2028,43 @@ impl<
     }
 }
 
+/// Outcome of a transaction included in the block without being executed, failed with `error`
+/// and without any state changes.
+fn unexecuted_outcome(
+    tx: &NormalizedExtendedTxEnvelope,
+    base_fee: U256,
+    error: UserError,
+) -> TransactionExecutionOutcome {
+    TransactionExecutionOutcome::new(
+        Err(error),
+        ChangeSet::new(),
+        0,
+        tx.effective_gas_price(base_fee),
+        Vec::new(),
+        Vec::new(),
+        None,
+    )
+}
+
+/// Canonical transactions are charged the L1 fee of their network encoding.
+fn l1_cost_input(tx: &TxEnvelope) -> L1GasFeeInput {
+    let mut encoded = Vec::new();
+    tx.encode(&mut encoded);
+    encoded.as_slice().into()
+}
+
 #[cfg(any(feature = "test-doubles", test))]
 pub use test_doubles::*;
 
 #[cfg(any(feature = "test-doubles", test))]
 mod test_doubles {
     use {
-        super::*, crate::primitives::U256, eth_trie::DB,
-        move_core_types::account_address::AccountAddress, std::sync::Arc,
+        super::*,
+        crate::primitives::U256,
+        eth_trie::DB,
+        move_core_types::{account_address::AccountAddress, resolver::MoveResolver},
+        move_table_extension::TableResolver,
+        std::sync::Arc,
     };
 
     pub struct MockStateQueries(pub AccountAddress, pub BlockHeight);
@@ -684,11 +2077,11 @@ mod test_doubles {
             _db: Arc<impl DB>,
             account: AccountAddress,
             height: BlockHeight,
-        ) -> Option<Balance> {
+        ) -> Result<Balance, StateUnavailable> {
             assert_eq!(account, self.0);
             assert_eq!(height, self.1);
 
-            Some(U256::from(5))
+            Ok(U256::from(5))
         }
 
         fn nonce_at(
@@ -696,11 +2089,11 @@ mod test_doubles {
             _db: Arc<impl DB>,
             account: AccountAddress,
             height: BlockHeight,
-        ) -> Option<Nonce> {
+        ) -> Result<Nonce, StateUnavailable> {
             assert_eq!(account, self.0);
             assert_eq!(height, self.1);
 
-            Some(3)
+            Ok(3)
         }
 
         fn get_proof(
@@ -712,6 +2105,50 @@ mod test_doubles {
         ) -> Option<crate::types::queries::ProofResponse> {
             None
         }
+
+        fn account_at(
+            &self,
+            db: Arc<impl DB>,
+            account: AccountAddress,
+            height: BlockHeight,
+        ) -> Result<crate::types::queries::AccountResponse, StateUnavailable> {
+            Ok(crate::types::queries::AccountResponse {
+                balance: self.balance_at(db.clone(), account, height)?,
+                nonce: U64::from(self.nonce_at(db, account, height)?),
+                code_hash: revm::primitives::KECCAK_EMPTY,
+                storage_root: B256::ZERO,
+            })
+        }
+
+        fn resource_exists_at(
+            &self,
+            _db: Arc<impl DB>,
+            _account: AccountAddress,
+            _resource: &move_core_types::language_storage::StructTag,
+            _height: BlockHeight,
+        ) -> Result<bool, StateUnavailable> {
+            Err(StateUnavailable::BlockNotFound)
+        }
+
+        fn resolver_at(
+            &self,
+            _db: Arc<impl DB>,
+            _height: BlockHeight,
+        ) -> Option<impl MoveResolver<PartialVMError> + TableResolver> {
+            None::<super::queries::HistoricResolver<eth_trie::MemoryDB>>
+        }
+
+        fn state_root_at(&self, _height: BlockHeight) -> Option<B256> {
+            None
+        }
+
+        fn is_pruned(&self, _height: BlockHeight) -> bool {
+            false
+        }
+
+        fn retention(&self) -> StateRetention {
+            StateRetention::Archive
+        }
     }
 }
 
@@ -756,6 +2193,7 @@ fn test_build_block_hash() {
         parent_beacon_block_root: Default::default(),
         transactions: Vec::new(),
         gas_limit: U64::from(0x1c9c380),
+        no_tx_pool: false,
     };
 
     let execution_outcome = ExecutionOutcome {
@@ -808,19 +2246,41 @@ mod tests {
                 MovedBlockHash,
             },
             genesis::{self, config::CHAIN_ID},
-            move_execution::{create_move_vm, create_vm_session, MovedBaseTokenAccounts},
+            move_execution::{
+                create_move_vm, create_vm_session,
+                evm_native::ResolverBackedDB,
+                tests::{module_bytes_to_tx_data, CompileJob, ModuleCompileJob},
+                CreateEcotoneL1GasFee, CreateMovedL2GasFee, MovedBaseTokenAccounts, BASE_FEE_VAULT,
+                L1_FEE_VAULT,
+            },
             storage::InMemoryState,
-            tests::{signer::Signer, EVM_ADDRESS, PRIVATE_KEY},
-            types::session_id::SessionId,
+            tests::{signer::Signer, ALT_EVM_ADDRESS, ALT_PRIVATE_KEY, EVM_ADDRESS, PRIVATE_KEY},
+            types::{
+                session_id::SessionId,
+                state::Withdrawal,
+                transactions::{DepositedTx, ScriptOrModule, TransactionData},
+            },
+            UserError,
         },
         alloy::{
             consensus::{SignableTransaction, TxEip1559, TxEnvelope},
             hex,
             network::TxSignerSync,
+            primitives::address,
+            rpc::types::{TransactionInput, TransactionRequest},
+        },
+        aptos_types::transaction::{EntryFunction, Script},
+        move_binary_format::file_format::{empty_script, Bytecode},
+        move_core_types::{
+            account_address::AccountAddress,
+            effects::ChangeSet,
+            identifier::Identifier,
+            language_storage::ModuleId,
+            value::{MoveTypeLayout, MoveValue},
         },
-        move_core_types::{account_address::AccountAddress, effects::ChangeSet},
         move_vm_runtime::module_traversal::{TraversalContext, TraversalStorage},
         move_vm_types::gas::UnmeteredGasMeter,
+        revm::DatabaseRef,
         test_case::test_case,
         tokio::sync::{
             mpsc::{self, Sender},
@@ -847,7 +2307,7 @@ mod tests {
         >,
         Sender<StateMessage>,
     ) {
-        let genesis_config = GenesisConfig::default();
+        let genesis_config = GenesisConfig::dev();
         let (state_channel, rx) = mpsc::channel(10);
 
         let head_hash = B256::new(hex!(
@@ -907,6 +2367,44 @@ mod tests {
         session.finish().unwrap()
     }
 
+    fn unsigned_eip1559(nonce: u64, to: TxKind, value: U256, input: Vec<u8>) -> TxEip1559 {
+        TxEip1559 {
+            chain_id: CHAIN_ID,
+            nonce,
+            gas_limit: u64::MAX,
+            max_fee_per_gas: 0,
+            max_priority_fee_per_gas: 0,
+            to,
+            value,
+            access_list: Default::default(),
+            input: input.into(),
+        }
+    }
+
+    fn sign_eip1559(signer: &Signer, mut tx: TxEip1559) -> TxEnvelope {
+        let signature = signer.inner.sign_transaction_sync(&mut tx).unwrap();
+        TxEnvelope::Eip1559(tx.into_signed(signature))
+    }
+
+    /// Signs a free EIP-1559 transaction without a gas limit.
+    fn signed_eip1559(
+        signer: &Signer,
+        nonce: u64,
+        to: TxKind,
+        value: U256,
+        input: Vec<u8>,
+    ) -> TxEnvelope {
+        sign_eip1559(signer, unsigned_eip1559(nonce, to, value, input))
+    }
+
+    /// Data of a transaction deploying the `counter` module at [`EVM_ADDRESS`].
+    fn counter_deploy_data() -> Vec<u8> {
+        let module_bytes = ModuleCompileJob::new("counter", &EVM_ADDRESS.to_move_address())
+            .compile()
+            .unwrap();
+        module_bytes_to_tx_data(module_bytes)
+    }
+
     fn create_state_actor_with_fake_queries(
         addr: AccountAddress,
         initial_balance: U256,
@@ -926,7 +2424,32 @@ mod tests {
         >,
         Sender<StateMessage>,
     ) {
-        let genesis_config = GenesisConfig::default();
+        create_state_actor_with_l1_fee(addr, initial_balance, U256::ZERO)
+    }
+
+    /// Same as [`create_state_actor_with_fake_queries`], but transactions also pay the L1 fee of
+    /// `l1_fee` if the first transaction in the block is a deposit.
+    fn create_state_actor_with_l1_fee(
+        addr: AccountAddress,
+        initial_balance: U256,
+        l1_fee: impl CreateL1GasFee,
+    ) -> (
+        StateActor<
+            impl State<Err = PartialVMError>,
+            impl NewPayloadId,
+            impl BlockHash,
+            impl BlockRepository<Storage = BlockMemory>,
+            impl BaseGasFee,
+            impl CreateL1GasFee,
+            impl CreateL2GasFee,
+            impl BaseTokenAccounts,
+            impl BlockQueries<Storage = BlockMemory>,
+            BlockMemory,
+            impl StateQueries,
+        >,
+        Sender<StateMessage>,
+    ) {
+        let genesis_config = GenesisConfig::dev();
         let (state_channel, rx) = mpsc::channel(10);
 
         let head_hash = B256::new(hex!(
@@ -940,7 +2463,7 @@ mod tests {
         repository.add(&mut block_memory, genesis_block);
 
         let mut state = InMemoryState::new();
-        let (genesis_changes, table_changes) = genesis::init(&genesis_config, &state);
+        let (genesis_changes, table_changes) = genesis::init(&genesis_config, &state).unwrap();
         state
             .apply_with_tables(genesis_changes.clone(), table_changes)
             .unwrap();
@@ -959,8 +2482,8 @@ mod tests {
             MovedBlockHash,
             repository,
             Eip1559GasFee::default(),
-            U256::ZERO,
-            U256::ZERO,
+            l1_fee,
+            CreateMovedL2GasFee,
             MovedBaseTokenAccounts::new(AccountAddress::ONE),
             InMemoryBlockQueries,
             block_memory,
@@ -971,64 +2494,1524 @@ mod tests {
         (state, state_channel)
     }
 
-    #[test_case(Latest, 4, 4; "Latest")]
-    #[test_case(Finalized, 4, 4; "Finalized")]
-    #[test_case(Safe, 4, 4; "Safe")]
-    #[test_case(Earliest, 4, 0; "Earliest")]
-    #[test_case(Pending, 4, 4; "Pending")]
-    #[test_case(Number(2), 4, 2; "Number")]
-    fn test_nonce_is_fetched_by_height_successfully(
-        height: BlockNumberOrTag,
-        head_height: BlockHeight,
-        expected_height: BlockHeight,
-    ) {
-        let address = primitives::Address::new(hex!("11223344556677889900ffeeaabbccddee111111"));
-        let (state_actor, _) = create_state_actor_with_given_queries(
-            head_height,
-            MockStateQueries(address.to_move_address(), expected_height),
-        );
-        let (tx, rx) = oneshot::channel();
+    #[test_case(Latest, 4, 4; "Latest")]
+    #[test_case(Finalized, 4, 4; "Finalized")]
+    #[test_case(Safe, 4, 4; "Safe")]
+    #[test_case(Earliest, 4, 0; "Earliest")]
+    #[test_case(Pending, 4, 4; "Pending")]
+    #[test_case(Number(2), 4, 2; "Number")]
+    fn test_nonce_is_fetched_by_height_successfully(
+        height: BlockNumberOrTag,
+        head_height: BlockHeight,
+        expected_height: BlockHeight,
+    ) {
+        let address = primitives::Address::new(hex!("11223344556677889900ffeeaabbccddee111111"));
+        let (state_actor, _) = create_state_actor_with_given_queries(
+            head_height,
+            MockStateQueries(address.to_move_address(), expected_height),
+        );
+        let (tx, rx) = oneshot::channel();
+
+        state_actor.handle_query(Query::NonceByHeight {
+            height,
+            address,
+            response_channel: tx,
+        });
+
+        let actual_nonce = rx.blocking_recv().unwrap().expect("Block should be found");
+        let expected_nonce = 3;
+
+        assert_eq!(actual_nonce, expected_nonce);
+    }
+
+    #[test_case(Latest, 2, 2; "Latest")]
+    #[test_case(Finalized, 2, 2; "Finalized")]
+    #[test_case(Safe, 2, 2; "Safe")]
+    #[test_case(Earliest, 2, 0; "Earliest")]
+    #[test_case(Pending, 2, 2; "Pending")]
+    #[test_case(Number(1), 2, 1; "Number")]
+    fn test_balance_is_fetched_by_height_successfully(
+        height: BlockNumberOrTag,
+        head_height: BlockHeight,
+        expected_height: BlockHeight,
+    ) {
+        let address = primitives::Address::new(hex!("44223344556677889900ffeeaabbccddee111111"));
+        let (state_actor, _) = create_state_actor_with_given_queries(
+            head_height,
+            MockStateQueries(address.to_move_address(), expected_height),
+        );
+        let (tx, rx) = oneshot::channel();
+
+        state_actor.handle_query(Query::BalanceByHeight {
+            height,
+            address,
+            response_channel: tx,
+        });
+
+        let actual_balance = rx.blocking_recv().unwrap().expect("Block should be found");
+        let expected_balance = U256::from(5);
+
+        assert_eq!(actual_balance, expected_balance);
+    }
+
+    #[test]
+    fn test_pruned_state_is_reported_as_unavailable() {
+        let address = primitives::Address::new(hex!("44223344556677889900ffeeaabbccddee111111"));
+        let genesis_config = GenesisConfig::dev();
+        let (mut state_actor, _) = create_state_actor_with_given_queries(
+            0,
+            InMemoryStateQueries::with_retention(
+                genesis_config.initial_state_root,
+                StateRetention::Pruned { blocks: 1 },
+            ),
+        );
+        for _ in 0..2 {
+            let root = state_actor.state.state_root();
+            state_actor.state_queries.push_state_root(root);
+        }
+
+        let (tx, rx) = oneshot::channel();
+        state_actor.handle_query(Query::BalanceByHeight {
+            height: Number(0),
+            address,
+            response_channel: tx,
+        });
+        assert_eq!(
+            rx.blocking_recv().unwrap(),
+            Err(StateUnavailable::Pruned(0))
+        );
+
+        let (tx, rx) = oneshot::channel();
+        state_actor.handle_query(Query::NonceByHeight {
+            height: Number(1),
+            address,
+            response_channel: tx,
+        });
+        assert_eq!(
+            rx.blocking_recv().unwrap(),
+            Err(StateUnavailable::Pruned(1))
+        );
+
+        let (tx, rx) = oneshot::channel();
+        state_actor.handle_query(Query::Call {
+            transaction: TransactionRequest {
+                from: Some(address),
+                to: Some(TxKind::Call(address)),
+                ..Default::default()
+            },
+            block_number: Number(0),
+            response_channel: tx,
+        });
+        let err = rx
+            .blocking_recv()
+            .unwrap()
+            .expect_err("Pruned state should not be callable");
+        assert!(matches!(
+            err,
+            User(UserError::StateUnavailable(StateUnavailable::Pruned(0)))
+        ));
+
+        let (tx, rx) = oneshot::channel();
+        state_actor.handle_query(Query::BalanceByHeight {
+            height: Number(2),
+            address,
+            response_channel: tx,
+        });
+        assert_eq!(rx.blocking_recv().unwrap(), Ok(U256::ZERO));
+
+        let (tx, rx) = oneshot::channel();
+        state_actor.handle_query(Query::BalanceByHeight {
+            height: Number(3),
+            address,
+            response_channel: tx,
+        });
+        assert_eq!(
+            rx.blocking_recv().unwrap(),
+            Err(StateUnavailable::BlockNotFound)
+        );
+    }
+
+    #[test]
+    fn test_receipts_are_served_after_state_is_pruned() {
+        let genesis_config = GenesisConfig::dev();
+        let (mut state_actor, _) = create_state_actor_with_given_queries(
+            0,
+            InMemoryStateQueries::with_retention(
+                genesis_config.initial_state_root,
+                StateRetention::Pruned { blocks: 1 },
+            ),
+        );
+        let mut signer = Signer::new(&PRIVATE_KEY);
+        let tx = sign_eip1559(
+            &signer,
+            TxEip1559 {
+                gas_limit: 1_000_000,
+                ..unsigned_eip1559(
+                    signer.nonce,
+                    TxKind::Call(ALT_EVM_ADDRESS),
+                    U256::ZERO,
+                    Vec::new(),
+                )
+            },
+        );
+        signer.nonce += 1;
+        let tx_hash: B256 = tx.tx_hash().0.into();
+
+        // The transaction is included in block 1, followed by blocks pruning its state
+        state_actor.handle_command(Command::AddTransaction { tx });
+        for _ in 0..3 {
+            state_actor.handle_command(Command::StartBlockBuild {
+                payload_attributes: Default::default(),
+                response_channel: oneshot::channel().0,
+            });
+            let root = state_actor.state.state_root();
+            state_actor.state_queries.push_state_root(root);
+        }
+
+        let (tx, rx) = oneshot::channel();
+        state_actor.handle_query(Query::BalanceByHeight {
+            height: Number(1),
+            address: EVM_ADDRESS,
+            response_channel: tx,
+        });
+        assert_eq!(
+            rx.blocking_recv().unwrap(),
+            Err(StateUnavailable::Pruned(1))
+        );
+
+        let (tx, rx) = oneshot::channel();
+        state_actor.handle_query(Query::TransactionReceipt {
+            tx_hash,
+            response_channel: tx,
+        });
+        let receipt = rx
+            .blocking_recv()
+            .unwrap()
+            .expect("Receipt should outlive the pruned state");
+        assert_eq!(receipt.inner.block_number, Some(1));
+    }
+
+    #[test]
+    fn test_fee_recipient_overrides_suggested_fee_recipient() {
+        let fee_recipient = address!("1111111111111111111111111111111111111111");
+        let (state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::ZERO);
+        let mut state_actor = state_actor.with_fee_recipient(fee_recipient);
+
+        let (tx, rx) = oneshot::channel();
+        state_actor.handle_command(Command::StartBlockBuild {
+            payload_attributes: Payload {
+                suggested_fee_recipient: address!("4200000000000000000000000000000000000011"),
+                ..Default::default()
+            },
+            response_channel: tx,
+        });
+        let id = rx.blocking_recv().unwrap().unwrap();
+
+        let (tx, rx) = oneshot::channel();
+        state_actor.handle_command(Command::GetPayload {
+            id,
+            response_channel: tx,
+        });
+        let payload = rx
+            .blocking_recv()
+            .unwrap()
+            .expect("Payload should be built");
+
+        assert_eq!(payload.execution_payload.fee_recipient, fee_recipient);
+    }
+
+    #[test]
+    fn test_fee_recipient_is_credited_with_priority_fees() {
+        let fee_recipient = address!("1111111111111111111111111111111111111111");
+        let (mut state_actor, _) = create_state_actor_with_fake_queries(
+            EVM_ADDRESS.to_move_address(),
+            U256::from(1_000_000_000_000u64),
+        );
+
+        let signer = Signer::new(&PRIVATE_KEY);
+        let tx = sign_eip1559(
+            &signer,
+            TxEip1559 {
+                gas_limit: 1_000_000,
+                max_fee_per_gas: 3,
+                max_priority_fee_per_gas: 2,
+                ..unsigned_eip1559(
+                    signer.nonce,
+                    TxKind::Call(address!("44223344556677889900ffeeaabbccddee111111")),
+                    U256::from(4),
+                    Vec::new(),
+                )
+            },
+        );
+
+        state_actor.handle_command(Command::AddTransaction { tx });
+        let (tx, rx) = oneshot::channel();
+        state_actor.handle_command(Command::StartBlockBuild {
+            payload_attributes: Payload {
+                suggested_fee_recipient: fee_recipient,
+                ..Default::default()
+            },
+            response_channel: tx,
+        });
+        let id = rx.blocking_recv().unwrap().unwrap();
+
+        let (tx, rx) = oneshot::channel();
+        state_actor.handle_command(Command::GetPayload {
+            id,
+            response_channel: tx,
+        });
+        let payload = rx
+            .blocking_recv()
+            .unwrap()
+            .expect("Payload should be built");
+
+        let (tx, rx) = oneshot::channel();
+        state_actor.handle_query(Query::BalanceByHeight {
+            height: Latest,
+            address: fee_recipient,
+            response_channel: tx,
+        });
+        let fee_recipient_balance = rx.blocking_recv().unwrap().expect("Block should be found");
+
+        // The base fee of the first block is zero, so the whole gas cost is the tip
+        let gas_used = payload.execution_payload.gas_used;
+        let expected_tip = U256::from(2) * U256::from(gas_used.as_limbs()[0]);
+        assert!(!expected_tip.is_zero());
+        assert_eq!(payload.block_value, expected_tip);
+        assert_eq!(fee_recipient_balance, expected_tip);
+    }
+
+    /// Parent block that makes the base fee of the next block equal to 1.
+    fn base_fee_one_parent() -> ExtendedBlock {
+        Block::new(
+            Header {
+                base_fee_per_gas: Some(1),
+                ..Default::default()
+            },
+            Vec::new(),
+        )
+        .with_hash(B256::repeat_byte(1))
+        .with_value(U256::ZERO)
+    }
+
+    /// Encoded deposit opening a block, carrying the L1 attributes `data`.
+    fn l1_info_deposit(data: Vec<u8>, gas: u64) -> Vec<u8> {
+        let deposit = ExtendedTxEnvelope::DepositedTx(DepositedTx {
+            source_hash: B256::ZERO,
+            from: EVM_ADDRESS,
+            to: EVM_ADDRESS,
+            mint: U256::ZERO,
+            value: U256::ZERO,
+            gas: U64::from(gas),
+            is_system_tx: false,
+            data: data.into(),
+        });
+        let mut encoded_deposit = Vec::new();
+        deposit.encode(&mut encoded_deposit);
+        encoded_deposit
+    }
+
+    #[test]
+    fn test_base_fee_and_l1_fee_are_paid_to_vaults() {
+        let (mut state_actor, _) = create_state_actor_with_l1_fee(
+            EVM_ADDRESS.to_move_address(),
+            U256::from(1_000_000_000_000u64),
+            U256::from(100),
+        );
+
+        state_actor.handle_command(Command::GenesisUpdate {
+            block: base_fee_one_parent(),
+        });
+
+        // The L1 fee is only charged in blocks starting with a deposit
+        let encoded_deposit = l1_info_deposit(Vec::new(), u64::MAX);
+
+        let signer = Signer::new(&PRIVATE_KEY);
+        let tx = sign_eip1559(
+            &signer,
+            TxEip1559 {
+                gas_limit: 1_000_000,
+                max_fee_per_gas: 3,
+                max_priority_fee_per_gas: 2,
+                ..unsigned_eip1559(
+                    signer.nonce,
+                    TxKind::Call(address!("44223344556677889900ffeeaabbccddee111111")),
+                    U256::from(4),
+                    Vec::new(),
+                )
+            },
+        );
+        let tx_hash: B256 = tx.tx_hash().0.into();
+
+        state_actor.handle_command(Command::AddTransaction { tx });
+        state_actor.handle_command(Command::StartBlockBuild {
+            payload_attributes: Payload {
+                transactions: vec![encoded_deposit.into()],
+                ..Default::default()
+            },
+            response_channel: oneshot::channel().0,
+        });
+
+        let gas_used = state_actor.tx_receipts.get(&tx_hash).unwrap().0.gas_used;
+        let balance_of = |address| {
+            let (tx, rx) = oneshot::channel();
+            state_actor.handle_query(Query::BalanceByHeight {
+                height: Latest,
+                address,
+                response_channel: tx,
+            });
+            rx.blocking_recv().unwrap().expect("Block should be found")
+        };
+
+        assert_eq!(balance_of(BASE_FEE_VAULT), U256::from(gas_used));
+        assert_eq!(balance_of(L1_FEE_VAULT), U256::from(100));
+        assert_eq!(balance_of(Address::ZERO), U256::from(2 * gas_used));
+    }
+
+    #[test]
+    fn test_l1_fee_params_match_the_l1_fee_of_transactions() {
+        let (mut state_actor, _) = create_state_actor_with_l1_fee(
+            EVM_ADDRESS.to_move_address(),
+            U256::from(1_000_000_000_000u64),
+            CreateEcotoneL1GasFee,
+        );
+
+        state_actor.handle_command(Command::GenesisUpdate {
+            block: base_fee_one_parent(),
+        });
+
+        // Without an L1 attributes deposit there are no parameters
+        let (tx, rx) = oneshot::channel();
+        state_actor.handle_query(Query::L1FeeParams {
+            response_channel: tx,
+        });
+        assert_eq!(rx.blocking_recv().unwrap(), None);
+
+        // Calldata of `setL1BlockValuesEcotone`, only the fee parameters are set
+        let mut l1_attributes = vec![0u8; 164];
+        l1_attributes[0..4].copy_from_slice(&hex!("440a5e20"));
+        l1_attributes[4..8].copy_from_slice(&1368u32.to_be_bytes());
+        l1_attributes[8..12].copy_from_slice(&810949u32.to_be_bytes());
+        l1_attributes[60..68].copy_from_slice(&7u64.to_be_bytes());
+        l1_attributes[92..100].copy_from_slice(&3u64.to_be_bytes());
+        let encoded_deposit = l1_info_deposit(l1_attributes, 1_000_000);
+
+        let signer = Signer::new(&PRIVATE_KEY);
+        let tx = sign_eip1559(
+            &signer,
+            TxEip1559 {
+                gas_limit: 1_000_000,
+                max_fee_per_gas: 3,
+                max_priority_fee_per_gas: 2,
+                ..unsigned_eip1559(
+                    signer.nonce,
+                    TxKind::Call(address!("44223344556677889900ffeeaabbccddee111111")),
+                    U256::from(4),
+                    Vec::new(),
+                )
+            },
+        );
+        let tx_hash: B256 = tx.tx_hash().0.into();
+
+        state_actor.handle_command(Command::AddTransaction { tx });
+        state_actor.handle_command(Command::StartBlockBuild {
+            payload_attributes: Payload {
+                transactions: vec![encoded_deposit.into()],
+                ..Default::default()
+            },
+            response_channel: oneshot::channel().0,
+        });
+
+        let l1_block_info = state_actor
+            .tx_receipts
+            .get(&tx_hash)
+            .unwrap()
+            .0
+            .l1_block_info
+            .expect("Transaction should be charged an L1 fee");
+        assert!(l1_block_info.l1_fee.unwrap() > 0);
+        let expected = L1FeeParams {
+            l1_base_fee: U256::from(7),
+            l1_base_fee_scalar: U256::from(1368),
+            l1_blob_base_fee: U256::from(3),
+            l1_blob_base_fee_scalar: U256::from(810949),
+        };
+        assert_eq!(
+            L1FeeParams::from_l1_block_info(&l1_block_info),
+            Some(expected)
+        );
+
+        let (tx, rx) = oneshot::channel();
+        state_actor.handle_query(Query::L1FeeParams {
+            response_channel: tx,
+        });
+        assert_eq!(rx.blocking_recv().unwrap(), Some(expected));
+    }
+
+    #[test]
+    fn test_receipts_have_effective_gas_price() {
+        let (mut state_actor, _) = create_state_actor_with_fake_queries(
+            EVM_ADDRESS.to_move_address(),
+            U256::from(1_000_000_000_000u64),
+        );
+
+        state_actor.handle_command(Command::GenesisUpdate {
+            block: base_fee_one_parent(),
+        });
+
+        let encoded_deposit = l1_info_deposit(Vec::new(), u64::MAX);
+        let deposit_hash = B256::new(keccak256(&encoded_deposit).0);
+
+        // The priority fee is capped by the max fee, so only 1 is left for the tip
+        let signer = Signer::new(&PRIVATE_KEY);
+        let tx = sign_eip1559(
+            &signer,
+            TxEip1559 {
+                gas_limit: 1_000_000,
+                max_fee_per_gas: 2,
+                max_priority_fee_per_gas: 5,
+                ..unsigned_eip1559(
+                    signer.nonce,
+                    TxKind::Call(address!("44223344556677889900ffeeaabbccddee111111")),
+                    U256::from(4),
+                    Vec::new(),
+                )
+            },
+        );
+        let tx_hash: B256 = tx.tx_hash().0.into();
+
+        state_actor.handle_command(Command::AddTransaction { tx });
+        state_actor.handle_command(Command::StartBlockBuild {
+            payload_attributes: Payload {
+                transactions: vec![encoded_deposit.into()],
+                ..Default::default()
+            },
+            response_channel: oneshot::channel().0,
+        });
+
+        let effective_gas_price = |tx_hash| {
+            state_actor
+                .query_transaction_receipt(tx_hash)
+                .expect("Receipt should exist")
+                .inner
+                .effective_gas_price
+        };
+
+        assert_eq!(effective_gas_price(tx_hash), 2);
+        assert_eq!(effective_gas_price(deposit_hash), 0);
+    }
+
+    #[test]
+    fn test_receipts_include_move_events_when_enabled() {
+        let (state_actor, _) = create_state_actor_with_fake_queries(
+            EVM_ADDRESS.to_move_address(),
+            U256::from(1_000_000_000_000u64),
+        );
+        let mut state_actor = state_actor.with_receipt_move_events();
+
+        let signer = Signer::new(&PRIVATE_KEY);
+        let tx = sign_eip1559(
+            &signer,
+            TxEip1559 {
+                gas_limit: 1_000_000,
+                ..unsigned_eip1559(
+                    signer.nonce,
+                    TxKind::Call(address!("44223344556677889900ffeeaabbccddee111111")),
+                    U256::from(4),
+                    Vec::new(),
+                )
+            },
+        );
+        let tx_hash: B256 = tx.tx_hash().0.into();
+
+        state_actor.handle_command(Command::AddTransaction { tx });
+        state_actor.handle_command(Command::StartBlockBuild {
+            payload_attributes: Default::default(),
+            response_channel: oneshot::channel().0,
+        });
+
+        let (tx, rx) = oneshot::channel();
+        state_actor.handle_query(Query::TransactionReceipt {
+            tx_hash,
+            response_channel: tx,
+        });
+        let receipt = rx.blocking_recv().unwrap().expect("Receipt should exist");
+        let move_events = receipt.move_events.expect("Move events should be included");
+        let logs = receipt.inner.inner.inner.logs();
+
+        // The transfer withdraws from the sender and deposits to the recipient
+        assert!(!move_events.is_empty());
+        assert_eq!(move_events.len(), logs.len());
+        for (event, log) in move_events.iter().zip(logs) {
+            assert_eq!(log.topics()[0], keccak256(event.type_tag.as_bytes()));
+            assert_eq!(log.data().data, event.data);
+        }
+
+        let json = serde_json::to_value(
+            state_actor
+                .query_extended_transaction_receipt(tx_hash)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(json["moveEvents"][0]["typeTag"], move_events[0].type_tag);
+        assert!(json["logs"].is_array());
+    }
+
+    fn fee_history(
+        state_actor: &StateActor<
+            impl State<Err = PartialVMError>,
+            impl NewPayloadId,
+            impl BlockHash,
+            impl BlockRepository<Storage = BlockMemory>,
+            impl BaseGasFee,
+            impl CreateL1GasFee,
+            impl CreateL2GasFee + Sync,
+            impl BaseTokenAccounts + Sync,
+            impl BlockQueries<Storage = BlockMemory>,
+            BlockMemory,
+            impl StateQueries,
+        >,
+        block_count: u64,
+        block_number: BlockNumberOrTag,
+    ) -> FeeHistory {
+        let (tx, rx) = oneshot::channel();
+        state_actor.handle_query(Query::FeeHistory {
+            block_count,
+            block_number,
+            reward_percentiles: Some(vec![50.0]),
+            response_channel: tx,
+        });
+        rx.blocking_recv().unwrap()
+    }
+
+    #[test_case(100, Latest, 0, 3; "count way past genesis")]
+    #[test_case(3, Latest, 0, 3; "count exactly at genesis")]
+    #[test_case(2, Latest, 1, 2; "count within range")]
+    #[test_case(100, Number(1), 0, 2; "count past genesis from older block")]
+    #[test_case(2, Number(100), 1, 2; "block after head")]
+    #[test_case(1, Latest, 2, 1; "single block at latest")]
+    #[test_case(1, Number(1), 1, 1; "single block at number")]
+    #[test_case(1, Earliest, 0, 1; "single block at earliest")]
+    #[test_case(2, Earliest, 0, 1; "count past genesis from earliest")]
+    fn test_fee_history_block_ranges(
+        block_count: u64,
+        block_number: BlockNumberOrTag,
+        expected_oldest: u64,
+        expected_len: usize,
+    ) {
+        let (mut state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::ZERO);
+        for _ in 0..2 {
+            state_actor.handle_command(Command::StartBlockBuild {
+                payload_attributes: Default::default(),
+                response_channel: oneshot::channel().0,
+            });
+        }
+
+        let history = fee_history(&state_actor, block_count, block_number);
+
+        assert_eq!(history.oldest_block, expected_oldest);
+        assert_eq!(history.gas_used_ratio.len(), expected_len);
+        assert_eq!(history.base_fee_per_gas.len(), expected_len + 1);
+        assert_eq!(history.reward.unwrap(), vec![vec![0]; expected_len]);
+    }
+
+    #[test]
+    fn test_fee_history_of_zero_blocks_is_empty() {
+        let (state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::ZERO);
+
+        let history = fee_history(&state_actor, 0, Latest);
+
+        assert_eq!(history, FeeHistory::default());
+    }
+
+    #[test_case(None, None, 1_000_000_000_000; "unbounded")]
+    #[test_case(None, Some(100), 100; "ceiling")]
+    #[test_case(Some(2_000_000_000_000), None, 2_000_000_000_000; "floor")]
+    fn test_gas_price_is_clamped_to_bounds(
+        floor: Option<u64>,
+        ceiling: Option<u64>,
+        expected: u64,
+    ) {
+        let gas_price_bounds = GasPriceBounds {
+            floor: floor.map(U256::from),
+            ceiling: ceiling.map(U256::from),
+        };
+        let (state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::ZERO);
+        let mut state_actor = state_actor.with_gas_price_bounds(gas_price_bounds);
+
+        // Spike the base fee of the next block
+        let head = Block::new(
+            Header {
+                base_fee_per_gas: Some(1_000_000_000_000),
+                ..Default::default()
+            },
+            Vec::new(),
+        )
+        .with_hash(B256::repeat_byte(1))
+        .with_value(U256::ZERO);
+        state_actor.handle_command(Command::GenesisUpdate { block: head });
+
+        let (tx, rx) = oneshot::channel();
+        state_actor.handle_query(Query::GasPrice {
+            response_channel: tx,
+        });
+
+        assert_eq!(rx.blocking_recv().unwrap(), U256::from(expected));
+    }
+
+    #[test]
+    fn test_transactions_are_listed_by_sender() {
+        let (mut state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::from(10));
+
+        let mut signer = Signer::new(&PRIVATE_KEY);
+        let mut tx_hashes = Vec::new();
+        for _ in 0..3 {
+            let tx = signed_eip1559(
+                &signer,
+                signer.nonce,
+                TxKind::Call(address!("44223344556677889900ffeeaabbccddee111111")),
+                U256::from(1),
+                Vec::new(),
+            );
+            signer.nonce += 1;
+            tx_hashes.push(B256::from(tx.tx_hash().0));
+
+            state_actor.handle_command(Command::AddTransaction { tx });
+            state_actor.handle_command(Command::StartBlockBuild {
+                payload_attributes: Default::default(),
+                response_channel: oneshot::channel().0,
+            });
+        }
+
+        let transactions_by_sender = |address, offset, limit| {
+            let (tx, rx) = oneshot::channel();
+            state_actor.handle_query(Query::TransactionsBySender {
+                address,
+                offset,
+                limit,
+                response_channel: tx,
+            });
+            rx.blocking_recv().unwrap()
+        };
+
+        assert_eq!(transactions_by_sender(EVM_ADDRESS, 0, 10), tx_hashes);
+        assert_eq!(transactions_by_sender(EVM_ADDRESS, 1, 1), tx_hashes[1..2]);
+        assert!(transactions_by_sender(EVM_ADDRESS, 3, 10).is_empty());
+        assert!(transactions_by_sender(Address::ZERO, 0, 10).is_empty());
+    }
+
+    #[test]
+    fn test_transactions_of_orphaned_blocks_are_not_listed_by_sender() {
+        let (mut state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::from(10));
+
+        let mut signer = Signer::new(&PRIVATE_KEY);
+        let mut tx_hashes = Vec::new();
+        let mut block_hashes = Vec::new();
+        for i in 0..3 {
+            if i == 2 {
+                // Reorg back to the first block, orphaning the second one
+                state_actor.handle_command(Command::UpdateHead {
+                    block_hash: block_hashes[0],
+                });
+                assert_eq!(state_actor.reorg_count, 1);
+            }
+            let tx = signed_eip1559(
+                &signer,
+                signer.nonce,
+                TxKind::Call(address!("44223344556677889900ffeeaabbccddee111111")),
+                U256::from(1),
+                Vec::new(),
+            );
+            signer.nonce += 1;
+            tx_hashes.push(B256::from(tx.tx_hash().0));
+
+            state_actor.handle_command(Command::AddTransaction { tx });
+            state_actor.handle_command(Command::StartBlockBuild {
+                payload_attributes: Default::default(),
+                response_channel: oneshot::channel().0,
+            });
+            let (_, payload) = state_actor.pending_payload.clone().unwrap();
+            let block_hash = payload.execution_payload.block_hash;
+            state_actor.handle_command(Command::UpdateHead { block_hash });
+            block_hashes.push(block_hash);
+        }
+
+        let (tx, rx) = oneshot::channel();
+        state_actor.handle_query(Query::TransactionsBySender {
+            address: EVM_ADDRESS,
+            offset: 0,
+            limit: 10,
+            response_channel: tx,
+        });
+
+        assert_eq!(
+            rx.blocking_recv().unwrap(),
+            vec![tx_hashes[0], tx_hashes[2]]
+        );
+    }
+
+    #[test_case(false; "sequential")]
+    #[test_case(true; "parallel")]
+    fn test_build_deadline_seals_partial_block(parallel_execution: bool) {
+        let (state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::from(10));
+        let state_actor = state_actor.with_build_deadline(Duration::ZERO);
+        let mut state_actor = if parallel_execution {
+            state_actor.with_parallel_execution()
+        } else {
+            state_actor
+        };
+
+        let mut signer = Signer::new(&PRIVATE_KEY);
+        let mut transactions = Vec::new();
+        for _ in 0..10 {
+            transactions.push(signed_eip1559(
+                &signer,
+                signer.nonce,
+                TxKind::Call(address!("44223344556677889900ffeeaabbccddee111111")),
+                U256::from(1),
+                Vec::new(),
+            ));
+            signer.nonce += 1;
+        }
+        let forced_tx = transactions.remove(0);
+        let forced_tx_hash = B256::from(forced_tx.tx_hash().0);
+        let mut encoded_forced_tx = Vec::new();
+        ExtendedTxEnvelope::Canonical(forced_tx).encode(&mut encoded_forced_tx);
+        for tx in transactions {
+            state_actor.handle_command(Command::AddTransaction { tx });
+        }
+
+        state_actor.handle_command(Command::StartBlockBuild {
+            payload_attributes: Payload {
+                transactions: vec![encoded_forced_tx.into()],
+                ..Default::default()
+            },
+            response_channel: oneshot::channel().0,
+        });
+
+        assert_eq!(state_actor.tx_receipts.len(), 1);
+        assert!(state_actor.tx_receipts.contains_key(&forced_tx_hash));
+        assert_eq!(state_actor.mem_pool.len(), 9);
+    }
+
+    #[test]
+    fn test_withdrawals_root_is_computed_from_payload_withdrawals() {
+        let (mut state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::ZERO);
+        let withdrawals: Vec<_> = (0..3)
+            .map(|index| Withdrawal {
+                index,
+                validator_index: 10 + index,
+                address: EVM_ADDRESS,
+                amount: 1_000 * (index + 1),
+            })
+            .collect();
+
+        state_actor.handle_command(Command::StartBlockBuild {
+            payload_attributes: Payload {
+                withdrawals: withdrawals.clone(),
+                ..Default::default()
+            },
+            response_channel: oneshot::channel().0,
+        });
+
+        let (_, payload) = state_actor.pending_payload.clone().unwrap();
+        let block_hash = payload.execution_payload.block_hash;
+        let block = state_actor
+            .block_repository
+            .by_hash(&state_actor.block_memory, block_hash)
+            .unwrap();
+        let expected_root = alloy::consensus::proofs::calculate_withdrawals_root(&withdrawals);
+        assert_ne!(expected_root, alloy_trie::EMPTY_ROOT_HASH);
+        assert_eq!(block.block.header.withdrawals_root, Some(expected_root));
+        assert_eq!(block.block.withdrawals, withdrawals);
+        assert_eq!(payload.execution_payload.withdrawals, withdrawals);
+    }
+
+    #[test]
+    fn test_block_summary_describes_built_block() {
+        let (state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::from(10));
+        let mut state_actor = state_actor.with_max_block_transactions(1);
+
+        let mut signer = Signer::new(&PRIVATE_KEY);
+        for _ in 0..3 {
+            let tx = signed_eip1559(
+                &signer,
+                signer.nonce,
+                TxKind::Call(address!("44223344556677889900ffeeaabbccddee111111")),
+                U256::from(1),
+                Vec::new(),
+            );
+            signer.nonce += 1;
+            state_actor.handle_command(Command::AddTransaction { tx });
+        }
+
+        state_actor.handle_command(Command::StartBlockBuild {
+            payload_attributes: Default::default(),
+            response_channel: oneshot::channel().0,
+        });
+
+        let block_hash = state_actor
+            .block_queries
+            .by_height(&state_actor.block_memory, 1, false)
+            .expect("Built block should exist")
+            .0
+            .header
+            .hash;
+        let block = state_actor
+            .block_repository
+            .by_hash(&state_actor.block_memory, block_hash)
+            .unwrap();
+        let summary = state_actor.block_summary(&block, Duration::from_millis(12));
+
+        assert_eq!(summary.height, 1);
+        assert_eq!(summary.transactions, 1);
+        assert_eq!(summary.gas_used, block.block.header.gas_used);
+        assert!(summary.gas_used > 0);
+        assert_eq!(
+            summary.base_fee,
+            block.block.header.base_fee_per_gas.unwrap()
+        );
+        assert_eq!(summary.mem_pool_size, 2);
+        assert_eq!(
+            summary.to_string(),
+            format!(
+                "height=1 hash={} transactions=1 gas_used={} base_fee={} build_duration_ms=12 \
+                mem_pool_size=2",
+                block.hash, summary.gas_used, summary.base_fee,
+            )
+        );
+    }
+
+    #[test]
+    fn test_reorgs_deeper_than_limit_are_refused() {
+        let (state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::ZERO);
+        let mut state_actor = state_actor.with_max_reorg_depth(1);
+        for _ in 0..3 {
+            state_actor.handle_command(Command::StartBlockBuild {
+                payload_attributes: Default::default(),
+                response_channel: oneshot::channel().0,
+            });
+        }
+        let block_hash = |height| {
+            state_actor
+                .block_queries
+                .by_height(&state_actor.block_memory, height, false)
+                .expect("Block should exist")
+                .0
+                .header
+                .hash
+        };
+        let (too_deep, within_limit) = (block_hash(1), block_hash(2));
+        let head = state_actor.head;
+
+        state_actor.handle_command(Command::UpdateHead {
+            block_hash: too_deep,
+        });
+        assert_eq!(state_actor.head, head);
+
+        state_actor.handle_command(Command::UpdateHead {
+            block_hash: within_limit,
+        });
+        assert_eq!(state_actor.head, within_limit);
+    }
+
+    #[test]
+    fn test_repeated_head_updates_count_as_a_single_reorg() {
+        let (mut state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::ZERO);
+        // Both blocks are built on top of genesis because the head is not updated in between
+        for _ in 0..2 {
+            state_actor.handle_command(Command::StartBlockBuild {
+                payload_attributes: Default::default(),
+                response_channel: oneshot::channel().0,
+            });
+        }
+        let block_hash = |height| {
+            state_actor
+                .block_queries
+                .by_height(&state_actor.block_memory, height, false)
+                .expect("Block should exist")
+                .0
+                .header
+                .hash
+        };
+        let (first, second) = (block_hash(1), block_hash(2));
+
+        state_actor.handle_command(Command::UpdateHead { block_hash: first });
+        assert_eq!(state_actor.reorg_count, 0);
+
+        for _ in 0..3 {
+            state_actor.handle_command(Command::UpdateHead { block_hash: second });
+        }
+        assert_eq!(state_actor.head, second);
+        assert_eq!(state_actor.reorg_count, 1);
+
+        state_actor.handle_command(Command::UpdateHead { block_hash: first });
+        assert_eq!(state_actor.reorg_count, 2);
+    }
+
+    #[test]
+    fn test_block_hashes_are_loaded_from_the_repository_on_restart() {
+        let (mut state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::ZERO);
+        let mut hashes = vec![state_actor.head];
+        for height in 1..=3 {
+            state_actor.handle_command(Command::StartBlockBuild {
+                payload_attributes: Default::default(),
+                response_channel: oneshot::channel().0,
+            });
+            let block_hash = state_actor
+                .block_queries
+                .by_height(&state_actor.block_memory, height, false)
+                .expect("Block should exist")
+                .0
+                .header
+                .hash;
+            state_actor.handle_command(Command::UpdateHead { block_hash });
+            hashes.push(block_hash);
+        }
+
+        let restarted = StateActor::new(
+            mpsc::channel(10).1,
+            state_actor.state,
+            state_actor.head,
+            state_actor.height,
+            state_actor.genesis_config,
+            state_actor.payload_id,
+            state_actor.block_hash,
+            state_actor.block_repository,
+            state_actor.gas_fee,
+            state_actor.l1_fee,
+            state_actor.l2_fee,
+            state_actor.base_token,
+            state_actor.block_queries,
+            state_actor.block_memory,
+            state_actor.state_queries,
+            StateActor::on_tx_noop(),
+            StateActor::on_tx_batch_noop(),
+        );
+
+        assert_eq!(restarted.block_hashes, state_actor.block_hashes);
+        let db = ResolverBackedDB::new(restarted.state.resolver())
+            .with_block_hashes(restarted.height + 1, restarted.block_hashes.clone());
+        for (height, hash) in hashes.into_iter().enumerate() {
+            assert_eq!(db.block_hash_ref(height as u64).unwrap(), hash);
+        }
+    }
+
+    fn start_block_build_at(
+        state_actor: &mut StateActor<
+            impl State<Err = PartialVMError>,
+            impl NewPayloadId,
+            impl BlockHash,
+            impl BlockRepository<Storage = BlockMemory>,
+            impl BaseGasFee,
+            impl CreateL1GasFee,
+            impl CreateL2GasFee + Sync,
+            impl BaseTokenAccounts + Sync,
+            impl BlockQueries<Storage = BlockMemory>,
+            BlockMemory,
+            impl StateQueries,
+        >,
+        timestamp: u64,
+    ) -> Result<PayloadId, InvalidPayloadAttributes> {
+        let (tx, rx) = oneshot::channel();
+        state_actor.handle_command(Command::StartBlockBuild {
+            payload_attributes: Payload {
+                timestamp: U64::from(timestamp),
+                ..Default::default()
+            },
+            response_channel: tx,
+        });
+        rx.blocking_recv().unwrap()
+    }
+
+    #[test]
+    fn test_future_timestamps_are_refused() {
+        let (state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::ZERO);
+        let mut state_actor = state_actor.with_max_timestamp_drift(Duration::from_secs(60));
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let result = start_block_build_at(&mut state_actor, now + 3600);
+        assert!(matches!(
+            result,
+            Err(InvalidPayloadAttributes::TimestampInFuture { .. })
+        ));
+        assert_eq!(state_actor.height, 0);
+
+        assert!(start_block_build_at(&mut state_actor, now + 30).is_ok());
+        assert_eq!(state_actor.height, 1);
+    }
+
+    #[test]
+    fn test_timestamps_not_after_parent_are_refused() {
+        let (state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::ZERO);
+        let mut state_actor = state_actor.with_max_timestamp_drift(Duration::from_secs(60));
+        let timestamp = 1_000;
+        assert!(start_block_build_at(&mut state_actor, timestamp).is_ok());
+        let block_hash = state_actor
+            .block_queries
+            .by_height(&state_actor.block_memory, 1, false)
+            .expect("Block should exist")
+            .0
+            .header
+            .hash;
+        state_actor.handle_command(Command::UpdateHead { block_hash });
+
+        for timestamp in [timestamp - 1, timestamp] {
+            let result = start_block_build_at(&mut state_actor, timestamp);
+            assert_eq!(
+                result,
+                Err(InvalidPayloadAttributes::TimestampNotAfterParent {
+                    timestamp,
+                    parent_timestamp: 1_000,
+                })
+            );
+        }
+        assert_eq!(state_actor.height, 1);
+
+        assert!(start_block_build_at(&mut state_actor, timestamp + 1).is_ok());
+        assert_eq!(state_actor.height, 2);
+    }
+
+    #[test]
+    fn test_max_block_transactions_defers_rest_of_mem_pool() {
+        let (state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::from(10));
+        let mut state_actor = state_actor.with_max_block_transactions(3);
+
+        let mut signer = Signer::new(&PRIVATE_KEY);
+        let mut transactions = Vec::new();
+        for _ in 0..10 {
+            transactions.push(signed_eip1559(
+                &signer,
+                signer.nonce,
+                TxKind::Call(address!("44223344556677889900ffeeaabbccddee111111")),
+                U256::from(1),
+                Vec::new(),
+            ));
+            signer.nonce += 1;
+        }
+        let tx_hashes = transactions
+            .iter()
+            .map(|tx| B256::from(tx.tx_hash().0))
+            .collect::<Vec<_>>();
+        let forced_tx = transactions.remove(0);
+        let mut encoded_forced_tx = Vec::new();
+        ExtendedTxEnvelope::Canonical(forced_tx).encode(&mut encoded_forced_tx);
+        for tx in transactions {
+            state_actor.handle_command(Command::AddTransaction { tx });
+        }
+
+        state_actor.handle_command(Command::StartBlockBuild {
+            payload_attributes: Payload {
+                transactions: vec![encoded_forced_tx.into()],
+                ..Default::default()
+            },
+            response_channel: oneshot::channel().0,
+        });
+
+        // The forced transaction takes one of the slots, the lowest nonces fill the rest
+        assert_eq!(state_actor.tx_receipts.len(), 3);
+        for tx_hash in &tx_hashes[..3] {
+            assert!(state_actor.tx_receipts.contains_key(tx_hash));
+        }
+        assert_eq!(state_actor.mem_pool.len(), 7);
+    }
+
+    #[test_case(false; "sequential")]
+    #[test_case(true; "parallel")]
+    fn test_deposits_precede_mem_pool_transactions(parallel_execution: bool) {
+        let (state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::from(10));
+        let mut state_actor = if parallel_execution {
+            state_actor.with_parallel_execution()
+        } else {
+            state_actor
+        };
+
+        // Mem-pool transactions arriving before the block build are still included after deposits
+        let mut signer = Signer::new(&PRIVATE_KEY);
+        let mut mem_pool_hashes = Vec::new();
+        for _ in 0..3 {
+            let tx = signed_eip1559(
+                &signer,
+                signer.nonce,
+                TxKind::Call(address!("44223344556677889900ffeeaabbccddee111111")),
+                U256::from(1),
+                Vec::new(),
+            );
+            signer.nonce += 1;
+            mem_pool_hashes.push(B256::from(tx.tx_hash().0));
+            state_actor.handle_command(Command::AddTransaction { tx });
+        }
+
+        let deposits = (1..=3u8)
+            .map(|i| {
+                let deposit = ExtendedTxEnvelope::DepositedTx(DepositedTx {
+                    source_hash: B256::repeat_byte(i),
+                    from: EVM_ADDRESS,
+                    to: EVM_ADDRESS,
+                    mint: U256::ZERO,
+                    value: U256::ZERO,
+                    gas: U64::from(u64::MAX),
+                    is_system_tx: false,
+                    data: Default::default(),
+                });
+                let mut encoded = Vec::new();
+                deposit.encode(&mut encoded);
+                encoded
+            })
+            .collect::<Vec<_>>();
+        let deposit_hashes = deposits
+            .iter()
+            .map(|deposit| B256::new(keccak256(deposit).0))
+            .collect::<Vec<_>>();
+
+        state_actor.handle_command(Command::StartBlockBuild {
+            payload_attributes: Payload {
+                transactions: deposits.into_iter().map(Into::into).collect(),
+                ..Default::default()
+            },
+            response_channel: oneshot::channel().0,
+        });
+
+        // Deposits keep the order of the payload attributes, the mem-pool follows in nonce order
+        let tx_index = |tx_hash| {
+            state_actor
+                .tx_receipts
+                .get(tx_hash)
+                .expect("Transaction should be included in the block")
+                .0
+                .tx_index
+        };
+        for (expected_index, tx_hash) in deposit_hashes.iter().chain(&mem_pool_hashes).enumerate() {
+            assert_eq!(tx_index(tx_hash), expected_index as u64);
+        }
+    }
+
+    #[test]
+    fn test_block_size_is_length_of_encoded_block() {
+        let (mut state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::from(10));
+
+        let mut signer = Signer::new(&PRIVATE_KEY);
+        for _ in 0..2 {
+            let tx = signed_eip1559(
+                &signer,
+                signer.nonce,
+                TxKind::Call(address!("44223344556677889900ffeeaabbccddee111111")),
+                U256::from(1),
+                Vec::new(),
+            );
+            signer.nonce += 1;
+            state_actor.handle_command(Command::AddTransaction { tx });
+        }
+        state_actor.handle_command(Command::StartBlockBuild {
+            payload_attributes: Default::default(),
+            response_channel: oneshot::channel().0,
+        });
+
+        for include_transactions in [false, true] {
+            let response = state_actor
+                .block_queries
+                .by_height(&state_actor.block_memory, 1, include_transactions)
+                .expect("Block should exist");
+            let block = state_actor
+                .block_repository
+                .by_hash(&state_actor.block_memory, response.0.header.hash)
+                .expect("Block should exist")
+                .block;
+            assert_eq!(block.transactions.len(), 2);
+
+            let expected_size = U256::from(block.rlp_encoded().len());
+            assert_eq!(response.0.header.size, Some(expected_size));
+        }
+    }
+
+    #[test]
+    fn test_no_tx_pool_builds_block_from_forced_transactions_only() {
+        let (mut state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::from(10));
+
+        let signer = Signer::new(&PRIVATE_KEY);
+        let signed_tx = |nonce| {
+            signed_eip1559(
+                &signer,
+                nonce,
+                TxKind::Call(address!("44223344556677889900ffeeaabbccddee111111")),
+                U256::from(1),
+                Vec::new(),
+            )
+        };
+        // The second transaction has a nonce too high, so it is invalid
+        let forced_txs = [signed_tx(0), signed_tx(5), signed_tx(1)];
+        let forced_tx_hashes = forced_txs
+            .iter()
+            .map(|tx| B256::from(tx.tx_hash().0))
+            .collect::<Vec<_>>();
+        let encoded_forced_txs = forced_txs
+            .into_iter()
+            .map(|tx| {
+                let mut encoded = Vec::new();
+                ExtendedTxEnvelope::Canonical(tx).encode(&mut encoded);
+                encoded.into()
+            })
+            .collect();
+        state_actor.handle_command(Command::AddTransaction { tx: signed_tx(2) });
+
+        state_actor.handle_command(Command::StartBlockBuild {
+            payload_attributes: Payload {
+                transactions: encoded_forced_txs,
+                no_tx_pool: true,
+                ..Default::default()
+            },
+            response_channel: oneshot::channel().0,
+        });
+
+        assert_eq!(state_actor.tx_receipts.len(), 3);
+        for (tx_index, tx_hash) in forced_tx_hashes.iter().enumerate() {
+            let (receipt, _) = state_actor.tx_receipts.get(tx_hash).unwrap();
+            assert_eq!(receipt.tx_index, tx_index as u64);
+            assert_eq!(receipt.receipt.is_success(), tx_index != 1);
+        }
+        let (invalid_receipt, _) = state_actor.tx_receipts.get(&forced_tx_hashes[1]).unwrap();
+        assert_eq!(invalid_receipt.gas_used, 0);
+        assert_eq!(state_actor.mem_pool.len(), 1);
+    }
+
+    #[test]
+    fn test_deposits_from_disallowed_senders_are_rejected() {
+        let (state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::ZERO);
+        let mut state_actor = state_actor.with_deposit_senders([]);
+
+        let encoded_deposit = |from| {
+            let deposit = ExtendedTxEnvelope::DepositedTx(DepositedTx {
+                source_hash: B256::ZERO,
+                from,
+                to: EVM_ADDRESS,
+                mint: U256::ZERO,
+                value: U256::ZERO,
+                gas: U64::from(u64::MAX),
+                is_system_tx: false,
+                data: Default::default(),
+            });
+            let mut encoded = Vec::new();
+            deposit.encode(&mut encoded);
+            encoded
+        };
+        let legitimate_deposit = encoded_deposit(L1_ATTRIBUTES_DEPOSITOR);
+        let forged_deposit = encoded_deposit(EVM_ADDRESS);
+        let legitimate_deposit_hash = B256::new(keccak256(&legitimate_deposit).0);
+        let forged_deposit_hash = B256::new(keccak256(&forged_deposit).0);
+
+        state_actor.handle_command(Command::StartBlockBuild {
+            payload_attributes: Payload {
+                transactions: vec![legitimate_deposit.into(), forged_deposit.into()],
+                ..Default::default()
+            },
+            response_channel: oneshot::channel().0,
+        });
+
+        assert!(state_actor
+            .tx_receipts
+            .contains_key(&legitimate_deposit_hash));
+        // Derived deposits are never dropped, forged ones fail without being executed
+        let (receipt, _) = state_actor.tx_receipts.get(&forged_deposit_hash).unwrap();
+        assert!(!receipt.receipt.is_success());
+        assert_eq!(receipt.gas_used, 0);
+        assert_eq!(receipt.tx_index, 1);
+    }
+
+    #[test]
+    fn test_malformed_deposits_are_rejected() {
+        let (state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::ZERO);
+        let mut state_actor = state_actor.with_deposit_checks();
+
+        let encoded_deposit = |source_hash, value| {
+            let deposit = ExtendedTxEnvelope::DepositedTx(DepositedTx {
+                source_hash,
+                from: EVM_ADDRESS,
+                to: EVM_ADDRESS,
+                mint: U256::from(10),
+                value,
+                gas: U64::from(u64::MAX),
+                is_system_tx: false,
+                data: Default::default(),
+            });
+            let mut encoded = Vec::new();
+            deposit.encode(&mut encoded);
+            encoded
+        };
+        let valid_deposit = encoded_deposit(B256::repeat_byte(1), U256::from(10));
+        let missing_source_hash = encoded_deposit(B256::ZERO, U256::from(10));
+        let excess_value = encoded_deposit(B256::repeat_byte(2), U256::from(11));
+        let valid_deposit_hash = B256::new(keccak256(&valid_deposit).0);
+        let missing_source_hash_hash = B256::new(keccak256(&missing_source_hash).0);
+        let excess_value_hash = B256::new(keccak256(&excess_value).0);
+
+        state_actor.handle_command(Command::StartBlockBuild {
+            payload_attributes: Payload {
+                transactions: vec![
+                    valid_deposit.into(),
+                    missing_source_hash.into(),
+                    excess_value.into(),
+                ],
+                ..Default::default()
+            },
+            response_channel: oneshot::channel().0,
+        });
+
+        let (receipt, _) = state_actor.tx_receipts.get(&valid_deposit_hash).unwrap();
+        assert!(receipt.receipt.is_success());
+        for tx_hash in [missing_source_hash_hash, excess_value_hash] {
+            let (receipt, _) = state_actor.tx_receipts.get(&tx_hash).unwrap();
+            assert!(!receipt.receipt.is_success());
+            assert_eq!(receipt.gas_used, 0);
+        }
+        // Only the valid deposit is minted
+        let balance = state_actor
+            .state_queries
+            .balance_at(state_actor.state.db(), EVM_ADDRESS.to_move_address(), 1)
+            .unwrap();
+        assert_eq!(balance, U256::from(10));
+    }
+
+    #[test]
+    fn test_l1_attributes_deposit_updates_l1_block_predeploy() {
+        let (mut state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::ZERO);
+        let l1_attributes_deposit = hex!("7ef8f8a0d449f5de7f558fa593dce80637d3a3f52cfaaee2913167371dd6ffd9014e431d94deaddeaddeaddeaddeaddeaddeaddeaddead00019442000000000000000000000000000000000000158080830f424080b8a4440a5e20000f424000000000000000000000000100000000666c9d8b0000000000000028000000000000000000000000000000000000000000000000000000000049165f0000000000000000000000000000000000000000000000000000000000000001d05450763214e6060d285b39ef5fe51ef9526395e5cef6ecb27ba06f9598f27d000000000000000000000000e25583099ba105d9ec0a67f5ae86d90e50036425");
+        let tx_hash = B256::new(keccak256(l1_attributes_deposit).0);
 
-        state_actor.handle_query(Query::NonceByHeight {
-            height,
-            address,
-            response_channel: tx,
+        state_actor.handle_command(Command::StartBlockBuild {
+            payload_attributes: Payload {
+                transactions: vec![l1_attributes_deposit.into()],
+                ..Default::default()
+            },
+            response_channel: oneshot::channel().0,
         });
 
-        let actual_nonce = rx.blocking_recv().unwrap().expect("Block should be found");
-        let expected_nonce = 3;
+        let (receipt, _) = state_actor.tx_receipts.get(&tx_hash).unwrap();
+        assert!(receipt.receipt.is_success());
 
-        assert_eq!(actual_nonce, expected_nonce);
+        let l1_block = address!("4200000000000000000000000000000000000015");
+        let db = ResolverBackedDB::new(state_actor.state.resolver());
+        let slot = |index: u64| db.storage_ref(l1_block, U256::from(index)).unwrap();
+        // `number` and `timestamp`
+        assert_eq!(slot(0), U256::from(0x666c9d8bu64) << 64 | U256::from(0x28));
+        // `basefee`
+        assert_eq!(slot(1), U256::from(0x49165f));
+        // `hash`
+        assert_eq!(
+            slot(2),
+            U256::from_be_bytes(hex!(
+                "d05450763214e6060d285b39ef5fe51ef9526395e5cef6ecb27ba06f9598f27d"
+            ))
+        );
+        // `sequenceNumber`, `blobBaseFeeScalar` and `baseFeeScalar`
+        assert_eq!(slot(3), U256::from(0xf4240) << 96 | U256::from(1));
     }
 
-    #[test_case(Latest, 2, 2; "Latest")]
-    #[test_case(Finalized, 2, 2; "Finalized")]
-    #[test_case(Safe, 2, 2; "Safe")]
-    #[test_case(Earliest, 2, 0; "Earliest")]
-    #[test_case(Pending, 2, 2; "Pending")]
-    #[test_case(Number(1), 2, 1; "Number")]
-    fn test_balance_is_fetched_by_height_successfully(
-        height: BlockNumberOrTag,
-        head_height: BlockHeight,
-        expected_height: BlockHeight,
-    ) {
-        let address = primitives::Address::new(hex!("44223344556677889900ffeeaabbccddee111111"));
-        let (state_actor, _) = create_state_actor_with_given_queries(
-            head_height,
-            MockStateQueries(address.to_move_address(), expected_height),
-        );
-        let (tx, rx) = oneshot::channel();
+    #[test]
+    fn test_deposits_above_max_gas_are_rejected() {
+        let (state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::ZERO);
+        let mut state_actor = state_actor.with_max_deposit_gas(1_000_000);
 
-        state_actor.handle_query(Query::BalanceByHeight {
-            height,
-            address,
-            response_channel: tx,
+        let encoded_deposit = |source_hash, gas| {
+            let deposit = ExtendedTxEnvelope::DepositedTx(DepositedTx {
+                source_hash,
+                from: EVM_ADDRESS,
+                to: EVM_ADDRESS,
+                mint: U256::from(1),
+                value: U256::ZERO,
+                gas: U64::from(gas),
+                is_system_tx: false,
+                data: Default::default(),
+            });
+            let mut encoded = Vec::new();
+            deposit.encode(&mut encoded);
+            encoded
+        };
+        let oversized_deposit = encoded_deposit(B256::repeat_byte(1), u64::MAX);
+        let deposit = encoded_deposit(B256::repeat_byte(2), 1_000_000);
+        let oversized_deposit_hash = B256::new(keccak256(&oversized_deposit).0);
+        let deposit_hash = B256::new(keccak256(&deposit).0);
+
+        state_actor.handle_command(Command::StartBlockBuild {
+            payload_attributes: Payload {
+                transactions: vec![oversized_deposit.into(), deposit.into()],
+                ..Default::default()
+            },
+            response_channel: oneshot::channel().0,
         });
 
-        let actual_balance = rx.blocking_recv().unwrap().expect("Block should be found");
-        let expected_balance = U256::from(5);
+        // The deposit is included as derived from L1, but fails without being executed
+        let (receipt, _) = state_actor
+            .tx_receipts
+            .get(&oversized_deposit_hash)
+            .unwrap();
+        assert!(!receipt.receipt.is_success());
+        assert_eq!(receipt.gas_used, 0);
+        let op_alloy::consensus::OpTxEnvelope::Deposit(included_deposit) = &receipt.tx else {
+            panic!("Block transaction should be a deposit");
+        };
+        assert_eq!(included_deposit.gas_limit, u64::MAX);
 
-        assert_eq!(actual_balance, expected_balance);
+        let (receipt, _) = state_actor.tx_receipts.get(&deposit_hash).unwrap();
+        assert!(receipt.receipt.is_success());
+    }
+
+    #[test]
+    fn test_latest_block_is_cached_until_new_block_is_built() {
+        let (mut state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::ZERO);
+        let latest_block = |state_actor: &StateActor<_, _, _, _, _, _, _, _, _, _, _>,
+                            include_transactions| {
+            let (tx, rx) = oneshot::channel();
+            state_actor.handle_query(Query::BlockByHeight {
+                height: Latest,
+                include_transactions,
+                response_channel: tx,
+            });
+            rx.blocking_recv()
+                .unwrap()
+                .expect("Latest block should exist")
+        };
+
+        assert_eq!(latest_block(&state_actor, false).0.header.number, 0);
+        assert_eq!(latest_block(&state_actor, false).0.header.number, 0);
+        let cache = state_actor.latest_block_cache.borrow();
+        assert!(cache[0].is_some());
+        assert!(cache[1].is_none());
+        drop(cache);
+
+        state_actor.handle_command(Command::StartBlockBuild {
+            payload_attributes: Default::default(),
+            response_channel: oneshot::channel().0,
+        });
+        assert!(state_actor.latest_block_cache.borrow()[0].is_none());
+
+        assert_eq!(latest_block(&state_actor, true).0.header.number, 1);
+        assert_eq!(latest_block(&state_actor, false).0.header.number, 1);
     }
 
     #[test]
@@ -1040,19 +4023,7 @@ mod tests {
             create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), initial_balance);
 
         let signer = Signer::new(&PRIVATE_KEY);
-        let mut tx = TxEip1559 {
-            chain_id: CHAIN_ID,
-            nonce: signer.nonce,
-            gas_limit: u64::MAX,
-            max_fee_per_gas: 0,
-            max_priority_fee_per_gas: 0,
-            to: TxKind::Call(to),
-            value: amount,
-            access_list: Default::default(),
-            input: Default::default(),
-        };
-        let signature = signer.inner.sign_transaction_sync(&mut tx).unwrap();
-        let tx = TxEnvelope::Eip1559(tx.into_signed(signature));
+        let tx = signed_eip1559(&signer, signer.nonce, TxKind::Call(to), amount, Vec::new());
 
         state_actor.handle_command(Command::AddTransaction { tx: tx.clone() });
         state_actor.handle_command(Command::StartBlockBuild {
@@ -1096,19 +4067,7 @@ mod tests {
             create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), initial_balance);
 
         let signer = Signer::new(&PRIVATE_KEY);
-        let mut tx = TxEip1559 {
-            chain_id: CHAIN_ID,
-            nonce: signer.nonce,
-            gas_limit: u64::MAX,
-            max_fee_per_gas: 0,
-            max_priority_fee_per_gas: 0,
-            to: TxKind::Call(to),
-            value: amount,
-            access_list: Default::default(),
-            input: Default::default(),
-        };
-        let signature = signer.inner.sign_transaction_sync(&mut tx).unwrap();
-        let tx = TxEnvelope::Eip1559(tx.into_signed(signature));
+        let tx = signed_eip1559(&signer, signer.nonce, TxKind::Call(to), amount, Vec::new());
 
         state_actor.handle_command(Command::AddTransaction { tx });
 
@@ -1145,4 +4104,643 @@ mod tests {
 
         assert_eq!(actual_sender_balance, expected_sender_balance);
     }
+
+    #[test]
+    fn test_estimated_gas_is_enough_to_execute_counter_entry_functions() {
+        let (mut state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::ZERO);
+        let signer = Signer::new(&PRIVATE_KEY);
+
+        let module_id = ModuleId::new(
+            EVM_ADDRESS.to_move_address(),
+            Identifier::new("counter").unwrap(),
+        );
+        let entry_fn = |function: &str, args: Vec<MoveValue>| {
+            let entry_fn = EntryFunction::new(
+                module_id.clone(),
+                Identifier::new(function).unwrap(),
+                Vec::new(),
+                args.iter().map(|arg| bcs::to_bytes(arg).unwrap()).collect(),
+            );
+            bcs::to_bytes(&TransactionData::EntryFunction(entry_fn)).unwrap()
+        };
+        let signer_arg = MoveValue::Signer(EVM_ADDRESS.to_move_address());
+        let address_arg = MoveValue::Address(EVM_ADDRESS.to_move_address());
+
+        let transactions = [
+            (TxKind::Create, counter_deploy_data()),
+            (
+                TxKind::Call(EVM_ADDRESS),
+                entry_fn("publish", vec![signer_arg, MoveValue::U64(7)]),
+            ),
+            (
+                TxKind::Call(EVM_ADDRESS),
+                entry_fn("increment", vec![address_arg]),
+            ),
+        ];
+
+        for (nonce, (to, input)) in transactions.into_iter().enumerate() {
+            let (tx, rx) = oneshot::channel();
+            state_actor.handle_query(Query::EstimateGas {
+                transaction: TransactionRequest {
+                    from: Some(EVM_ADDRESS),
+                    to: Some(to),
+                    input: TransactionInput::new(input.clone().into()),
+                    ..Default::default()
+                },
+                block_number: Latest,
+                response_channel: tx,
+            });
+            let estimate = rx
+                .blocking_recv()
+                .unwrap()
+                .expect("Gas estimation should succeed");
+
+            // Execute the same transaction with the estimate as the gas limit
+            let tx = sign_eip1559(
+                &signer,
+                TxEip1559 {
+                    gas_limit: estimate,
+                    ..unsigned_eip1559(nonce as u64, to, U256::ZERO, input)
+                },
+            );
+            let tx_hash = *tx.tx_hash();
+
+            state_actor.handle_command(Command::AddTransaction { tx });
+            state_actor.handle_command(Command::StartBlockBuild {
+                payload_attributes: Default::default(),
+                response_channel: oneshot::channel().0,
+            });
+
+            let receipt = state_actor
+                .query_transaction_receipt(tx_hash)
+                .expect("Transaction should be included in a block");
+            assert!(
+                receipt.inner.inner.status(),
+                "Transaction with estimated gas limit should succeed"
+            );
+        }
+    }
+
+    #[test]
+    fn test_estimate_gas_is_bounded_by_cap() {
+        let (mut state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::ZERO);
+        let counter_module = counter_deploy_data();
+        let query = || {
+            let (tx, rx) = oneshot::channel();
+            let query = Query::EstimateGas {
+                transaction: TransactionRequest {
+                    from: Some(EVM_ADDRESS),
+                    to: Some(TxKind::Create),
+                    input: TransactionInput::new(counter_module.clone().into()),
+                    ..Default::default()
+                },
+                block_number: Latest,
+                response_channel: tx,
+            };
+            (query, rx)
+        };
+
+        let (estimate, rx) = query();
+        state_actor.handle_query(estimate);
+        let gas_used = rx.blocking_recv().unwrap().unwrap() / 1000;
+
+        // The best estimate within the cap is returned
+        let mut state_actor = state_actor.with_max_estimate_gas(10 * gas_used);
+        let (estimate, rx) = query();
+        state_actor.handle_query(estimate);
+        assert_eq!(rx.blocking_recv().unwrap().unwrap(), 10 * gas_used);
+
+        // Executions that run out of the cap fail instead of running on
+        let mut state_actor = state_actor.with_max_estimate_gas(gas_used - 1);
+        let (estimate, rx) = query();
+        state_actor.handle_query(estimate);
+        let err = rx.blocking_recv().unwrap().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Gas required exceeds the estimation cap of {}",
+                gas_used - 1
+            )
+        );
+    }
+
+    #[test]
+    fn test_call_is_bounded_by_rpc_gas_cap() {
+        let (state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::ZERO);
+        let mut state_actor = state_actor.with_rpc_gas_cap(1_000_000);
+
+        // A script looping forever, only the gas limit stops it
+        let mut script = empty_script();
+        script.code.code = vec![Bytecode::Branch(0)];
+        let mut script_bytes = Vec::new();
+        script.serialize(&mut script_bytes).unwrap();
+        let input = bcs::to_bytes(&ScriptOrModule::Script(Script::new(
+            script_bytes,
+            Vec::new(),
+            Vec::new(),
+        )))
+        .unwrap();
+
+        // The request does not set a gas limit, so it would run with `u64::MAX` without the cap
+        let (tx, rx) = oneshot::channel();
+        state_actor.handle_query(Query::Call {
+            transaction: TransactionRequest {
+                from: Some(EVM_ADDRESS),
+                to: Some(TxKind::Create),
+                input: TransactionInput::new(input.into()),
+                ..Default::default()
+            },
+            block_number: Latest,
+            response_channel: tx,
+        });
+        let err = rx.blocking_recv().unwrap().unwrap_err();
+        assert!(format!("{err:?}").contains("OUT_OF_GAS"), "{err:?}");
+    }
+
+    #[test]
+    fn test_call_reads_counter_value_at_historical_heights() {
+        let (mut state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::ZERO);
+        let signer = Signer::new(&PRIVATE_KEY);
+
+        let module_id = ModuleId::new(
+            EVM_ADDRESS.to_move_address(),
+            Identifier::new("counter").unwrap(),
+        );
+        let entry_fn = |function: &str, args: Vec<MoveValue>| {
+            let entry_fn = EntryFunction::new(
+                module_id.clone(),
+                Identifier::new(function).unwrap(),
+                Vec::new(),
+                args.iter().map(|arg| bcs::to_bytes(arg).unwrap()).collect(),
+            );
+            bcs::to_bytes(&TransactionData::EntryFunction(entry_fn)).unwrap()
+        };
+        let signer_arg = MoveValue::Signer(EVM_ADDRESS.to_move_address());
+        let address_arg = MoveValue::Address(EVM_ADDRESS.to_move_address());
+
+        // One transaction per block, the counter is created at height 2
+        let transactions = [
+            (TxKind::Create, counter_deploy_data()),
+            (
+                TxKind::Call(EVM_ADDRESS),
+                entry_fn("publish", vec![signer_arg, MoveValue::U64(7)]),
+            ),
+            (
+                TxKind::Call(EVM_ADDRESS),
+                entry_fn("increment", vec![address_arg.clone()]),
+            ),
+            (
+                TxKind::Call(EVM_ADDRESS),
+                entry_fn("increment", vec![address_arg.clone()]),
+            ),
+        ];
+
+        for (nonce, (to, input)) in transactions.into_iter().enumerate() {
+            let tx = signed_eip1559(&signer, nonce as u64, to, U256::ZERO, input);
+
+            state_actor.handle_command(Command::AddTransaction { tx });
+            state_actor.handle_command(Command::StartBlockBuild {
+                payload_attributes: Default::default(),
+                response_channel: oneshot::channel().0,
+            });
+        }
+
+        let call = |block_number| {
+            let (tx, rx) = oneshot::channel();
+            state_actor.handle_query(Query::Call {
+                transaction: TransactionRequest {
+                    from: Some(EVM_ADDRESS),
+                    to: Some(TxKind::Call(EVM_ADDRESS)),
+                    input: TransactionInput::new(
+                        entry_fn("get_count", vec![address_arg.clone()]).into(),
+                    ),
+                    ..Default::default()
+                },
+                block_number,
+                response_channel: tx,
+            });
+            rx.blocking_recv().unwrap()
+        };
+
+        let count_response = |count: u64| {
+            let return_values = vec![(bcs::to_bytes(&count).unwrap(), MoveTypeLayout::U64)];
+            bcs::to_bytes(&return_values).unwrap()
+        };
+
+        for (height, expected_count) in [(2, 7), (3, 8), (4, 9)] {
+            let actual_response = call(Number(height)).expect("Historical call should succeed");
+
+            assert_eq!(actual_response, count_response(expected_count));
+        }
+
+        let actual_response = call(Latest).expect("Latest call should succeed");
+        assert_eq!(actual_response, count_response(9));
+
+        let err = call(Number(5)).expect_err("Future block height should not be found");
+        assert!(matches!(
+            err,
+            User(UserError::StateUnavailable(StateUnavailable::BlockNotFound))
+        ));
+    }
+
+    #[test]
+    fn test_call_reading_tables_at_past_height_fails_without_panicking() {
+        let (mut state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::ZERO);
+        let signer = Signer::new(&PRIVATE_KEY);
+
+        let module_bytes = ModuleCompileJob::new("tables", &EVM_ADDRESS.to_move_address())
+            .compile()
+            .unwrap();
+        let tx = signed_eip1559(
+            &signer,
+            0,
+            TxKind::Create,
+            U256::ZERO,
+            module_bytes_to_tx_data(module_bytes),
+        );
+        state_actor.handle_command(Command::AddTransaction { tx });
+        // The module is deployed at height 1, which is in the past after the second block
+        for _ in 0..2 {
+            state_actor.handle_command(Command::StartBlockBuild {
+                payload_attributes: Default::default(),
+                response_channel: oneshot::channel().0,
+            });
+        }
+
+        let entry_fn = EntryFunction::new(
+            ModuleId::new(
+                EVM_ADDRESS.to_move_address(),
+                Identifier::new("tables").unwrap(),
+            ),
+            Identifier::new("make_test_tables").unwrap(),
+            Vec::new(),
+            vec![bcs::to_bytes(&MoveValue::Signer(EVM_ADDRESS.to_move_address())).unwrap()],
+        );
+        let input = bcs::to_bytes(&TransactionData::EntryFunction(entry_fn)).unwrap();
+        let call = |block_number| {
+            let (tx, rx) = oneshot::channel();
+            state_actor.handle_query(Query::Call {
+                transaction: TransactionRequest {
+                    from: Some(EVM_ADDRESS),
+                    to: Some(TxKind::Call(EVM_ADDRESS)),
+                    input: TransactionInput::new(input.clone().into()),
+                    ..Default::default()
+                },
+                block_number,
+                response_channel: tx,
+            });
+            rx.blocking_recv().unwrap()
+        };
+
+        call(Latest).expect("Latest call should read table entries");
+
+        let err = call(Number(1)).expect_err("Table entries of past heights are not available");
+        assert!(
+            matches!(err, User(UserError::Vm(_))),
+            "Unexpected error {err:?}"
+        );
+    }
+
+    #[test]
+    fn test_pending_call_sees_mem_pool_transactions() {
+        let (state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::ZERO);
+        let mut state_actor = state_actor.with_pending_state_calls();
+        let signer = Signer::new(&PRIVATE_KEY);
+
+        let module_id = ModuleId::new(
+            EVM_ADDRESS.to_move_address(),
+            Identifier::new("counter").unwrap(),
+        );
+        let entry_fn = |function: &str, args: Vec<MoveValue>| {
+            let entry_fn = EntryFunction::new(
+                module_id.clone(),
+                Identifier::new(function).unwrap(),
+                Vec::new(),
+                args.iter().map(|arg| bcs::to_bytes(arg).unwrap()).collect(),
+            );
+            bcs::to_bytes(&TransactionData::EntryFunction(entry_fn)).unwrap()
+        };
+        let signer_arg = MoveValue::Signer(EVM_ADDRESS.to_move_address());
+        let address_arg = MoveValue::Address(EVM_ADDRESS.to_move_address());
+
+        // The counter is published in blocks, the increment stays in the mem-pool
+        let transactions = [
+            (TxKind::Create, counter_deploy_data()),
+            (
+                TxKind::Call(EVM_ADDRESS),
+                entry_fn("publish", vec![signer_arg, MoveValue::U64(7)]),
+            ),
+            (
+                TxKind::Call(EVM_ADDRESS),
+                entry_fn("increment", vec![address_arg.clone()]),
+            ),
+        ];
+        for (nonce, (to, input)) in transactions.into_iter().enumerate() {
+            let tx = signed_eip1559(&signer, nonce as u64, to, U256::ZERO, input);
+
+            state_actor.handle_command(Command::AddTransaction { tx });
+            if nonce < 2 {
+                state_actor.handle_command(Command::StartBlockBuild {
+                    payload_attributes: Default::default(),
+                    response_channel: oneshot::channel().0,
+                });
+            }
+        }
+        assert_eq!(state_actor.mem_pool.len(), 1);
+
+        let call = |block_number| {
+            let (tx, rx) = oneshot::channel();
+            state_actor.handle_query(Query::Call {
+                transaction: TransactionRequest {
+                    from: Some(EVM_ADDRESS),
+                    to: Some(TxKind::Call(EVM_ADDRESS)),
+                    input: TransactionInput::new(
+                        entry_fn("get_count", vec![address_arg.clone()]).into(),
+                    ),
+                    ..Default::default()
+                },
+                block_number,
+                response_channel: tx,
+            });
+            rx.blocking_recv().unwrap().expect("Call should succeed")
+        };
+        let count_response = |count: u64| {
+            let return_values = vec![(bcs::to_bytes(&count).unwrap(), MoveTypeLayout::U64)];
+            bcs::to_bytes(&return_values).unwrap()
+        };
+
+        assert_eq!(call(Pending), count_response(8));
+        assert_eq!(call(Latest), count_response(7));
+        // Pending calls leave both the state and the mem-pool untouched
+        assert_eq!(call(Pending), count_response(8));
+        assert_eq!(state_actor.mem_pool.len(), 1);
+    }
+
+    #[test]
+    fn test_traced_transaction_is_executed_after_preceding_ones_in_its_block() {
+        let (mut state_actor, _) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::ZERO);
+        let signer = Signer::new(&PRIVATE_KEY);
+
+        // A transfer followed by `decimals()` of the gas price oracle predeploy, which is only valid
+        // with the nonce of the transfer applied
+        let gas_price_oracle = address!("420000000000000000000000000000000000000f");
+        let transactions = [
+            (ALT_EVM_ADDRESS, Vec::new()),
+            (gas_price_oracle, hex!("313ce567").to_vec()),
+        ];
+        let mut tx_hashes = Vec::new();
+        for (nonce, (to, input)) in transactions.into_iter().enumerate() {
+            let tx = signed_eip1559(&signer, nonce as u64, TxKind::Call(to), U256::ZERO, input);
+            tx_hashes.push(B256::from(tx.tx_hash().0));
+            state_actor.handle_command(Command::AddTransaction { tx });
+        }
+        // The parent of the block with both transactions is in the past after the second block
+        for _ in 0..2 {
+            state_actor.handle_command(Command::StartBlockBuild {
+                payload_attributes: Default::default(),
+                response_channel: oneshot::channel().0,
+            });
+        }
+
+        let trace = |tx_hash, config| {
+            let (tx, rx) = oneshot::channel();
+            state_actor.handle_query(Query::TraceTransaction {
+                tx_hash,
+                config,
+                response_channel: tx,
+            });
+            rx.blocking_recv().unwrap().expect("Trace should succeed")
+        };
+
+        let actual_trace = trace(tx_hashes[1], StructLoggerConfig::default())
+            .expect("Included transaction should be traced");
+        let (receipt, _) = state_actor.tx_receipts.get(&tx_hashes[1]).unwrap();
+        assert!(!actual_trace.failed);
+        assert_eq!(actual_trace.gas, receipt.gas_used);
+        assert_eq!(
+            actual_trace.return_value.as_ref(),
+            &U256::from(6).to_be_bytes::<32>()
+        );
+        // The proxy delegates to the implementation, which runs one call deeper
+        let logs = &actual_trace.struct_logs;
+        assert_eq!((logs[0].pc, logs[0].depth), (0, 1));
+        assert!(logs.iter().any(|log| log.op == "DELEGATECALL"));
+        assert!(logs.iter().any(|log| log.depth == 2));
+        assert!(logs.iter().all(|log| log.stack.is_some()));
+
+        let config = StructLoggerConfig {
+            disable_memory: true,
+            disable_stack: true,
+            disable_storage: true,
+        };
+        let disabled_trace = trace(tx_hashes[1], config).unwrap();
+        assert_eq!(disabled_trace.struct_logs.len(), logs.len());
+        assert!(disabled_trace
+            .struct_logs
+            .iter()
+            .all(|log| log.stack.is_none() && log.memory.is_none() && log.storage.is_none()));
+
+        // Transactions without EVM execution have no struct logs
+        let transfer_trace = trace(tx_hashes[0], StructLoggerConfig::default()).unwrap();
+        assert!(transfer_trace.struct_logs.is_empty());
+
+        assert!(trace(B256::ZERO, StructLoggerConfig::default()).is_none());
+    }
+
+    #[test]
+    fn test_parallel_execution_builds_same_blocks_as_sequential_execution() {
+        let senders = [EVM_ADDRESS, ALT_EVM_ADDRESS];
+        let recipients = [
+            EVM_ADDRESS,
+            ALT_EVM_ADDRESS,
+            address!("44223344556677889900ffeeaabbccddee111111"),
+            address!("55223344556677889900ffeeaabbccddee222222"),
+        ];
+        let weth = address!("4200000000000000000000000000000000000006");
+        let counter_module = counter_deploy_data();
+        let counter_call = |function: &str, args: Vec<MoveValue>| {
+            let entry_fn = EntryFunction::new(
+                ModuleId::new(
+                    EVM_ADDRESS.to_move_address(),
+                    Identifier::new("counter").unwrap(),
+                ),
+                Identifier::new(function).unwrap(),
+                Vec::new(),
+                args.iter().map(|arg| bcs::to_bytes(arg).unwrap()).collect(),
+            );
+            bcs::to_bytes(&TransactionData::EntryFunction(entry_fn)).unwrap()
+        };
+
+        for seed in [1u64, 7, 42, 1234] {
+            // Xorshift, enough to get different interleavings of conflicting transactions
+            let mut state = seed;
+            let mut random = move |bound: u64| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state % bound
+            };
+            let mut signers = [Signer::new(&PRIVATE_KEY), Signer::new(&ALT_PRIVATE_KEY)];
+            // Transfers, Move counter increments and WETH deposits and transfers writing EVM storage
+            let mut transactions = Vec::new();
+            for _ in 0..20 {
+                let sender = random(2) as usize;
+                let value = 1 + random(1000);
+                let transaction = match random(4) {
+                    0 => (
+                        TxKind::Call(recipients[random(recipients.len() as u64) as usize]),
+                        value,
+                        Vec::new(),
+                    ),
+                    1 => (
+                        TxKind::Call(EVM_ADDRESS),
+                        0,
+                        counter_call(
+                            "increment",
+                            vec![MoveValue::Address(EVM_ADDRESS.to_move_address())],
+                        ),
+                    ),
+                    2 => (TxKind::Call(weth), value, hex!("d0e30db0").to_vec()),
+                    _ => {
+                        // The transfer reverts when the deposits so far do not cover it
+                        let to = senders[1 - sender].into_word();
+                        let amount = U256::from(value).to_be_bytes::<32>();
+                        let input = [
+                            hex!("a9059cbb").as_slice(),
+                            to.as_slice(),
+                            amount.as_slice(),
+                        ]
+                        .concat();
+                        (TxKind::Call(weth), 0, input)
+                    }
+                };
+                transactions.push((sender, transaction));
+            }
+
+            let block_hashes = [false, true].map(|parallel| {
+                let (state_actor, _) = create_state_actor_with_fake_queries(
+                    EVM_ADDRESS.to_move_address(),
+                    U256::from(1_000_000),
+                );
+                let mut state_actor = if parallel {
+                    state_actor.with_parallel_execution()
+                } else {
+                    state_actor
+                };
+                for signer in signers.iter_mut() {
+                    signer.nonce = 0;
+                }
+                let mut signed_tx = |sender: usize, (to, value, input): (TxKind, u64, Vec<u8>)| {
+                    let signer = &mut signers[sender];
+                    let tx = signed_eip1559(signer, signer.nonce, to, U256::from(value), input);
+                    signer.nonce += 1;
+                    tx
+                };
+
+                // The second sender is funded and the counter is published in the first block
+                let setup = [
+                    (TxKind::Call(ALT_EVM_ADDRESS), 100_000, Vec::new()),
+                    (TxKind::Create, 0, counter_module.clone()),
+                    (
+                        TxKind::Call(EVM_ADDRESS),
+                        0,
+                        counter_call(
+                            "publish",
+                            vec![
+                                MoveValue::Signer(EVM_ADDRESS.to_move_address()),
+                                MoveValue::U64(0),
+                            ],
+                        ),
+                    ),
+                ];
+                for transaction in setup {
+                    let tx = signed_tx(0, transaction);
+                    state_actor.handle_command(Command::AddTransaction { tx });
+                }
+                state_actor.handle_command(Command::StartBlockBuild {
+                    payload_attributes: Default::default(),
+                    response_channel: oneshot::channel().0,
+                });
+                for (sender, transaction) in transactions.iter() {
+                    let tx = signed_tx(*sender, transaction.clone());
+                    state_actor.handle_command(Command::AddTransaction { tx });
+                }
+                state_actor.handle_command(Command::StartBlockBuild {
+                    payload_attributes: Default::default(),
+                    response_channel: oneshot::channel().0,
+                });
+                assert!(state_actor.mem_pool.is_empty());
+
+                let response = state_actor
+                    .block_queries
+                    .by_height(&state_actor.block_memory, 2, false)
+                    .expect("Block should exist");
+                let block = state_actor
+                    .block_repository
+                    .by_hash(&state_actor.block_memory, response.0.header.hash)
+                    .expect("Block should exist")
+                    .block;
+                assert_eq!(block.transactions.len(), transactions.len());
+
+                response.0.header.hash
+            });
+
+            assert_eq!(block_hashes[0], block_hashes[1], "Seed {seed}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_queued_messages_are_processed_on_shutdown() {
+        let (state_actor, state_channel) =
+            create_state_actor_with_fake_queries(EVM_ADDRESS.to_move_address(), U256::ZERO);
+
+        let mut payload_ids = Vec::new();
+        for _ in 0..3 {
+            let (tx, rx) = oneshot::channel();
+            state_channel
+                .send(
+                    Command::StartBlockBuild {
+                        payload_attributes: Default::default(),
+                        response_channel: tx,
+                    }
+                    .into(),
+                )
+                .await
+                .unwrap();
+            payload_ids.push(rx);
+        }
+        let (tx, height) = oneshot::channel();
+        state_channel
+            .send(
+                Query::BlockNumber {
+                    response_channel: tx,
+                }
+                .into(),
+            )
+            .await
+            .unwrap();
+
+        // Shut down right away, before any of the queued messages is processed
+        let state_handle = state_actor.spawn_with_shutdown(async {});
+        state_handle.await.unwrap();
+
+        for payload_id in payload_ids {
+            let payload_id = payload_id.await.expect("Block should be built before exit");
+            assert!(payload_id.is_ok());
+        }
+        assert_eq!(height.await.unwrap(), 3);
+
+        let (tx, _rx) = oneshot::channel();
+        let msg = Query::BlockNumber {
+            response_channel: tx,
+        };
+        assert!(state_channel.send(msg.into()).await.is_err());
+    }
 }
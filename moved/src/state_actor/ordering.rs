@@ -0,0 +1,213 @@
+use {
+    crate::{
+        move_execution::L1GasFeeInput,
+        primitives::{Address, B256, U256},
+        types::transactions::{ExtendedTxEnvelope, NormalizedExtendedTxEnvelope},
+    },
+    std::{
+        cmp::Reverse,
+        collections::{BinaryHeap, HashMap},
+    },
+};
+
+/// A transaction waiting in the mem-pool to be included in a block.
+#[derive(Debug, Clone)]
+pub struct MemPoolTx {
+    pub tx: ExtendedTxEnvelope,
+    pub l1_cost_input: L1GasFeeInput,
+    /// Sequence number of the transaction in the mem-pool, lower numbers were received earlier.
+    pub arrival: u64,
+}
+
+/// Policy deciding how mem-pool transactions of different accounts are interleaved in a block.
+///
+/// Transactions of the same account are always included in nonce order, only the heads of the
+/// per-account queues compete with each other. Ties are broken by the transaction hash so the
+/// resulting order is deterministic.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum TransactionOrdering {
+    /// Transactions paying the highest tip per gas at the block base fee come first.
+    #[default]
+    TipDescending,
+    /// Transactions received first come first.
+    ReceivedTime,
+    /// Transactions with the highest priority returned by the function come first. The function
+    /// is given the transaction and the block base fee.
+    Custom(fn(&NormalizedExtendedTxEnvelope, U256) -> U256),
+}
+
+impl TransactionOrdering {
+    fn priority(&self, tx: &NormalizedExtendedTxEnvelope, arrival: u64, base_fee: U256) -> U256 {
+        match self {
+            Self::TipDescending => tx.tip_per_gas(base_fee),
+            Self::ReceivedTime => U256::from(u64::MAX - arrival),
+            Self::Custom(priority) => priority(tx, base_fee),
+        }
+    }
+
+    /// Sorts `transactions` for inclusion in a block with `base_fee`.
+    ///
+    /// Transactions without a recoverable sender are placed last, they will be rejected during
+    /// execution anyway.
+    pub fn order(
+        &self,
+        transactions: impl IntoIterator<Item = (B256, MemPoolTx)>,
+        base_fee: U256,
+    ) -> Vec<(B256, MemPoolTx)> {
+        let mut invalid = Vec::new();
+        let mut queues: HashMap<Address, Vec<(u64, U256, B256, MemPoolTx)>> = HashMap::new();
+        for (tx_hash, mem_pool_tx) in transactions {
+            let Ok(normalized_tx) = NormalizedExtendedTxEnvelope::try_from(mem_pool_tx.tx.clone())
+            else {
+                invalid.push((tx_hash, mem_pool_tx));
+                continue;
+            };
+            let nonce = match &normalized_tx {
+                NormalizedExtendedTxEnvelope::Canonical(tx) => tx.nonce,
+                NormalizedExtendedTxEnvelope::DepositedTx(_) => 0,
+            };
+            let priority = self.priority(&normalized_tx, mem_pool_tx.arrival, base_fee);
+            queues.entry(normalized_tx.sender()).or_default().push((
+                nonce,
+                priority,
+                tx_hash,
+                mem_pool_tx,
+            ));
+        }
+
+        // Queues are popped from the back, so the lowest nonce is sorted last
+        let mut heads = BinaryHeap::new();
+        for (sender, queue) in queues.iter_mut() {
+            queue.sort_by_key(|(nonce, _, _, tx)| Reverse((*nonce, tx.arrival)));
+            if let Some((_, priority, tx_hash, _)) = queue.last() {
+                heads.push((*priority, Reverse(*tx_hash), *sender));
+            }
+        }
+
+        let mut ordered = Vec::new();
+        while let Some((_, _, sender)) = heads.pop() {
+            let queue = queues
+                .get_mut(&sender)
+                .expect("Sender of a queue head has a queue");
+            let (_, _, tx_hash, mem_pool_tx) = queue.pop().expect("Queue head exists");
+            ordered.push((tx_hash, mem_pool_tx));
+            if let Some((_, priority, tx_hash, _)) = queue.last() {
+                heads.push((*priority, Reverse(*tx_hash), sender));
+            }
+        }
+
+        invalid.sort_by_key(|(tx_hash, _)| *tx_hash);
+        ordered.extend(invalid);
+        ordered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            genesis::config::CHAIN_ID,
+            tests::{signer::Signer, ALT_PRIVATE_KEY, PRIVATE_KEY},
+        },
+        alloy::{
+            consensus::{SignableTransaction, TxEip1559, TxEnvelope},
+            network::TxSignerSync,
+            primitives::{address, TxKind},
+        },
+    };
+
+    fn mem_pool_tx(
+        signer: &mut Signer,
+        max_priority_fee_per_gas: u128,
+        arrival: u64,
+    ) -> (B256, MemPoolTx) {
+        let mut tx = TxEip1559 {
+            chain_id: CHAIN_ID,
+            nonce: signer.nonce,
+            gas_limit: 21_000,
+            max_fee_per_gas: 100,
+            max_priority_fee_per_gas,
+            to: TxKind::Call(address!("44223344556677889900ffeeaabbccddee111111")),
+            value: U256::from(1),
+            access_list: Default::default(),
+            input: Default::default(),
+        };
+        signer.nonce += 1;
+        let signature = signer.inner.sign_transaction_sync(&mut tx).unwrap();
+        let tx = TxEnvelope::Eip1559(tx.into_signed(signature));
+        let tx_hash = B256::from(tx.tx_hash().0);
+
+        (
+            tx_hash,
+            MemPoolTx {
+                tx: ExtendedTxEnvelope::Canonical(tx),
+                l1_cost_input: L1GasFeeInput::default(),
+                arrival,
+            },
+        )
+    }
+
+    #[test]
+    fn test_tip_descending_puts_higher_tips_of_other_accounts_first() {
+        let mut signer = Signer::new(&PRIVATE_KEY);
+        let mut alt_signer = Signer::new(&ALT_PRIVATE_KEY);
+        let low_tip = mem_pool_tx(&mut signer, 1, 0);
+        let high_tip = mem_pool_tx(&mut alt_signer, 10, 1);
+        let medium_tip = mem_pool_tx(&mut alt_signer, 5, 2);
+        let expected = vec![high_tip.0, medium_tip.0, low_tip.0];
+
+        let ordered = TransactionOrdering::TipDescending
+            .order([low_tip, medium_tip, high_tip], U256::from(10));
+
+        let actual: Vec<_> = ordered.into_iter().map(|(tx_hash, _)| tx_hash).collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_tip_descending_keeps_nonce_order_of_an_account() {
+        let mut signer = Signer::new(&PRIVATE_KEY);
+        let mut alt_signer = Signer::new(&ALT_PRIVATE_KEY);
+        let first_nonce = mem_pool_tx(&mut signer, 1, 0);
+        let second_nonce = mem_pool_tx(&mut signer, 20, 1);
+        let other_account = mem_pool_tx(&mut alt_signer, 10, 2);
+        let expected = vec![other_account.0, first_nonce.0, second_nonce.0];
+
+        let ordered = TransactionOrdering::TipDescending
+            .order([second_nonce, other_account, first_nonce], U256::from(10));
+
+        let actual: Vec<_> = ordered.into_iter().map(|(tx_hash, _)| tx_hash).collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_equal_tips_are_ordered_by_hash() {
+        let mut signer = Signer::new(&PRIVATE_KEY);
+        let mut alt_signer = Signer::new(&ALT_PRIVATE_KEY);
+        let tx = mem_pool_tx(&mut signer, 5, 0);
+        let alt_tx = mem_pool_tx(&mut alt_signer, 5, 1);
+        let mut expected = vec![tx.0, alt_tx.0];
+        expected.sort();
+
+        for transactions in [[tx.clone(), alt_tx.clone()], [alt_tx, tx]] {
+            let ordered = TransactionOrdering::TipDescending.order(transactions, U256::from(10));
+
+            let actual: Vec<_> = ordered.into_iter().map(|(tx_hash, _)| tx_hash).collect();
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_received_time_puts_earlier_transactions_first() {
+        let mut signer = Signer::new(&PRIVATE_KEY);
+        let mut alt_signer = Signer::new(&ALT_PRIVATE_KEY);
+        let first = mem_pool_tx(&mut alt_signer, 1, 0);
+        let second = mem_pool_tx(&mut signer, 10, 1);
+        let expected = vec![first.0, second.0];
+
+        let ordered = TransactionOrdering::ReceivedTime.order([second, first], U256::from(10));
+
+        let actual: Vec<_> = ordered.into_iter().map(|(tx_hash, _)| tx_hash).collect();
+        assert_eq!(actual, expected);
+    }
+}
@@ -5,8 +5,27 @@ use {
     },
     alloy::eips::eip4895::Withdrawal,
     sha2::{Digest, Sha256},
+    thiserror::Error,
 };
 
+/// The reason payload attributes are refused before building a block on top of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum InvalidPayloadAttributes {
+    #[error("Timestamp {timestamp} is not after the parent block timestamp {parent_timestamp}")]
+    TimestampNotAfterParent {
+        timestamp: u64,
+        parent_timestamp: u64,
+    },
+    #[error(
+        "Timestamp {timestamp} is more than {max_drift} seconds ahead of the local clock {now}"
+    )]
+    TimestampInFuture {
+        timestamp: u64,
+        now: u64,
+        max_drift: u64,
+    },
+}
+
 /// The payload ID algorithm arguments.
 ///
 /// See trait [`NewPayloadId`] for the definition of the Payload ID creation behavior.
@@ -2,12 +2,12 @@ use {
     crate::{
         move_execution::{
             evm_native::{self, EVM_NATIVE_ADDRESS},
-            quick_get_eth_balance, quick_get_nonce,
+            quick_get_nonce, try_quick_get_eth_balance,
         },
-        primitives::{KeyHashable, ToEthAddress, B256, U256},
-        storage::IN_MEMORY_EXPECT_MSG,
+        primitives::{KeyHashable, ToEthAddress, B256, U256, U64},
+        storage::TreeKey,
         types::{
-            queries::{ProofResponse, StorageProof},
+            queries::{AccountResponse, ProofResponse, StorageProof},
             transactions::{L2_HIGHEST_ADDRESS, L2_LOWEST_ADDRESS},
         },
     },
@@ -15,9 +15,10 @@ use {
     aptos_types::state_store::{state_key::StateKey, state_value::StateValue},
     bytes::Bytes,
     eth_trie::{EthTrie, Trie, TrieError, DB},
-    move_binary_format::errors::PartialVMError,
+    move_binary_format::{errors::PartialVMError, CompiledModule},
     move_core_types::{
         account_address::AccountAddress,
+        effects::ChangeSet,
         language_storage::{ModuleId, StructTag},
         metadata::Metadata,
         resolver::{ModuleResolver, MoveResolver, ResourceResolver},
@@ -25,8 +26,16 @@ use {
         vm_status::StatusCode,
     },
     move_table_extension::{TableHandle, TableResolver},
-    revm::DatabaseRef,
-    std::{fmt::Debug, sync::Arc},
+    revm::{primitives::KECCAK_EMPTY, DatabaseRef},
+    std::{
+        collections::{HashMap, VecDeque},
+        fmt::Debug,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex, MutexGuard,
+        },
+    },
+    thiserror::Error,
 };
 
 /// A non-negative integer for indicating the amount of base token on an account.
@@ -62,7 +71,7 @@ pub trait StateQueries {
         db: Arc<impl DB>,
         account: AccountAddress,
         height: BlockHeight,
-    ) -> Option<Balance>;
+    ) -> Result<Balance, StateUnavailable>;
 
     /// Queries the blockchain state version corresponding with block `height` for the nonce value
     /// associated with `account`.
@@ -71,7 +80,7 @@ pub trait StateQueries {
         db: Arc<impl DB>,
         account: AccountAddress,
         height: BlockHeight,
-    ) -> Option<Nonce>;
+    ) -> Result<Nonce, StateUnavailable>;
 
     fn get_proof(
         &self,
@@ -80,35 +89,255 @@ pub trait StateQueries {
         storage_slots: &[U256],
         height: BlockHeight,
     ) -> Option<ProofResponse>;
+
+    /// Queries the blockchain state version corresponding with block `height` for the balance,
+    /// nonce and code hash of `account` at once.
+    fn account_at(
+        &self,
+        db: Arc<impl DB>,
+        account: AccountAddress,
+        height: BlockHeight,
+    ) -> Result<AccountResponse, StateUnavailable>;
+
+    /// Queries the blockchain state version corresponding with block `height` for whether
+    /// `account` holds a `resource`. Only the state key of the resource is looked up, the value is
+    /// not deserialized.
+    fn resource_exists_at(
+        &self,
+        db: Arc<impl DB>,
+        account: AccountAddress,
+        resource: &StructTag,
+        height: BlockHeight,
+    ) -> Result<bool, StateUnavailable>;
+
+    /// Creates a resolver that reads the blockchain state version corresponding with block
+    /// `height`.
+    fn resolver_at(
+        &self,
+        db: Arc<impl DB>,
+        height: BlockHeight,
+    ) -> Option<impl MoveResolver<PartialVMError> + TableResolver>;
+
+    /// The root of the state trie of the blockchain state version corresponding with block
+    /// `height`, `None` if it is not retained.
+    fn state_root_at(&self, height: BlockHeight) -> Option<B256>;
+
+    /// Checks if the blockchain state version corresponding with block `height` has been discarded
+    /// according to the [`StateRetention`] mode.
+    fn is_pruned(&self, height: BlockHeight) -> bool;
+
+    /// The [`StateRetention`] mode the blockchain state of past blocks is kept according to.
+    fn retention(&self) -> StateRetention;
+}
+
+/// Determines for how long the blockchain state of past blocks remains available for queries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StateRetention {
+    /// Retains the state of every block indefinitely.
+    #[default]
+    Archive,
+    /// Retains only the state of the most recent `blocks` blocks, including the latest one.
+    Pruned { blocks: u64 },
+}
+
+/// The error returned when querying the blockchain state at a block height that is not available.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum StateUnavailable {
+    #[error("Block not found")]
+    BlockNotFound,
+    #[error("State unavailable (pruned) at block height {0}")]
+    Pruned(BlockHeight),
+    #[error("Failed to read state at block height {0}")]
+    ReadFailed(BlockHeight),
+}
+
+/// The error returned when an account proof cannot be generated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum ProofError {
+    #[error(transparent)]
+    StateUnavailable(#[from] StateUnavailable),
+    #[error("Too many storage slots: {requested} requested, at most {max} allowed")]
+    TooManyStorageSlots { requested: usize, max: usize },
+}
+
+/// The maximum number of values kept in the [`StateCache`].
+const STATE_CACHE_CAPACITY: usize = 100_000;
+
+/// Memoizes the values read from the trie at the state root of the latest block, so that repeated
+/// queries at the latest height do not walk the trie again.
+///
+/// Reads at any other root bypass the cache.
+#[derive(Debug)]
+struct StateCache {
+    entries: Mutex<StateCacheEntries>,
+}
+
+#[derive(Debug)]
+struct StateCacheEntries {
+    root: B256,
+    values: HashMap<TreeKey, Option<Bytes>>,
+}
+
+impl StateCache {
+    fn new(root: B256) -> Self {
+        Self {
+            entries: Mutex::new(StateCacheEntries {
+                root,
+                values: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Discards all values and starts caching the values at `root`.
+    fn reset(&self, root: B256) {
+        let mut entries = self.lock();
+        entries.root = root;
+        entries.values.clear();
+    }
+
+    /// Returns the value of `key` at `root`, calling `read` if it is not cached.
+    fn get_or_read(
+        &self,
+        root: B256,
+        key: TreeKey,
+        read: impl FnOnce() -> Result<Option<Bytes>, PartialVMError>,
+    ) -> Result<Option<Bytes>, PartialVMError> {
+        {
+            let entries = self.lock();
+            if entries.root != root {
+                drop(entries);
+                return read();
+            }
+            if let Some(value) = entries.values.get(&key) {
+                return Ok(value.clone());
+            }
+        }
+
+        // The lock is not held while reading so that concurrent queries are not serialized
+        let value = read()?;
+        let mut entries = self.lock();
+        if entries.root == root && entries.values.len() < STATE_CACHE_CAPACITY {
+            entries.values.insert(key, value.clone());
+        }
+
+        Ok(value)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, StateCacheEntries> {
+        self.entries
+            .lock()
+            .expect("State cache lock should not be poisoned")
+    }
+}
+
+/// How many times failed database reads are attempted again before the error is returned, so that
+/// a transient storage error does not fail the query.
+///
+/// Reads run within the state actor task, so they are retried right away instead of blocking it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadRetry {
+    pub retries: u32,
+}
+
+impl Default for ReadRetry {
+    fn default() -> Self {
+        Self { retries: 3 }
+    }
+}
+
+impl ReadRetry {
+    /// Runs `read` until it succeeds or the retries are exhausted, returning the last error.
+    ///
+    /// Only errors of the database are retried, a trie that is malformed or missing the root fails
+    /// on the first attempt.
+    pub fn run<T>(&self, mut read: impl FnMut() -> Result<T, TrieError>) -> Result<T, TrieError> {
+        let mut retries = self.retries;
+        loop {
+            match read() {
+                Err(e @ TrieError::DB(_)) if retries > 0 => {
+                    println!("WARN: Database read failed, retrying: {e:?}");
+                    retries -= 1;
+                }
+                result => return result,
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct StateMemory {
-    state_roots: Vec<B256>,
+    /// State roots starting from block `first_height`.
+    state_roots: VecDeque<B256>,
+    first_height: BlockHeight,
+    retention: StateRetention,
+    cache: StateCache,
+    read_retry: ReadRetry,
 }
 
 impl StateMemory {
     /// Creates state memory with `genesis_changes` on `version` 0 tagged as block `height` 0.
-    pub fn from_genesis(genesis_state_root: B256) -> Self {
+    pub fn new(genesis_state_root: B256, retention: StateRetention) -> Self {
         Self {
-            state_roots: vec![genesis_state_root],
+            state_roots: VecDeque::from([genesis_state_root]),
+            first_height: 0,
+            retention,
+            cache: StateCache::new(genesis_state_root),
+            read_retry: ReadRetry::default(),
         }
     }
 
+    /// Retries failed reads of historic state according to `read_retry`.
+    pub fn with_read_retry(self, read_retry: ReadRetry) -> Self {
+        Self { read_retry, ..self }
+    }
+
+    /// Creates state memory in [`StateRetention::Archive`] mode.
+    pub fn from_genesis(genesis_state_root: B256) -> Self {
+        Self::new(genesis_state_root, StateRetention::Archive)
+    }
+
     fn push_state_root(&mut self, root: B256) {
-        self.state_roots.push(root);
+        self.state_roots.push_back(root);
+        self.cache.reset(root);
+        self.prune();
+    }
+
+    /// Drops the state roots of blocks that are no longer retained.
+    ///
+    /// Only the roots are bounded, the trie nodes stay in the database as they are shared with the
+    /// roots that are retained.
+    fn prune(&mut self) {
+        let StateRetention::Pruned { blocks } = self.retention else {
+            return;
+        };
+        while self.state_roots.len() as u64 > blocks.max(1) {
+            self.state_roots.pop_front();
+            self.first_height += 1;
+        }
+    }
+
+    fn is_pruned(&self, height: BlockHeight) -> bool {
+        height < self.first_height
     }
 
+    /// Roots are stored contiguously by height, so finding the root of any retained block is a
+    /// constant time index instead of a scan over the blocks in between.
     fn get_root_by_height(&self, height: BlockHeight) -> Option<B256> {
-        self.state_roots.get(height as usize).copied()
+        let index = height.checked_sub(self.first_height)?;
+        self.state_roots.get(index as usize).copied()
     }
 
-    fn resolver<'a>(
-        &'a self,
-        db: Arc<impl DB + 'a>,
+    fn resolver<D: DB>(
+        &self,
+        db: Arc<D>,
         height: BlockHeight,
-    ) -> Option<impl MoveResolver<PartialVMError> + TableResolver + 'a> {
-        Some(HistoricResolver::new(db, self.get_root_by_height(height)?))
+    ) -> Result<CachedResolver<'_, D>, StateUnavailable> {
+        let root = self
+            .get_root_by_height(height)
+            .ok_or(StateUnavailable::BlockNotFound)?;
+        let resolver = HistoricResolver::new(db, root).with_read_retry(self.read_retry);
+
+        Ok(CachedResolver::new(resolver, &self.cache))
     }
 }
 
@@ -127,6 +356,16 @@ impl InMemoryStateQueries {
         Self::new(StateMemory::from_genesis(genesis_state_root))
     }
 
+    /// Creates state memory with `genesis_changes` that keeps past state according to `retention`.
+    pub fn with_retention(genesis_state_root: B256, retention: StateRetention) -> Self {
+        Self::new(StateMemory::new(genesis_state_root, retention))
+    }
+
+    /// Retries failed reads of historic state according to `read_retry`.
+    pub fn with_read_retry(self, read_retry: ReadRetry) -> Self {
+        Self::new(self.storage.with_read_retry(read_retry))
+    }
+
     /// Marks current state root with current block height.
     ///
     /// The internal block height number is incremented by this operation.
@@ -143,10 +382,12 @@ impl StateQueries for InMemoryStateQueries {
         db: Arc<impl DB>,
         account: AccountAddress,
         height: BlockHeight,
-    ) -> Option<Balance> {
+    ) -> Result<Balance, StateUnavailable> {
         let resolver = self.storage.resolver(db, height)?;
+        let balance = try_quick_get_eth_balance(&account, &resolver);
+        resolver.check_reads(height)?;
 
-        Some(quick_get_eth_balance(&account, &resolver))
+        balance.map_err(|_| StateUnavailable::ReadFailed(height))
     }
 
     fn nonce_at(
@@ -154,10 +395,12 @@ impl StateQueries for InMemoryStateQueries {
         db: Arc<impl DB>,
         account: AccountAddress,
         height: BlockHeight,
-    ) -> Option<Nonce> {
+    ) -> Result<Nonce, StateUnavailable> {
         let resolver = self.storage.resolver(db, height)?;
+        let nonce = quick_get_nonce(&account, &resolver);
+        resolver.check_reads(height)?;
 
-        Some(quick_get_nonce(&account, &resolver))
+        Ok(nonce)
     }
 
     fn get_proof(
@@ -175,13 +418,17 @@ impl StateQueries for InMemoryStateQueries {
         }
 
         // All L2 contract account data is part of the EVM state
-        let resolver = self.storage.resolver(db.clone(), height)?;
+        let resolver = self.storage.resolver(db.clone(), height).ok()?;
         let evm_db = evm_native::ResolverBackedDB::new(&resolver);
         let account_info = evm_db.basic_ref(address).ok()??;
 
         let account_struct = evm_native::type_utils::account_info_struct_tag(&address);
         let root = self.storage.get_root_by_height(height)?;
-        let mut tree = EthTrie::from(db, root).expect(IN_MEMORY_EXPECT_MSG);
+        let mut tree = self
+            .storage
+            .read_retry
+            .run(|| EthTrie::from(db.clone(), root))
+            .ok()?;
         let account_proof = get_proof(&mut tree, &EVM_NATIVE_ADDRESS, &account_struct)?;
 
         let mut storage_proof = Vec::new();
@@ -207,6 +454,80 @@ impl StateQueries for InMemoryStateQueries {
             storage_proof,
         })
     }
+
+    fn account_at(
+        &self,
+        db: Arc<impl DB>,
+        account: AccountAddress,
+        height: BlockHeight,
+    ) -> Result<AccountResponse, StateUnavailable> {
+        let address = account.to_eth_address();
+        let resolver = self.storage.resolver(db, height)?;
+        let storage_root = self
+            .storage
+            .get_root_by_height(height)
+            .ok_or(StateUnavailable::BlockNotFound)?;
+
+        // L2 contract account data is part of the EVM state
+        if (L2_LOWEST_ADDRESS..=L2_HIGHEST_ADDRESS).contains(&address) {
+            let evm_db = evm_native::ResolverBackedDB::new(&resolver);
+            let account_info = evm_db.basic_ref(address).ok().flatten();
+            resolver.check_reads(height)?;
+            if let Some(account_info) = account_info {
+                return Ok(AccountResponse {
+                    balance: account_info.balance,
+                    nonce: U64::from(account_info.nonce),
+                    code_hash: account_info.code_hash,
+                    storage_root,
+                });
+            }
+        }
+
+        let balance = try_quick_get_eth_balance(&account, &resolver);
+        let nonce = quick_get_nonce(&account, &resolver);
+        resolver.check_reads(height)?;
+
+        Ok(AccountResponse {
+            balance: balance.map_err(|_| StateUnavailable::ReadFailed(height))?,
+            nonce: U64::from(nonce),
+            code_hash: KECCAK_EMPTY,
+            storage_root,
+        })
+    }
+
+    fn resource_exists_at(
+        &self,
+        db: Arc<impl DB>,
+        account: AccountAddress,
+        resource: &StructTag,
+        height: BlockHeight,
+    ) -> Result<bool, StateUnavailable> {
+        let resolver = self.storage.resolver(db, height)?;
+        let (value, _) = resolver
+            .get_resource_bytes_with_metadata_and_layout(&account, resource, &[], None)
+            .map_err(|_| StateUnavailable::ReadFailed(height))?;
+        Ok(value.is_some())
+    }
+
+    fn resolver_at(
+        &self,
+        db: Arc<impl DB>,
+        height: BlockHeight,
+    ) -> Option<impl MoveResolver<PartialVMError> + TableResolver> {
+        self.storage.resolver(db, height).ok()
+    }
+
+    fn state_root_at(&self, height: BlockHeight) -> Option<B256> {
+        self.storage.get_root_by_height(height)
+    }
+
+    fn is_pruned(&self, height: BlockHeight) -> bool {
+        self.storage.is_pruned(height)
+    }
+
+    fn retention(&self) -> StateRetention {
+        self.storage.retention
+    }
 }
 
 fn get_proof<R>(
@@ -227,28 +548,52 @@ where
 pub struct HistoricResolver<D> {
     db: Arc<D>,
     root: B256,
+    retry: ReadRetry,
+    /// Set once a read fails, so that callers recovering from the error still notice it.
+    read_failed: AtomicBool,
 }
 
 impl<D> HistoricResolver<D> {
     pub fn new(db: Arc<D>, root: B256) -> Self {
-        Self { db, root }
+        Self {
+            db,
+            root,
+            retry: ReadRetry::default(),
+            read_failed: AtomicBool::new(false),
+        }
+    }
+
+    pub fn with_read_retry(self, retry: ReadRetry) -> Self {
+        Self { retry, ..self }
+    }
+}
+
+impl<D: DB> HistoricResolver<D> {
+    fn read(&self, key_hash: &[u8]) -> Result<Option<Bytes>, PartialVMError> {
+        self.retry
+            .run(|| EthTrie::from(self.db.clone(), self.root)?.get(key_hash))
+            .map_err(trie_err)
+            .and_then(deserialize_state_value)
+            .inspect_err(|_| self.read_failed.store(true, Ordering::Relaxed))
     }
 }
 
 impl<D: DB> ModuleResolver for HistoricResolver<D> {
     type Error = PartialVMError;
 
-    fn get_module_metadata(&self, _module_id: &ModuleId) -> Vec<Metadata> {
-        Vec::new()
+    fn get_module_metadata(&self, module_id: &ModuleId) -> Vec<Metadata> {
+        let Ok(Some(bytes)) = self.get_module(module_id) else {
+            return Vec::new();
+        };
+        CompiledModule::deserialize(&bytes)
+            .map(|module| module.metadata)
+            .unwrap_or_default()
     }
 
     fn get_module(&self, id: &ModuleId) -> Result<Option<Bytes>, Self::Error> {
-        let tree = EthTrie::from(self.db.clone(), self.root).expect(IN_MEMORY_EXPECT_MSG);
         let state_key = StateKey::module(id.address(), id.name());
         let key_hash = state_key.key_hash();
-        let value = tree.get(key_hash.0.as_slice()).map_err(trie_err)?;
-
-        Ok(deserialize_state_value(value))
+        self.read(key_hash.0.as_slice())
     }
 }
 
@@ -262,19 +607,18 @@ impl<D: DB> ResourceResolver for HistoricResolver<D> {
         _metadata: &[Metadata],
         _layout: Option<&MoveTypeLayout>,
     ) -> Result<(Option<Bytes>, usize), Self::Error> {
-        let tree = EthTrie::from(self.db.clone(), self.root).expect(IN_MEMORY_EXPECT_MSG);
         let state_key = StateKey::resource(address, struct_tag)
             .inspect_err(|e| print!("{e:?}"))
             .map_err(|_| PartialVMError::new(StatusCode::DATA_FORMAT_ERROR))?;
         let key_hash = state_key.key_hash();
-        let value = tree.get(key_hash.0.as_slice()).map_err(trie_err)?;
-        let value = deserialize_state_value(value);
+        let value = self.read(key_hash.0.as_slice())?;
         let len = value.as_ref().map(|v| v.len()).unwrap_or_default();
 
         Ok((value, len))
     }
 }
 
+// Table entries are not part of the state trie, so they cannot be read at past roots
 impl<D: DB> TableResolver for HistoricResolver<D> {
     fn resolve_table_entry_bytes_with_layout(
         &self,
@@ -282,14 +626,173 @@ impl<D: DB> TableResolver for HistoricResolver<D> {
         _key: &[u8],
         _maybe_layout: Option<&MoveTypeLayout>,
     ) -> Result<Option<Bytes>, PartialVMError> {
-        unimplemented!()
+        Err(PartialVMError::new(StatusCode::STORAGE_ERROR)
+            .with_message("Table entries are not available in historic state".into()))
     }
 }
 
-fn deserialize_state_value(bytes: Option<Vec<u8>>) -> Option<Bytes> {
-    let value: StateValue = bcs::from_bytes(&bytes?).expect("Bytes must be serialized StateValue");
+/// A [`HistoricResolver`] that reads through the [`StateCache`].
+struct CachedResolver<'a, D> {
+    inner: HistoricResolver<D>,
+    cache: &'a StateCache,
+}
+
+impl<'a, D> CachedResolver<'a, D> {
+    fn new(inner: HistoricResolver<D>, cache: &'a StateCache) -> Self {
+        Self { inner, cache }
+    }
+
+    /// Fails if any read of the state at `height` failed, even if the caller went on without it.
+    fn check_reads(&self, height: BlockHeight) -> Result<(), StateUnavailable> {
+        if self.inner.read_failed.load(Ordering::Relaxed) {
+            return Err(StateUnavailable::ReadFailed(height));
+        }
+        Ok(())
+    }
+}
+
+impl<D: DB> ModuleResolver for CachedResolver<'_, D> {
+    type Error = PartialVMError;
+
+    fn get_module_metadata(&self, module_id: &ModuleId) -> Vec<Metadata> {
+        self.inner.get_module_metadata(module_id)
+    }
+
+    fn get_module(&self, id: &ModuleId) -> Result<Option<Bytes>, Self::Error> {
+        let state_key = StateKey::module(id.address(), id.name());
+        self.cache
+            .get_or_read(self.inner.root, state_key, || self.inner.get_module(id))
+    }
+}
+
+impl<D: DB> ResourceResolver for CachedResolver<'_, D> {
+    type Error = PartialVMError;
+
+    fn get_resource_bytes_with_metadata_and_layout(
+        &self,
+        address: &AccountAddress,
+        struct_tag: &StructTag,
+        metadata: &[Metadata],
+        layout: Option<&MoveTypeLayout>,
+    ) -> Result<(Option<Bytes>, usize), Self::Error> {
+        let Ok(state_key) = StateKey::resource(address, struct_tag) else {
+            return self.inner.get_resource_bytes_with_metadata_and_layout(
+                address, struct_tag, metadata, layout,
+            );
+        };
+        let value = self.cache.get_or_read(self.inner.root, state_key, || {
+            self.inner
+                .get_resource_bytes_with_metadata_and_layout(address, struct_tag, metadata, layout)
+                .map(|(value, _)| value)
+        })?;
+        let len = value.as_ref().map(|v| v.len()).unwrap_or_default();
+
+        Ok((value, len))
+    }
+}
+
+impl<D: DB> TableResolver for CachedResolver<'_, D> {
+    fn resolve_table_entry_bytes_with_layout(
+        &self,
+        handle: &TableHandle,
+        key: &[u8],
+        maybe_layout: Option<&MoveTypeLayout>,
+    ) -> Result<Option<Bytes>, PartialVMError> {
+        self.inner
+            .resolve_table_entry_bytes_with_layout(handle, key, maybe_layout)
+    }
+}
+
+/// A [`MoveResolver`] that reads `changes` on top of the `base` state, so that transactions can be
+/// executed after others without modifying the state.
+pub struct PendingResolver<'a, R> {
+    base: &'a R,
+    changes: ChangeSet,
+}
+
+impl<'a, R> PendingResolver<'a, R> {
+    pub fn new(base: &'a R) -> Self {
+        Self {
+            base,
+            changes: ChangeSet::new(),
+        }
+    }
+
+    pub fn apply(&mut self, changes: ChangeSet) -> Result<(), PartialVMError> {
+        self.changes.squash(changes)
+    }
+}
+
+impl<R: MoveResolver<PartialVMError>> ModuleResolver for PendingResolver<'_, R> {
+    type Error = PartialVMError;
+
+    fn get_module_metadata(&self, module_id: &ModuleId) -> Vec<Metadata> {
+        self.base.get_module_metadata(module_id)
+    }
+
+    fn get_module(&self, id: &ModuleId) -> Result<Option<Bytes>, Self::Error> {
+        let change = self
+            .changes
+            .accounts()
+            .get(id.address())
+            .and_then(|account| account.modules().get(id.name()));
+        match change {
+            Some(op) => Ok(op.clone().ok()),
+            None => self.base.get_module(id),
+        }
+    }
+}
+
+impl<R: MoveResolver<PartialVMError>> ResourceResolver for PendingResolver<'_, R> {
+    type Error = PartialVMError;
+
+    fn get_resource_bytes_with_metadata_and_layout(
+        &self,
+        address: &AccountAddress,
+        struct_tag: &StructTag,
+        metadata: &[Metadata],
+        layout: Option<&MoveTypeLayout>,
+    ) -> Result<(Option<Bytes>, usize), Self::Error> {
+        let change = self
+            .changes
+            .accounts()
+            .get(address)
+            .and_then(|account| account.resources().get(struct_tag));
+        let Some(op) = change else {
+            return self.base.get_resource_bytes_with_metadata_and_layout(
+                address, struct_tag, metadata, layout,
+            );
+        };
+        let value = op.clone().ok();
+        let len = value.as_ref().map(|v| v.len()).unwrap_or_default();
+
+        Ok((value, len))
+    }
+}
+
+// Table changes are not part of `ChangeSet`, so tables are read from the base state
+impl<R: TableResolver> TableResolver for PendingResolver<'_, R> {
+    fn resolve_table_entry_bytes_with_layout(
+        &self,
+        handle: &TableHandle,
+        key: &[u8],
+        maybe_layout: Option<&MoveTypeLayout>,
+    ) -> Result<Option<Bytes>, PartialVMError> {
+        self.base
+            .resolve_table_entry_bytes_with_layout(handle, key, maybe_layout)
+    }
+}
+
+fn deserialize_state_value(bytes: Option<Vec<u8>>) -> Result<Option<Bytes>, PartialVMError> {
+    let Some(bytes) = bytes else {
+        return Ok(None);
+    };
+    let value: StateValue = bcs::from_bytes(&bytes).map_err(|e| {
+        PartialVMError::new(StatusCode::STORAGE_ERROR)
+            .with_message(format!("Invalid state value in storage: {e}"))
+    })?;
     let (_, inner) = value.unpack();
-    Some(inner)
+    Ok(Some(inner))
 }
 
 fn trie_err(e: TrieError) -> PartialVMError {
@@ -308,10 +811,12 @@ mod tests {
             types::session_id::SessionId,
         },
         alloy::hex,
-        move_core_types::effects::ChangeSet,
+        eth_trie::MemoryDB,
+        move_core_types::{effects::ChangeSet, identifier::Identifier},
         move_table_extension::TableChangeSet,
         move_vm_runtime::module_traversal::{TraversalContext, TraversalStorage},
         move_vm_types::gas::UnmeteredGasMeter,
+        std::sync::atomic::{AtomicUsize, Ordering},
     };
 
     struct StateSpy(InMemoryState, ChangeSet);
@@ -377,7 +882,7 @@ mod tests {
         let state = InMemoryState::new();
         let mut state = StateSpy(state, ChangeSet::new());
 
-        let genesis_config = GenesisConfig::default();
+        let genesis_config = GenesisConfig::dev();
         init_and_apply(&genesis_config, &mut state);
 
         let mut state = state.0;
@@ -403,7 +908,7 @@ mod tests {
         let state = InMemoryState::new();
         let mut state = StateSpy(state, ChangeSet::new());
 
-        let genesis_config = GenesisConfig::default();
+        let genesis_config = GenesisConfig::dev();
         init_and_apply(&genesis_config, &mut state);
 
         let mut state = state.0;
@@ -428,12 +933,46 @@ mod tests {
         assert_eq!(actual_balance, expected_balance);
     }
 
+    #[test]
+    fn test_query_fetches_balance_far_back_in_history() {
+        let state = InMemoryState::new();
+        let mut state = StateSpy(state, ChangeSet::new());
+
+        let genesis_config = GenesisConfig::dev();
+        init_and_apply(&genesis_config, &mut state);
+
+        let mut state = state.0;
+
+        let addr = AccountAddress::TWO;
+
+        let mut storage = StateMemory::from_genesis(genesis_config.initial_state_root);
+
+        mint_one_eth(&mut state, addr);
+        storage.push_state_root(state.state_root());
+        for _ in 0..256 {
+            mint_one_eth(&mut state, addr);
+            storage.push_state_root(state.state_root());
+        }
+
+        let query = InMemoryStateQueries::new(storage);
+
+        let actual_balance = query
+            .balance_at(state.db(), addr, 1)
+            .expect("Block height should exist");
+        assert_eq!(actual_balance, U256::from(1u64));
+
+        let actual_balance = query
+            .balance_at(state.db(), addr, 257)
+            .expect("Block height should exist");
+        assert_eq!(actual_balance, U256::from(257u64));
+    }
+
     #[test]
     fn test_query_fetches_latest_and_previous_balance() {
         let state = InMemoryState::new();
         let mut state = StateSpy(state, ChangeSet::new());
 
-        let genesis_config = GenesisConfig::default();
+        let genesis_config = GenesisConfig::dev();
         init_and_apply(&genesis_config, &mut state);
 
         let mut state = state.0;
@@ -470,7 +1009,7 @@ mod tests {
         let state = InMemoryState::new();
         let mut state = StateSpy(state, ChangeSet::new());
 
-        let genesis_config = GenesisConfig::default();
+        let genesis_config = GenesisConfig::dev();
         init_and_apply(&genesis_config, &mut state);
 
         let state = state.0;
@@ -491,6 +1030,156 @@ mod tests {
         assert_eq!(actual_balance, expected_balance);
     }
 
+    #[test]
+    fn test_pruned_query_only_retains_recent_state() {
+        let state = InMemoryState::new();
+        let mut state = StateSpy(state, ChangeSet::new());
+
+        let genesis_config = GenesisConfig::dev();
+        init_and_apply(&genesis_config, &mut state);
+
+        let mut state = state.0;
+        let addr = AccountAddress::TWO;
+
+        let mut query = InMemoryStateQueries::with_retention(
+            genesis_config.initial_state_root,
+            StateRetention::Pruned { blocks: 2 },
+        );
+
+        mint_one_eth(&mut state, addr);
+        query.push_state_root(state.state_root());
+        mint_one_eth(&mut state, addr);
+        query.push_state_root(state.state_root());
+
+        assert!(query.is_pruned(0));
+        assert!(!query.is_pruned(1));
+        assert!(!query.is_pruned(2));
+        assert_eq!(
+            query.balance_at(state.db(), addr, 0),
+            Err(StateUnavailable::BlockNotFound)
+        );
+
+        let actual_balance = query
+            .balance_at(state.db(), addr, 1)
+            .expect("Block height should exist");
+        assert_eq!(actual_balance, U256::from(1u64));
+
+        let actual_balance = query
+            .balance_at(state.db(), addr, 2)
+            .expect("Block height should exist");
+        assert_eq!(actual_balance, U256::from(2u64));
+    }
+
+    #[test]
+    fn test_archive_query_retains_all_state() {
+        let state = InMemoryState::new();
+        let mut state = StateSpy(state, ChangeSet::new());
+
+        let genesis_config = GenesisConfig::dev();
+        init_and_apply(&genesis_config, &mut state);
+
+        let mut state = state.0;
+        let addr = AccountAddress::TWO;
+
+        let mut query = InMemoryStateQueries::with_retention(
+            genesis_config.initial_state_root,
+            StateRetention::Archive,
+        );
+
+        for _ in 0..3 {
+            mint_one_eth(&mut state, addr);
+            query.push_state_root(state.state_root());
+        }
+
+        for height in 0..=3 {
+            assert!(!query.is_pruned(height));
+
+            let actual_balance = query
+                .balance_at(state.db(), addr, height)
+                .expect("Block height should exist");
+            assert_eq!(actual_balance, U256::from(height));
+        }
+    }
+
+    /// A [`DB`] that counts the trie nodes read from it.
+    struct CountingDB<D> {
+        inner: Arc<D>,
+        reads: AtomicUsize,
+    }
+
+    impl<D> CountingDB<D> {
+        fn new(inner: Arc<D>) -> Self {
+            Self {
+                inner,
+                reads: AtomicUsize::new(0),
+            }
+        }
+
+        fn take_reads(&self) -> usize {
+            self.reads.swap(0, Ordering::Relaxed)
+        }
+    }
+
+    impl<D: DB> DB for CountingDB<D> {
+        type Error = D::Error;
+
+        fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+            self.reads.fetch_add(1, Ordering::Relaxed);
+            self.inner.get(key)
+        }
+
+        fn insert(&self, key: &[u8], value: Vec<u8>) -> Result<(), Self::Error> {
+            self.inner.insert(key, value)
+        }
+
+        fn remove(&self, key: &[u8]) -> Result<(), Self::Error> {
+            self.inner.remove(key)
+        }
+
+        fn flush(&self) -> Result<(), Self::Error> {
+            self.inner.flush()
+        }
+    }
+
+    #[test]
+    fn test_repeated_latest_queries_are_served_from_cache() {
+        let state = InMemoryState::new();
+        let mut state = StateSpy(state, ChangeSet::new());
+
+        let genesis_config = GenesisConfig::dev();
+        init_and_apply(&genesis_config, &mut state);
+
+        let mut state = state.0;
+        let addr = AccountAddress::TWO;
+        let db = Arc::new(CountingDB::new(state.db()));
+
+        let mut query = InMemoryStateQueries::from_genesis(genesis_config.initial_state_root);
+        mint_one_eth(&mut state, addr);
+        query.push_state_root(state.state_root());
+
+        // The first query at the latest height reads the trie, repeated ones do not
+        assert_eq!(query.balance_at(db.clone(), addr, 1), Ok(U256::from(1u64)));
+        assert!(db.take_reads() > 0);
+        for _ in 0..3 {
+            assert_eq!(query.balance_at(db.clone(), addr, 1), Ok(U256::from(1u64)));
+            assert_eq!(db.take_reads(), 0);
+        }
+
+        // Queries at older heights bypass the cache
+        for _ in 0..2 {
+            assert_eq!(query.balance_at(db.clone(), addr, 0), Ok(U256::ZERO));
+            assert!(db.take_reads() > 0);
+        }
+
+        // Sealing a new block invalidates the cache
+        mint_one_eth(&mut state, addr);
+        query.push_state_root(state.state_root());
+        assert_eq!(query.balance_at(db.clone(), addr, 2), Ok(U256::from(2u64)));
+        assert!(db.take_reads() > 0);
+        assert_eq!(query.balance_at(db.clone(), addr, 2), Ok(U256::from(2u64)));
+        assert_eq!(db.take_reads(), 0);
+    }
+
     fn inc_one_nonce(
         old_nonce: u64,
         state: &mut impl State<Err = PartialVMError>,
@@ -523,7 +1212,7 @@ mod tests {
         let state = InMemoryState::new();
         let mut state = StateSpy(state, ChangeSet::new());
 
-        let genesis_config = GenesisConfig::default();
+        let genesis_config = GenesisConfig::dev();
         init_and_apply(&genesis_config, &mut state);
 
         let mut state = state.0;
@@ -549,7 +1238,7 @@ mod tests {
         let state = InMemoryState::new();
         let mut state = StateSpy(state, ChangeSet::new());
 
-        let genesis_config = GenesisConfig::default();
+        let genesis_config = GenesisConfig::dev();
         init_and_apply(&genesis_config, &mut state);
 
         let mut state = state.0;
@@ -579,7 +1268,7 @@ mod tests {
         let state = InMemoryState::new();
         let mut state = StateSpy(state, ChangeSet::new());
 
-        let genesis_config = GenesisConfig::default();
+        let genesis_config = GenesisConfig::dev();
         init_and_apply(&genesis_config, &mut state);
 
         let mut state = state.0;
@@ -616,7 +1305,7 @@ mod tests {
         let state = InMemoryState::new();
         let mut state = StateSpy(state, ChangeSet::new());
 
-        let genesis_config = GenesisConfig::default();
+        let genesis_config = GenesisConfig::dev();
         init_and_apply(&genesis_config, &mut state);
 
         let state = state.0;
@@ -636,4 +1325,129 @@ mod tests {
 
         assert_eq!(actual_nonce, expected_nonce);
     }
+
+    #[derive(Debug, Error)]
+    #[error("Transient read failure")]
+    struct TransientReadError;
+
+    /// A [`MemoryDB`] failing the next `failing_reads` reads.
+    struct FlakyDb {
+        inner: MemoryDB,
+        failing_reads: AtomicUsize,
+    }
+
+    impl FlakyDb {
+        fn new() -> Self {
+            Self {
+                inner: MemoryDB::new(false),
+                failing_reads: AtomicUsize::new(0),
+            }
+        }
+
+        fn fail_next_reads(&self, count: usize) {
+            self.failing_reads.store(count, Ordering::SeqCst);
+        }
+    }
+
+    impl DB for FlakyDb {
+        type Error = TransientReadError;
+
+        fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+            let failing = self
+                .failing_reads
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok();
+            if failing {
+                return Err(TransientReadError);
+            }
+            self.inner.get(key).map_err(|_| TransientReadError)
+        }
+
+        fn insert(&self, key: &[u8], value: Vec<u8>) -> Result<(), Self::Error> {
+            self.inner
+                .insert(key, value)
+                .map_err(|_| TransientReadError)
+        }
+
+        fn remove(&self, key: &[u8]) -> Result<(), Self::Error> {
+            self.inner.remove(key).map_err(|_| TransientReadError)
+        }
+
+        fn flush(&self) -> Result<(), Self::Error> {
+            self.inner.flush().map_err(|_| TransientReadError)
+        }
+    }
+
+    #[test]
+    fn test_historic_resolver_retries_transient_read_failures() {
+        let db = Arc::new(FlakyDb::new());
+        let struct_tag = StructTag {
+            address: AccountAddress::ONE,
+            module: Identifier::new("test").unwrap(),
+            name: Identifier::new("Resource").unwrap(),
+            type_args: Vec::new(),
+        };
+        let state_key = StateKey::resource(&AccountAddress::TWO, &struct_tag).unwrap();
+        let value = StateValue::new_legacy(Bytes::from_static(b"value"));
+        let mut tree = EthTrie::new(db.clone());
+        tree.insert(
+            state_key.key_hash().0.as_slice(),
+            &bcs::to_bytes(&value).unwrap(),
+        )
+        .unwrap();
+        let root = tree.root_hash().unwrap();
+
+        let retry = ReadRetry { retries: 2 };
+        let resolver = HistoricResolver::new(db.clone(), root).with_read_retry(retry);
+        let read = || {
+            resolver.get_resource_bytes_with_metadata_and_layout(
+                &AccountAddress::TWO,
+                &struct_tag,
+                &[],
+                None,
+            )
+        };
+
+        // Failures within the retries are recovered from
+        db.fail_next_reads(2);
+        let (actual, _) = read().unwrap();
+        assert_eq!(actual, Some(Bytes::from_static(b"value")));
+
+        // Persistent failures are returned as an error instead of panicking
+        db.fail_next_reads(3);
+        let err = read().unwrap_err();
+        assert_eq!(err.major_status(), StatusCode::STORAGE_ERROR);
+    }
+
+    #[test]
+    fn test_historic_resolver_does_not_retry_missing_root() {
+        let db = Arc::new(CountingDB::new(Arc::new(MemoryDB::new(false))));
+        let resolver = HistoricResolver::new(db.clone(), B256::repeat_byte(1));
+        let module_id = ModuleId::new(AccountAddress::ONE, Identifier::new("test").unwrap());
+
+        let err = resolver.get_module(&module_id).unwrap_err();
+
+        assert_eq!(err.major_status(), StatusCode::STORAGE_ERROR);
+        assert_eq!(db.take_reads(), 1);
+    }
+
+    #[test]
+    fn test_query_fails_instead_of_panicking_when_state_cannot_be_read() {
+        let db = Arc::new(MemoryDB::new(false));
+        let query = InMemoryStateQueries::from_genesis(B256::repeat_byte(1));
+        let addr = AccountAddress::TWO;
+
+        assert_eq!(
+            query.balance_at(db.clone(), addr, 0),
+            Err(StateUnavailable::ReadFailed(0))
+        );
+        assert_eq!(
+            query.nonce_at(db.clone(), addr, 0),
+            Err(StateUnavailable::ReadFailed(0))
+        );
+        assert_eq!(
+            query.balance_at(db, addr, 1),
+            Err(StateUnavailable::BlockNotFound)
+        );
+    }
 }
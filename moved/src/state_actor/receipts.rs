@@ -0,0 +1,107 @@
+use {
+    crate::{
+        move_execution::{BlockFees, LogsBloom},
+        primitives::{ToEthAddress, B256, U256, U64},
+        types::{
+            state::{ExecutionOutcome, TransactionWithReceipt},
+            transactions::{
+                ExtendedTxEnvelope, NormalizedExtendedTxEnvelope, TransactionExecutionOutcome,
+            },
+        },
+    },
+    alloy::{consensus::Receipt, primitives::Bloom, rlp::Encodable},
+    move_core_types::effects::ChangeSet,
+    op_alloy::rpc_types::L1BlockInfo,
+};
+
+/// Receipts and fees of the transactions of a block, collected in block order.
+#[derive(Debug, Default)]
+pub struct BlockReceipts {
+    pub receipts: Vec<TransactionWithReceipt>,
+    pub fees: BlockFees,
+    cumulative_gas_used: u128,
+    logs_bloom: Bloom,
+    log_offset: u64,
+}
+
+impl BlockReceipts {
+    /// Adds the receipt of `tx` executed with `outcome` on top of the previous transactions and
+    /// charged with `l1_cost`, returning the state changes of the transaction.
+    #[allow(clippy::too_many_arguments)]
+    pub fn push(
+        &mut self,
+        tx_hash: B256,
+        tx: ExtendedTxEnvelope,
+        normalized_tx: NormalizedExtendedTxEnvelope,
+        outcome: TransactionExecutionOutcome,
+        l1_cost: u64,
+        l1_block_info: Option<L1BlockInfo>,
+        base_fee: U256,
+    ) -> ChangeSet {
+        self.cumulative_gas_used = self
+            .cumulative_gas_used
+            .saturating_add(outcome.gas_used as u128);
+
+        let bloom = outcome.logs.iter().logs_bloom();
+        self.logs_bloom.accrue_bloom(&bloom);
+
+        let logs_offset = self.log_offset;
+        self.log_offset += outcome.logs.len() as u64;
+        let receipt = Receipt {
+            status: outcome.vm_outcome.is_ok().into(),
+            cumulative_gas_used: self.cumulative_gas_used,
+            logs: outcome.logs,
+        };
+        let receipt = tx.wrap_receipt(receipt, bloom);
+
+        let gas_used = U256::from(outcome.gas_used);
+        self.fees.tip = self
+            .fees
+            .tip
+            .saturating_add(gas_used.saturating_mul(normalized_tx.tip_per_gas(base_fee)));
+        // Deposited transactions don't pay for gas
+        if let NormalizedExtendedTxEnvelope::Canonical(_) = normalized_tx {
+            self.fees.base_fee = self
+                .fees
+                .base_fee
+                .saturating_add(gas_used.saturating_mul(base_fee.min(outcome.l2_price)));
+            self.fees.l1_fee = self.fees.l1_fee.saturating_add(U256::from(l1_cost));
+        }
+
+        self.receipts.push(TransactionWithReceipt {
+            tx_hash,
+            tx: tx.into(),
+            normalized_tx,
+            receipt,
+            l1_block_info,
+            gas_used: outcome.gas_used,
+            l2_gas_price: outcome.l2_price,
+            tx_index: self.receipts.len() as u64,
+            contract_address: outcome
+                .deployment
+                .map(|(address, _)| address.to_eth_address()),
+            logs_offset,
+            move_events: outcome.move_events,
+        });
+
+        outcome.changes
+    }
+
+    /// The outcome of executing the block transactions, ending with `state_root`.
+    pub fn outcome(&self, state_root: B256) -> ExecutionOutcome {
+        // Compute the receipts root by RLP-encoding each receipt to be a leaf of
+        // a merkle trie.
+        let receipts_root =
+            alloy_trie::root::ordered_trie_root_with_encoder(&self.receipts, |rx, buf| {
+                rx.receipt.encode(buf)
+            });
+
+        ExecutionOutcome {
+            state_root,
+            gas_used: U64::from(self.cumulative_gas_used),
+            receipts_root,
+            logs_bloom: self.logs_bloom.into(),
+            total_tip: self.fees.tip,
+        }
+    }
+}
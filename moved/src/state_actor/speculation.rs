@@ -0,0 +1,190 @@
+use {
+    super::queries::HistoricResolver,
+    crate::{
+        block::HeaderForExecution,
+        genesis::config::GenesisConfig,
+        move_execution::{execute_transaction, BaseTokenAccounts, CreateL2GasFee, L2GasFeeInput},
+        primitives::{B256, U256},
+        types::transactions::{NormalizedExtendedTxEnvelope, TransactionExecutionOutcome},
+    },
+    aptos_types::state_store::state_key::StateKey,
+    bytes::Bytes,
+    eth_trie::DB,
+    move_binary_format::errors::PartialVMError,
+    move_core_types::{
+        account_address::AccountAddress,
+        language_storage::{ModuleId, StructTag},
+        metadata::Metadata,
+        resolver::{ModuleResolver, MoveResolver, ResourceResolver},
+        value::MoveTypeLayout,
+        vm_status::StatusCode,
+    },
+    move_table_extension::{TableHandle, TableResolver},
+    std::{
+        cell::{Cell, RefCell},
+        collections::HashSet,
+        num::NonZeroUsize,
+        sync::Arc,
+        thread,
+    },
+};
+
+/// A transaction prepared for execution in a block.
+#[derive(Debug)]
+pub struct SpeculationInput {
+    pub tx_hash: B256,
+    pub tx: NormalizedExtendedTxEnvelope,
+    pub l1_cost: u64,
+    pub l2_gas_input: L2GasFeeInput,
+}
+
+/// The outcome of executing a transaction against the state at the start of the block.
+#[derive(Debug)]
+pub struct Speculation {
+    pub outcome: TransactionExecutionOutcome,
+    /// State keys read during the execution.
+    reads: HashSet<StateKey>,
+}
+
+impl Speculation {
+    /// Whether the outcome is the same when executed after the transactions that wrote `written`.
+    pub fn holds_after(&self, written: &HashSet<StateKey>) -> bool {
+        self.reads.is_disjoint(written)
+    }
+}
+
+/// Executes `transactions` concurrently against the state with `state_root`.
+///
+/// The result matches `transactions` by index. It is `None` where there is no input, the
+/// execution fails or reads state that is not tracked, these transactions have to be executed
+/// again in order.
+pub fn speculate(
+    transactions: &[Option<SpeculationInput>],
+    db: Arc<impl DB>,
+    state_root: B256,
+    genesis_config: &GenesisConfig,
+    l2_fee: &(impl CreateL2GasFee + Sync),
+    base_token: &(impl BaseTokenAccounts + Sync),
+    block_header: &HeaderForExecution,
+) -> Vec<Option<Speculation>> {
+    let workers = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    let chunk_size = transactions.len().div_ceil(workers).max(1);
+
+    thread::scope(|scope| {
+        let handles = transactions
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let db = db.clone();
+                scope.spawn(move || {
+                    let resolver = HistoricResolver::new(db, state_root);
+                    let l2_fee = l2_fee.with_gas_fee_multiplier(U256::from(1));
+                    chunk
+                        .iter()
+                        .map(|input| {
+                            let input = input.as_ref()?;
+                            let tracked = TrackedResolver::new(&resolver);
+                            let outcome = execute_transaction(
+                                &input.tx,
+                                &input.tx_hash,
+                                &tracked,
+                                genesis_config,
+                                input.l1_cost,
+                                l2_fee.clone(),
+                                input.l2_gas_input.clone(),
+                                base_token,
+                                block_header.clone(),
+                            )
+                            .ok()?;
+                            let reads = tracked.into_reads()?;
+                            Some(Speculation { outcome, reads })
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .expect("Speculative execution should not panic")
+            })
+            .collect()
+    })
+}
+
+/// A [`MoveResolver`] that records the state keys read from the `base` resolver.
+struct TrackedResolver<'a, R> {
+    base: &'a R,
+    reads: RefCell<HashSet<StateKey>>,
+    /// Set on reads that cannot be recorded as a state key, like table entries.
+    untracked_read: Cell<bool>,
+}
+
+impl<'a, R> TrackedResolver<'a, R> {
+    fn new(base: &'a R) -> Self {
+        Self {
+            base,
+            reads: RefCell::new(HashSet::new()),
+            untracked_read: Cell::new(false),
+        }
+    }
+
+    fn into_reads(self) -> Option<HashSet<StateKey>> {
+        (!self.untracked_read.get()).then(|| self.reads.into_inner())
+    }
+
+    fn record_module(&self, id: &ModuleId) {
+        let state_key = StateKey::module(id.address(), id.name());
+        self.reads.borrow_mut().insert(state_key);
+    }
+}
+
+impl<R: MoveResolver<PartialVMError>> ModuleResolver for TrackedResolver<'_, R> {
+    type Error = PartialVMError;
+
+    fn get_module_metadata(&self, module_id: &ModuleId) -> Vec<Metadata> {
+        self.record_module(module_id);
+        self.base.get_module_metadata(module_id)
+    }
+
+    fn get_module(&self, id: &ModuleId) -> Result<Option<Bytes>, Self::Error> {
+        self.record_module(id);
+        self.base.get_module(id)
+    }
+}
+
+impl<R: MoveResolver<PartialVMError>> ResourceResolver for TrackedResolver<'_, R> {
+    type Error = PartialVMError;
+
+    fn get_resource_bytes_with_metadata_and_layout(
+        &self,
+        address: &AccountAddress,
+        struct_tag: &StructTag,
+        metadata: &[Metadata],
+        layout: Option<&MoveTypeLayout>,
+    ) -> Result<(Option<Bytes>, usize), Self::Error> {
+        match StateKey::resource(address, struct_tag) {
+            Ok(state_key) => {
+                self.reads.borrow_mut().insert(state_key);
+            }
+            Err(_) => self.untracked_read.set(true),
+        }
+        self.base
+            .get_resource_bytes_with_metadata_and_layout(address, struct_tag, metadata, layout)
+    }
+}
+
+impl<R> TableResolver for TrackedResolver<'_, R> {
+    fn resolve_table_entry_bytes_with_layout(
+        &self,
+        _handle: &TableHandle,
+        _key: &[u8],
+        _maybe_layout: Option<&MoveTypeLayout>,
+    ) -> Result<Option<Bytes>, PartialVMError> {
+        self.untracked_read.set(true);
+        Err(PartialVMError::new(StatusCode::STORAGE_ERROR)
+            .with_message("Table entries are not available to speculative execution".into()))
+    }
+}
@@ -0,0 +1,49 @@
+use {
+    crate::{block::ExtendedBlock, primitives::B256},
+    std::{fmt, time::Duration},
+};
+
+/// Concise description of a built block, logged once per block for operators.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockSummary {
+    pub height: u64,
+    pub hash: B256,
+    pub transactions: usize,
+    pub gas_used: u64,
+    pub base_fee: u64,
+    pub build_duration: Duration,
+    /// Number of transactions left in the mem-pool after the block was built.
+    pub mem_pool_size: usize,
+}
+
+impl BlockSummary {
+    pub fn new(block: &ExtendedBlock, build_duration: Duration, mem_pool_size: usize) -> Self {
+        let header = &block.block.header;
+        Self {
+            height: header.number,
+            hash: block.hash,
+            transactions: block.block.transactions.len(),
+            gas_used: header.gas_used,
+            base_fee: header.base_fee_per_gas.unwrap_or_default(),
+            build_duration,
+            mem_pool_size,
+        }
+    }
+}
+
+impl fmt::Display for BlockSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "height={} hash={} transactions={} gas_used={} base_fee={} build_duration_ms={} \
+            mem_pool_size={}",
+            self.height,
+            self.hash,
+            self.transactions,
+            self.gas_used,
+            self.base_fee,
+            self.build_duration.as_millis(),
+            self.mem_pool_size,
+        )
+    }
+}
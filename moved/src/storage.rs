@@ -1,7 +1,7 @@
 use {
     crate::primitives::{KeyHashable, B256},
     aptos_types::state_store::{state_key::StateKey, state_value::StateValue},
-    eth_trie::{EthTrie, MemoryDB, Trie, DB},
+    eth_trie::{EthTrie, MemoryDB, Trie, TrieError, DB},
     move_binary_format::errors::PartialVMError,
     move_core_types::{effects::ChangeSet, resolver::MoveResolver},
     move_table_extension::{TableChangeSet, TableResolver},
@@ -117,36 +117,42 @@ impl State for InMemoryState {
 
 impl InMemoryState {
     fn insert_change_set_into_merkle_trie(&mut self, change_set: &ChangeSet) -> B256 {
-        let values = change_set.to_tree_values();
-
         let mut trie = self.tree();
-        for (k, v) in values {
-            let key_bytes = k.key_hash();
-            let value_bytes = v
-                .as_ref()
-                .map(|x| bcs::to_bytes(x).expect("Value must serialize"));
-            trie.insert(
-                key_bytes.0.as_slice(),
-                value_bytes.as_deref().unwrap_or(&[]),
-            )
-            .expect(IN_MEMORY_EXPECT_MSG);
-        }
+        insert_change_set(&mut trie, change_set).expect(IN_MEMORY_EXPECT_MSG);
         let root = trie.root_hash().expect(IN_MEMORY_EXPECT_MSG);
         self.current_state_root = Some(root);
         root
     }
 }
 
+/// Inserts the [`ToTreeValues`] of `change_set` into the merkle `trie`.
+pub(crate) fn insert_change_set<D: DB>(
+    trie: &mut EthTrie<D>,
+    change_set: &ChangeSet,
+) -> Result<(), TrieError> {
+    for (k, v) in change_set.to_tree_values() {
+        let key_bytes = k.key_hash();
+        let value_bytes = v
+            .as_ref()
+            .map(|x| bcs::to_bytes(x).expect("Value must serialize"));
+        trie.insert(
+            key_bytes.0.as_slice(),
+            value_bytes.as_deref().unwrap_or(&[]),
+        )?;
+    }
+    Ok(())
+}
+
 /// The merkle patricia trie key is the hash of the actual key.
-type TreeKey = StateKey;
+pub(crate) type TreeKey = StateKey;
 
 /// The merkle patricia trie value. None indicates the value was deleted.
-type TreeValue = Option<StateValue>;
+pub(crate) type TreeValue = Option<StateValue>;
 
 /// Converts itself to a set of updates for a merkle patricia trie.
 ///
 /// This trait is defined by a single operation called [`Self::to_tree_values`].
-trait ToTreeValues {
+pub(crate) trait ToTreeValues {
     /// Extracts modules and resources and generates a set of merkle trie keys and values applicable
     /// to a trie for the purpose of updating it resulting in a new root hash.
     ///
@@ -0,0 +1,12 @@
+module 0x8fd379246834eac74b8419ffda202cf8051f7a03::evm_caller {
+    use 0x1::eth_token::get_metadata;
+    use 0x1::evm::{evm_call, is_result_success};
+    use 0x1::fungible_asset_u256;
+
+    /// Calls the EVM contract at `to` without data and returns if the call succeeded.
+    public fun call_succeeds(caller: &signer, to: address): bool {
+        let value = fungible_asset_u256::zero(get_metadata());
+        let result = evm_call(caller, to, value, vector[]);
+        is_result_success(&result)
+    }
+}
@@ -1,4 +1,44 @@
-use alloy::rpc::types::{EIP1186AccountProofResponse, EIP1186StorageProof};
+use {
+    crate::primitives::{B256, U256, U64},
+    alloy::rpc::types::{EIP1186AccountProofResponse, EIP1186StorageProof},
+    op_alloy::rpc_types::L1BlockInfo,
+    serde::{Deserialize, Serialize},
+};
 
 pub type ProofResponse = EIP1186AccountProofResponse;
 pub type StorageProof = EIP1186StorageProof;
+
+/// All the values of an account returned by `eth_getAccount` as defined in
+/// [EIP-7639](https://eips.ethereum.org/EIPS/eip-7639).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountResponse {
+    pub balance: U256,
+    pub nonce: U64,
+    pub code_hash: B256,
+    /// The root of the whole state, as accounts do not have storage tries of their own.
+    pub storage_root: B256,
+}
+
+/// Parameters of the L1 data fee charged to transactions, as set by the L1 attributes deposit at
+/// the start of the block. There is no fee overhead since the Ecotone upgrade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L1FeeParams {
+    pub l1_base_fee: U256,
+    pub l1_base_fee_scalar: U256,
+    pub l1_blob_base_fee: U256,
+    pub l1_blob_base_fee_scalar: U256,
+}
+
+impl L1FeeParams {
+    /// The fee parameters of `info`, `None` if any of them is missing.
+    pub fn from_l1_block_info(info: &L1BlockInfo) -> Option<Self> {
+        Some(Self {
+            l1_base_fee: U256::from(info.l1_gas_price?),
+            l1_base_fee_scalar: U256::from(info.l1_base_fee_scalar?),
+            l1_blob_base_fee: U256::from(info.l1_blob_base_fee?),
+            l1_blob_base_fee_scalar: U256::from(info.l1_blob_base_fee_scalar?),
+        })
+    }
+}
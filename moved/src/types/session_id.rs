@@ -3,6 +3,7 @@ use {
     crate::{
         block::HeaderForExecution,
         genesis::config::{GenesisConfig, CHAIN_ID},
+        move_execution::evm_native::StructLoggerConfig,
         primitives::{ToMoveAddress, B256},
         types::transactions::NormalizedEthTransaction,
     },
@@ -23,6 +24,14 @@ pub struct SessionId {
     pub chain_id: u8,
     pub user_txn_context: Option<UserTransactionContext>,
     pub block_header: HeaderForExecution,
+    /// Opcodes reverting EVM native calls made in the session.
+    pub disabled_evm_opcodes: Vec<u8>,
+    /// Bytes of memory EVM native calls made in the session may expand to, the
+    /// [`DEFAULT_EVM_MEMORY_LIMIT`](crate::move_execution::evm_native::DEFAULT_EVM_MEMORY_LIMIT)
+    /// when unset.
+    pub evm_memory_limit: Option<u64>,
+    /// Records the opcodes executed by EVM native calls made in the session when set.
+    pub struct_logger: Option<StructLoggerConfig>,
 }
 
 impl SessionId {
@@ -52,6 +61,9 @@ impl SessionId {
             chain_id,
             user_txn_context: Some(user_context),
             block_header,
+            disabled_evm_opcodes: genesis_config.disabled_evm_opcodes.clone(),
+            evm_memory_limit: Some(genesis_config.evm_memory_limit),
+            struct_logger: None,
         }
     }
 
@@ -79,6 +91,24 @@ impl SessionId {
             chain_id,
             user_txn_context: Some(user_context),
             block_header,
+            disabled_evm_opcodes: genesis_config.disabled_evm_opcodes.clone(),
+            evm_memory_limit: Some(genesis_config.evm_memory_limit),
+            struct_logger: None,
+        }
+    }
+
+    /// Session for the state changes made by the node itself when sealing a block,
+    /// outside of any transaction.
+    pub fn new_from_block(
+        genesis_config: &GenesisConfig,
+        block_header: HeaderForExecution,
+    ) -> Self {
+        Self {
+            chain_id: u8_chain_id(genesis_config),
+            block_header,
+            disabled_evm_opcodes: genesis_config.disabled_evm_opcodes.clone(),
+            evm_memory_limit: Some(genesis_config.evm_memory_limit),
+            ..Default::default()
         }
     }
 }
@@ -4,12 +4,16 @@
 //! accepts.
 
 use {
-    super::queries::ProofResponse,
+    super::queries::{AccountResponse, L1FeeParams, ProofResponse},
     crate::{
         block::{ExtendedBlock, Header},
+        move_execution::evm_native::{StructLoggerConfig, TransactionTrace},
         primitives::{Address, Bytes, ToU64, B2048, B256, U256, U64},
-        state_actor::NewPayloadIdInput,
-        types::transactions::NormalizedExtendedTxEnvelope,
+        state_actor::{
+            InvalidPayloadAttributes, NewPayloadIdInput, ProofError, StateRetention,
+            StateUnavailable,
+        },
+        types::transactions::{MoveEvent, NormalizedExtendedTxEnvelope},
     },
     alloy::{
         consensus::transaction::TxEnvelope,
@@ -17,10 +21,12 @@ use {
         primitives::Bloom,
         rpc::types::{BlockTransactions, FeeHistory, TransactionRequest},
     },
+    move_core_types::{account_address::AccountAddress, language_storage::StructTag},
     op_alloy::{
         consensus::{OpReceiptEnvelope, OpTxEnvelope},
         rpc_types::L1BlockInfo,
     },
+    serde::{Deserialize, Serialize},
     tokio::sync::oneshot,
 };
 
@@ -33,6 +39,8 @@ pub struct Payload {
     pub parent_beacon_block_root: B256,
     pub transactions: Vec<Bytes>,
     pub gas_limit: U64,
+    /// Build the block only from `transactions`, leaving the mem-pool untouched.
+    pub no_tx_pool: bool,
 }
 
 pub type Withdrawal = alloy::rpc::types::Withdrawal;
@@ -114,7 +122,7 @@ impl ExecutionPayload {
             extra_data: value.block.header.extra_data,
             base_fee_per_gas: U256::from(value.block.header.base_fee_per_gas.unwrap_or_default()),
             transactions,
-            withdrawals: Vec::new(), // TODO: withdrawals
+            withdrawals: value.block.withdrawals,
             blob_gas_used: U64::from(value.block.header.blob_gas_used.unwrap_or_default()),
             excess_blob_gas: U64::from(value.block.header.excess_blob_gas.unwrap_or_default()),
         }
@@ -141,7 +149,7 @@ pub enum Command {
     },
     StartBlockBuild {
         payload_attributes: Payload,
-        response_channel: oneshot::Sender<PayloadId>,
+        response_channel: oneshot::Sender<Result<PayloadId, InvalidPayloadAttributes>>,
     },
     GetPayload {
         id: PayloadId,
@@ -170,15 +178,29 @@ pub enum Query {
     ChainId {
         response_channel: oneshot::Sender<u64>,
     },
+    NodeSettings {
+        response_channel: oneshot::Sender<NodeSettings>,
+    },
     BalanceByHeight {
         address: Address,
         height: BlockNumberOrTag,
-        response_channel: oneshot::Sender<Option<U256>>,
+        response_channel: oneshot::Sender<Result<U256, StateUnavailable>>,
+    },
+    AccountByHeight {
+        address: Address,
+        height: BlockNumberOrTag,
+        response_channel: oneshot::Sender<Result<AccountResponse, StateUnavailable>>,
+    },
+    ResourceExists {
+        address: AccountAddress,
+        resource: StructTag,
+        height: BlockNumberOrTag,
+        response_channel: oneshot::Sender<Result<bool, StateUnavailable>>,
     },
     NonceByHeight {
         address: Address,
         height: BlockNumberOrTag,
-        response_channel: oneshot::Sender<Option<u64>>,
+        response_channel: oneshot::Sender<Result<u64, StateUnavailable>>,
     },
     BlockByHash {
         hash: B256,
@@ -193,6 +215,9 @@ pub enum Query {
     BlockNumber {
         response_channel: oneshot::Sender<u64>,
     },
+    GasPrice {
+        response_channel: oneshot::Sender<U256>,
+    },
     FeeHistory {
         block_count: u64,
         block_number: BlockNumberOrTag,
@@ -211,13 +236,54 @@ pub enum Query {
     },
     TransactionReceipt {
         tx_hash: B256,
-        response_channel: oneshot::Sender<Option<TransactionReceipt>>,
+        response_channel: oneshot::Sender<Option<ExtendedTransactionReceipt>>,
     },
     GetProof {
         address: Address,
         storage_slots: Vec<U256>,
         height: BlockId,
-        response_channel: oneshot::Sender<Option<ProofResponse>>,
+        response_channel: oneshot::Sender<Result<ProofResponse, ProofError>>,
+    },
+    /// Hashes of the transactions sent by `address` in the order they were included in blocks,
+    /// skipping the first `offset` and returning at most `limit` of them.
+    TransactionsBySender {
+        address: Address,
+        offset: usize,
+        limit: usize,
+        response_channel: oneshot::Sender<Vec<B256>>,
+    },
+    /// The transaction with `tx_hash` if it is in a block or waiting in the mem-pool.
+    TransactionByHash {
+        tx_hash: B256,
+        response_channel: oneshot::Sender<Option<RpcTransaction>>,
+    },
+    /// Where the deposit with `source_hash` was included, `None` if it is not processed yet.
+    DepositStatus {
+        source_hash: B256,
+        response_channel: oneshot::Sender<Option<DepositInclusion>>,
+    },
+    /// The block as stored by the node, e.g. to RLP encode it, `None` if it is not found.
+    ExtendedBlock {
+        block_id: BlockId,
+        response_channel: oneshot::Sender<Option<ExtendedBlock>>,
+    },
+    /// The L1 fee parameters transactions of the latest block are charged with, `None` if the
+    /// block does not start with a deposit setting them.
+    L1FeeParams {
+        response_channel: oneshot::Sender<Option<L1FeeParams>>,
+    },
+    /// The opcodes executed by the EVM when the transaction with `tx_hash` is executed again,
+    /// `None` if the transaction is not in a block.
+    TraceTransaction {
+        tx_hash: B256,
+        config: StructLoggerConfig,
+        response_channel: oneshot::Sender<crate::Result<Option<TransactionTrace>>>,
+    },
+    /// The outcome of executing the transactions of `payload` again on top of the state of its
+    /// parent block, `None` if the parent block is unknown.
+    ExecutePayload {
+        payload: ExecutionPayload,
+        response_channel: oneshot::Sender<crate::Result<Option<ExecutionOutcome>>>,
     },
 }
 
@@ -227,9 +293,27 @@ impl From<Query> for StateMessage {
     }
 }
 
-pub type RpcBlock = alloy::rpc::types::Block<op_alloy::rpc_types::Transaction>;
+pub type RpcTransaction = op_alloy::rpc_types::Transaction;
 
-#[derive(Debug)]
+pub type RpcBlock = alloy::rpc::types::Block<RpcTransaction>;
+
+/// Settings of the node that are safe to share publicly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeSettings {
+    pub chain_id: u64,
+    pub state_retention: StateRetention,
+    pub dev: bool,
+}
+
+/// The L2 transaction resulting from a deposit and the block it was included in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DepositInclusion {
+    pub tx_hash: B256,
+    pub block_hash: B256,
+    pub block_number: u64,
+}
+
+#[derive(Debug, Clone)]
 pub struct BlockResponse(pub RpcBlock);
 
 impl BlockResponse {
@@ -238,6 +322,7 @@ impl BlockResponse {
     }
 
     pub fn from_block_with_transaction_hashes(value: ExtendedBlock) -> Self {
+        let size = U256::from(value.block.rlp_length());
         Self::new(RpcBlock {
             transactions: BlockTransactions::Hashes(
                 value
@@ -257,9 +342,9 @@ impl BlockResponse {
             header: alloy::rpc::types::Header {
                 hash: value.hash,
                 inner: value.block.header,
+                size: Some(size),
                 // TODO: review fields below
                 total_difficulty: None,
-                size: None,
             },
             // TODO: review fields below
             uncles: Vec::new(),
@@ -268,6 +353,7 @@ impl BlockResponse {
     }
 
     pub fn from_block_with_transactions(value: ExtendedBlock) -> Self {
+        let size = U256::from(value.block.rlp_length());
         Self::new(RpcBlock {
             transactions: BlockTransactions::Full(
                 value
@@ -276,33 +362,21 @@ impl BlockResponse {
                     .into_iter()
                     .enumerate()
                     .map(|(i, inner)| {
-                        let tx = alloy::rpc::types::Transaction {
-                            block_hash: Some(value.hash),
-                            block_number: Some(value.block.header.number),
-                            transaction_index: Some(i as u64),
-                            // TODO: Gassing it up requires either modifying supported variants of
-                            // `OpTxEnvelope` or storing a different type in block transactions
-                            // altogether
-                            effective_gas_price: None,
-                            from: compute_from(&inner)
-                                .expect("Block transactions should contain valid signature"),
+                        to_rpc_transaction(
                             inner,
-                        };
-                        op_alloy::rpc_types::Transaction {
-                            inner: tx,
-                            // TODO: what are these fields?
-                            deposit_nonce: None,
-                            deposit_receipt_version: None,
-                        }
+                            Some(value.hash),
+                            Some(value.block.header.number),
+                            Some(i as u64),
+                        )
                     })
                     .collect(),
             ),
             header: alloy::rpc::types::Header {
                 hash: value.hash,
                 inner: value.block.header,
+                size: Some(size),
                 // TODO: review fields below
                 total_difficulty: None,
-                size: None,
             },
             // TODO: review fields below
             uncles: Vec::new(),
@@ -311,6 +385,33 @@ impl BlockResponse {
     }
 }
 
+/// Converts `inner` into its RPC representation. The block fields are `None` for transactions
+/// still waiting in the mem-pool.
+pub fn to_rpc_transaction(
+    inner: OpTxEnvelope,
+    block_hash: Option<B256>,
+    block_number: Option<u64>,
+    transaction_index: Option<u64>,
+) -> RpcTransaction {
+    let tx = alloy::rpc::types::Transaction {
+        block_hash,
+        block_number,
+        transaction_index,
+        // TODO: Gassing it up requires either modifying supported variants of
+        // `OpTxEnvelope` or storing a different type in block transactions
+        // altogether
+        effective_gas_price: None,
+        from: compute_from(&inner).expect("Transactions should contain valid signature"),
+        inner,
+    };
+    op_alloy::rpc_types::Transaction {
+        inner: tx,
+        // TODO: what are these fields?
+        deposit_nonce: None,
+        deposit_receipt_version: None,
+    }
+}
+
 fn compute_from(tx: &OpTxEnvelope) -> Result<Address, alloy::primitives::SignatureError> {
     match tx {
         OpTxEnvelope::Legacy(tx) => tx.recover_signer(),
@@ -324,6 +425,18 @@ fn compute_from(tx: &OpTxEnvelope) -> Result<Address, alloy::primitives::Signatu
 
 pub type TransactionReceipt = op_alloy::rpc_types::OpTransactionReceipt;
 
+/// A [`TransactionReceipt`] with the Move events emitted by the transaction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtendedTransactionReceipt {
+    #[serde(flatten)]
+    pub inner: TransactionReceipt,
+    /// Only set when enabled in the node configuration. The `logs` of the receipt still contain
+    /// the Ethereum log approximations of these events.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub move_events: Option<Vec<MoveEvent>>,
+}
+
 #[derive(Debug)]
 pub struct ExecutionOutcome {
     pub receipts_root: B256,
@@ -357,6 +470,8 @@ pub struct TransactionWithReceipt {
     ///
     /// This allows computing the log index for each log in this transaction.
     pub logs_offset: u64,
+    /// Move events emitted by the transaction, excluding the ones carrying EVM logs.
+    pub move_events: Vec<MoveEvent>,
 }
 
 pub(crate) trait WithExecutionOutcome {
@@ -1,12 +1,17 @@
 use {
-    crate::{primitives::ToMoveAddress, Error, InvalidTransactionCause, UserError},
+    crate::{
+        move_execution::evm_native::StructLogger, primitives::ToMoveAddress, Error,
+        InvalidTransactionCause, UserError,
+    },
     alloy::{
         consensus::{
             Receipt, ReceiptWithBloom, Signed, Transaction, TxEip1559, TxEip2930, TxEnvelope,
             TxLegacy,
         },
         eips::eip2930::AccessList,
-        primitives::{address, Address, Bloom, Bytes, Log, LogData, TxKind, B256, U256, U64},
+        primitives::{
+            address, Address, Bloom, Bytes, Log, LogData, Signature, TxKind, B256, U256, U64,
+        },
         rlp::{Buf, Decodable, Encodable, RlpDecodable, RlpEncodable},
         rpc::types::TransactionRequest,
     },
@@ -24,6 +29,15 @@ use {
 const DEPOSITED_TYPE_BYTE: u8 = 0x7e;
 pub const L2_LOWEST_ADDRESS: Address = address!("4200000000000000000000000000000000000000");
 pub const L2_HIGHEST_ADDRESS: Address = address!("42000000000000000000000000000000000000ff");
+/// Sender of the L1 attributes deposit at the start of every block.
+pub const L1_ATTRIBUTES_DEPOSITOR: Address = address!("deaddeaddeaddeaddeaddeaddeaddeaddead0001");
+/// Half of the secp256k1 curve order, the largest `s` value of a canonical signature per EIP-2.
+pub const SECP256K1N_HALF: U256 = U256::from_limbs([
+    0xdfe92f46681b20a0,
+    0x5d576e7357a4501d,
+    0xffffffffffffffff,
+    0x7fffffffffffffff,
+]);
 
 /// OP-stack special transactions defined in
 /// https://specs.optimism.io/protocol/deposits.html#the-deposited-transaction-type
@@ -39,6 +53,25 @@ pub struct DepositedTx {
     pub data: Bytes,
 }
 
+impl DepositedTx {
+    /// Checks the fields derived from L1 before execution, returning the reason the deposit is
+    /// malformed.
+    ///
+    /// Deposits are credited by minting, so a `value` above `mint` would create tokens that were
+    /// never locked on L1.
+    pub fn check_well_formed(&self) -> Result<(), &'static str> {
+        if self.source_hash == B256::ZERO {
+            Err("missing source hash")
+        } else if self.from == Address::ZERO || self.to == Address::ZERO {
+            Err("zero sender or recipient address")
+        } else if self.value > self.mint {
+            Err("value exceeds the minted amount")
+        } else {
+            Ok(())
+        }
+    }
+}
+
 /// Same as `alloy_consensus::TxEnvelope` except extended to
 /// include the new Deposited transaction defined in OP-stack.
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
@@ -221,6 +254,13 @@ impl NormalizedExtendedTxEnvelope {
             Self::Canonical(tx) => tx.effective_gas_price(base_fee),
         }
     }
+
+    pub fn sender(&self) -> Address {
+        match self {
+            Self::DepositedTx(tx) => tx.from,
+            Self::Canonical(tx) => tx.signer,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -241,8 +281,22 @@ pub struct TransactionExecutionOutcome {
     pub l2_price: U256,
     /// All emitted Move events converted to Ethereum logs.
     pub logs: Vec<Log<LogData>>,
+    /// Emitted Move events in their original form, excluding the ones carrying EVM logs.
+    pub move_events: Vec<MoveEvent>,
     /// AccountAddress + ModuleId of a deployed module (if any).
     pub deployment: Option<(AccountAddress, ModuleId)>,
+    /// Opcodes executed by the EVM, only recorded if the transaction is traced.
+    pub struct_logger: Option<StructLogger>,
+}
+
+/// A Move event as emitted by the VM, before it is approximated by an Ethereum log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveEvent {
+    /// Canonical string of the event type tag.
+    pub type_tag: String,
+    /// BCS serialized event.
+    pub data: Bytes,
 }
 
 impl TransactionExecutionOutcome {
@@ -252,6 +306,7 @@ impl TransactionExecutionOutcome {
         gas_used: u64,
         l2_price: U256,
         logs: Vec<Log<LogData>>,
+        move_events: Vec<MoveEvent>,
         deployment: Option<(AccountAddress, ModuleId)>,
     ) -> Self {
         Self {
@@ -260,7 +315,16 @@ impl TransactionExecutionOutcome {
             gas_used,
             l2_price,
             logs,
+            move_events,
             deployment,
+            struct_logger: None,
+        }
+    }
+
+    pub fn with_struct_logger(self, struct_logger: Option<StructLogger>) -> Self {
+        Self {
+            struct_logger,
+            ..self
         }
     }
 }
@@ -306,10 +370,20 @@ impl TryFrom<TxEnvelope> for NormalizedEthTransaction {
     }
 }
 
+/// Rejects signatures with `s` in the upper half of the curve order. Both halves recover the same
+/// signer, so accepting them would give one transaction two hashes.
+fn check_canonical_signature(signature: &Signature) -> Result<(), Error> {
+    if signature.s() > SECP256K1N_HALF {
+        Err(InvalidTransactionCause::NonCanonicalSignature)?;
+    }
+    Ok(())
+}
+
 impl TryFrom<Signed<TxEip1559>> for NormalizedEthTransaction {
     type Error = Error;
 
     fn try_from(value: Signed<TxEip1559>) -> Result<Self, Self::Error> {
+        check_canonical_signature(value.signature())?;
         let address = value.recover_signer()?;
         let tx = value.strip_signature();
 
@@ -332,6 +406,7 @@ impl TryFrom<Signed<TxEip2930>> for NormalizedEthTransaction {
     type Error = Error;
 
     fn try_from(value: Signed<TxEip2930>) -> Result<Self, Self::Error> {
+        check_canonical_signature(value.signature())?;
         let address = value.recover_signer()?;
         let tx = value.strip_signature();
 
@@ -354,6 +429,7 @@ impl TryFrom<Signed<TxLegacy>> for NormalizedEthTransaction {
     type Error = Error;
 
     fn try_from(value: Signed<TxLegacy>) -> Result<Self, Self::Error> {
+        check_canonical_signature(value.signature())?;
         let address = value.recover_signer()?;
         let tx = value.strip_signature();
 
@@ -463,6 +539,7 @@ mod tests {
     use {
         super::*,
         alloy::{
+            consensus::SignableTransaction,
             primitives::{address, hex},
             rlp::{Decodable, Encodable},
         },
@@ -497,6 +574,36 @@ mod tests {
         rlp_roundtrip(&Bytes::from_static(&hex!("02f86f82a45580808346a8928252089465d08a056c17ae13370565b04cf77d2afa1cb9fa8806f05b59d3b2000080c080a0dd50efde9a4d2f01f5248e1a983165c8cfa5f193b07b4b094f4078ad4717c1e4a017db1be1e8751b09e033bcffca982d0fe4919ff6b8594654e06647dee9292750")));
     }
 
+    #[test]
+    fn test_high_s_signature_is_rejected() {
+        let encoded = hex!("02f86f82a45580808346a8928252089465d08a056c17ae13370565b04cf77d2afa1cb9fa8806f05b59d3b2000080c080a0dd50efde9a4d2f01f5248e1a983165c8cfa5f193b07b4b094f4078ad4717c1e4a017db1be1e8751b09e033bcffca982d0fe4919ff6b8594654e06647dee9292750");
+        let Ok(ExtendedTxEnvelope::Canonical(TxEnvelope::Eip1559(tx))) =
+            ExtendedTxEnvelope::decode(&mut encoded.as_slice())
+        else {
+            panic!("Transaction should decode as EIP-1559");
+        };
+        let signer = NormalizedEthTransaction::try_from(tx.clone())
+            .expect("Canonical signature should be accepted")
+            .signer;
+
+        // The same signer is recovered from the other half of the curve order
+        let signature = *tx.signature();
+        let curve_order = SECP256K1N_HALF * U256::from(2) + U256::from(1);
+        let high_s = Signature::new(
+            signature.r(),
+            curve_order - signature.s(),
+            signature.v().inverted(),
+        );
+        let malleated = tx.strip_signature().into_signed(high_s);
+        assert_eq!(malleated.recover_signer().unwrap(), signer);
+
+        let err = NormalizedEthTransaction::try_from(malleated).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Signature s value must be in the lower half of the curve order"
+        );
+    }
+
     fn rlp_roundtrip(encoded: &[u8]) {
         let mut re_encoded = Vec::with_capacity(encoded.len());
         let mut slice = encoded;
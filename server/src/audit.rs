@@ -0,0 +1,45 @@
+use {moved_engine_api::jsonrpc::JsonRpcResponse, serde::Serialize, serde_json::Value};
+
+/// Prefix of audit log lines, so that they can be filtered separately from other output.
+pub const AUDIT_LOG_TARGET: &str = "AUDIT";
+
+/// Audit log entry of a call on the authenticated port.
+#[derive(Debug, Serialize)]
+pub struct AuditLog<'a> {
+    pub method: &'a Value,
+    pub request_id: &'a Value,
+    /// `iat` claim of the JWT the caller authenticated with.
+    pub issued_at: u64,
+    pub outcome: AuditOutcome<'a>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum AuditOutcome<'a> {
+    Ok,
+    Error { code: i64, message: &'a str },
+}
+
+impl<'a> AuditLog<'a> {
+    pub fn new(request: &'a Value, response: &'a JsonRpcResponse, issued_at: u64) -> Self {
+        let outcome = match &response.error {
+            None => AuditOutcome::Ok,
+            Some(e) => AuditOutcome::Error {
+                code: e.code,
+                message: &e.message,
+            },
+        };
+        Self {
+            method: &request["method"],
+            request_id: &request["id"],
+            issued_at,
+            outcome,
+        }
+    }
+
+    /// Serializes the log entry to a single line starting with [`AUDIT_LOG_TARGET`].
+    pub fn render(&self) -> String {
+        let entry = serde_json::to_string(self).expect("Audit log must serialize");
+        format!("{AUDIT_LOG_TARGET}: {entry}")
+    }
+}
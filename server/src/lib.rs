@@ -1,40 +1,76 @@
 use {
-    crate::mirror::MirrorLog,
-    clap::Parser,
-    flate2::read::GzDecoder,
+    crate::{
+        audit::AuditLog,
+        mirror::{MirrorLog, MirrorLogConfig, MirrorLogMode},
+    },
+    clap::{Parser, Subcommand},
+    flate2::{
+        read::GzDecoder,
+        write::{GzEncoder, ZlibEncoder},
+        Compression,
+    },
+    futures_util::StreamExt,
     jsonwebtoken::{DecodingKey, Validation},
     moved::{
         block::{
             Block, BlockHash, BlockMemory, BlockRepository, Eip1559GasFee, ExtendedBlock, Header,
             InMemoryBlockQueries, InMemoryBlockRepository, MovedBlockHash,
         },
-        genesis::{self, config::GenesisConfig},
-        move_execution::{CreateEcotoneL1GasFee, CreateMovedL2GasFee, MovedBaseTokenAccounts},
-        primitives::U256,
-        state_actor::{InMemoryStateQueries, StatePayloadId},
-        storage::InMemoryState,
+        genesis::{
+            self,
+            config::{
+                GenesisConfig, DEFAULT_MAX_MODULE_DEPENDENCIES, DEFAULT_MAX_MODULE_SIZE,
+                PLACEHOLDER_TREASURY,
+            },
+        },
+        move_execution::{
+            evm_native::DEFAULT_EVM_MEMORY_LIMIT, CreateEcotoneL1GasFee, CreateMovedL2GasFee,
+            MovedBaseTokenAccounts,
+        },
+        primitives::{Address, ToMoveAddress, B256, U256},
+        state_actor::{
+            GasPriceBounds, InMemoryStateQueries, ReadRetry, StatePayloadId, StateRetention,
+            TransactionOrdering, DEFAULT_MAX_PROOF_SLOTS,
+        },
+        storage::{InMemoryState, State},
         types::state::{Command, StateMessage},
     },
+    moved_engine_api::{
+        jsonrpc::JsonRpcError,
+        method_name::MethodName,
+        request::{HeavyReadLimit, RequestConfig, RequestTimeouts, DEFAULT_MAX_HEAVY_READS},
+    },
     once_cell::sync::Lazy,
     std::{
         fs,
-        io::Read,
+        io::{Read, Write},
         net::{Ipv4Addr, SocketAddr, SocketAddrV4},
-        path::Path,
-        time::SystemTime,
+        path::{Path, PathBuf},
+        sync::Arc,
+        time::{Duration, SystemTime},
+    },
+    tokio::{
+        signal::{self, unix::SignalKind},
+        sync::mpsc,
     },
-    tokio::sync::mpsc,
     warp::{
-        hyper::{body::Bytes, Body, Response},
+        http::{
+            header::{HeaderValue, CONTENT_ENCODING},
+            StatusCode,
+        },
+        hyper::{
+            body::{Buf, Bytes},
+            Body, Response,
+        },
         path::FullPath,
         Filter, Rejection,
     },
     warp_reverse_proxy::{
-        extract_request_data_filter, proxy_to_and_forward_response, Headers, Method,
-        QueryParameters,
+        proxy_to_and_forward_response, query_params_filter, Headers, Method, QueryParameters,
     },
 };
 
+mod audit;
 mod geth_genesis;
 mod mirror;
 
@@ -43,8 +79,194 @@ mod tests;
 
 #[derive(Parser)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<CliCommand>,
     #[arg(short, long)]
-    jwtsecret: String,
+    jwtsecret: Option<String>,
+    /// Maximum size in bytes of a request body, larger requests are rejected with 413
+    #[arg(long, default_value_t = MAX_REQUEST_BODY_SIZE)]
+    max_request_body_size: u64,
+    /// JSON-RPC methods rejected on the public HTTP port
+    #[arg(long, value_delimiter = ',', value_parser = parse_method_name)]
+    http_disabled_methods: Vec<MethodName>,
+    /// JSON-RPC methods rejected on the authenticated port
+    #[arg(long, value_delimiter = ',', value_parser = parse_method_name)]
+    auth_disabled_methods: Vec<MethodName>,
+    /// Which requests are written to the mirror log
+    #[arg(long, value_enum, default_value_t = MirrorLogMode::All)]
+    mirror_log: MirrorLogMode,
+    /// Truncate strings in the mirror log longer than this many characters
+    #[arg(long)]
+    mirror_log_max_string_len: Option<usize>,
+    /// Write an audit log line prefixed with `AUDIT:` for every call on the authenticated port
+    #[arg(long)]
+    audit_log: bool,
+    /// Seconds after which a JSON-RPC request is abandoned with a timeout error
+    #[arg(long, default_value_t = 30)]
+    request_timeout: u64,
+    /// Execute the transactions of `engine_newPayloadV3` payloads again on top of their parent
+    /// block and reject payloads whose state root, receipts root or gas used differ, off by
+    /// default as it doubles the execution cost of every block
+    #[arg(long)]
+    check_payload_execution: bool,
+    /// Serve the `debug_getRawBlock`, `debug_getRawHeader` and `debug_traceTransaction` JSON-RPC
+    /// methods
+    #[arg(long)]
+    debug_methods: bool,
+    /// Seconds after which a `debug_*` JSON-RPC request is abandoned with a timeout error
+    #[arg(long, default_value_t = 300)]
+    debug_request_timeout: u64,
+    /// Account collecting transaction fees before they are paid out, required unless `--dev` is set
+    #[arg(long)]
+    treasury: Option<Address>,
+    /// Run a development chain, accepting placeholder settings like the default treasury
+    #[arg(long)]
+    dev: bool,
+    /// Accept legacy transactions signed without a chain id, which can be replayed on other
+    /// chains
+    #[arg(long)]
+    allow_unprotected_txs: bool,
+    /// EVM opcodes reverting the call executing them, as hex bytes, e.g. `0xff` for `SELFDESTRUCT`
+    #[arg(long, value_delimiter = ',', value_parser = parse_opcode)]
+    disabled_evm_opcodes: Vec<u8>,
+    /// Bytes of memory a single EVM call may expand to, exceeding it halts the call as out of gas
+    #[arg(long, default_value_t = DEFAULT_EVM_MEMORY_LIMIT)]
+    evm_memory_limit: u64,
+    /// Move frameworks deployed at genesis after the default ones, each a release bundle file
+    /// (`.mrb`) or a directory of compiled modules (`.mv`)
+    #[arg(long, value_delimiter = ',')]
+    custom_frameworks: Vec<PathBuf>,
+    /// Maximum size in bytes of a deployed Move module, larger deployments are rejected
+    #[arg(long, default_value_t = DEFAULT_MAX_MODULE_SIZE)]
+    max_module_size: usize,
+    /// Maximum number of modules a deployed Move module may depend on
+    #[arg(long, default_value_t = DEFAULT_MAX_MODULE_DEPENDENCIES)]
+    max_module_dependencies: usize,
+    /// Timestamp of the genesis block in seconds since the UNIX epoch
+    #[arg(long, default_value_t = 0)]
+    genesis_timestamp: u64,
+    /// Beneficiary of all built blocks, overriding the fee recipient suggested by op-node
+    #[arg(long)]
+    fee_recipient: Option<Address>,
+    /// Minimum tip in wei per gas of transactions accepted by `eth_sendRawTransaction`, the max
+    /// priority fee of EIP-1559 transactions and the gas price of older ones
+    #[arg(long, default_value_t = 0)]
+    min_priority_fee: u128,
+    /// Minimum gas price in wei suggested by `eth_gasPrice`
+    #[arg(long)]
+    gas_price_floor: Option<U256>,
+    /// Maximum gas price in wei suggested by `eth_gasPrice`
+    #[arg(long)]
+    gas_price_ceiling: Option<U256>,
+    /// Milliseconds after which block building stops including mem-pool transactions
+    #[arg(long)]
+    build_deadline: Option<u64>,
+    /// Order of mem-pool transactions of different accounts in built blocks: `tip` or
+    /// `received-time`
+    #[arg(long, default_value = "tip", value_parser = parse_transaction_ordering)]
+    transaction_ordering: TransactionOrdering,
+    /// Only execute deposits from these senders besides the L1 attributes depositor, deposits
+    /// from other senders are included as failed without state changes
+    #[arg(long, value_delimiter = ',')]
+    deposit_senders: Option<Vec<Address>>,
+    /// Maximum gas limit of deposits, deposits above it are included as failed without state
+    /// changes
+    #[arg(long)]
+    max_deposit_gas: Option<u64>,
+    /// Include deposits with a missing source hash, a zero sender or recipient, or a value above
+    /// the minted amount as failed without state changes instead of executing them
+    #[arg(long)]
+    check_deposits: bool,
+    /// Maximum number of transactions in a block, payload attributes transactions are always
+    /// included
+    #[arg(long)]
+    max_block_transactions: Option<usize>,
+    /// Seconds a block timestamp may be ahead of the local clock, also requires block timestamps to
+    /// be increasing
+    #[arg(long)]
+    max_timestamp_drift: Option<u64>,
+    /// Maximum number of blocks the head may be moved back by op-node
+    #[arg(long)]
+    max_reorg_depth: Option<u64>,
+    /// Execute `eth_call` against the `pending` block on top of the mem-pool transactions, which
+    /// executes the whole mem-pool for every such call
+    #[arg(long)]
+    pending_state_calls: bool,
+    /// Execute the transactions of built blocks in parallel, transactions that conflict with an
+    /// earlier one in the block are executed again in order and `--build-deadline` stops
+    /// committing them
+    #[arg(long)]
+    parallel_execution: bool,
+    /// Run a Move-only chain without the EVM and the L2 contracts, EVM transactions are rejected
+    /// and EVM-specific JSON-RPC methods are disabled. Requires `--accept-genesis-state-root` as
+    /// the configured genesis state root includes the L2 contracts
+    #[arg(long, requires = "accept_genesis_state_root")]
+    move_only: bool,
+    /// Start with the computed genesis state root instead of failing if it differs from the
+    /// configured one
+    #[arg(long)]
+    accept_genesis_state_root: bool,
+    /// Add the Move events emitted by a transaction to its receipt as `moveEvents`
+    #[arg(long)]
+    receipt_move_events: bool,
+    /// Keep the state of every past block (`archive`) or only of the latest `--retained-blocks`
+    /// blocks (`pruned`) for queries
+    #[arg(long, default_value = "archive", value_parser = ["archive", "pruned"])]
+    state_retention: String,
+    /// Number of latest blocks, including the head, whose state is kept with
+    /// `--state-retention pruned`
+    #[arg(long, required_if_eq("state_retention", "pruned"))]
+    retained_blocks: Option<u64>,
+    /// Number of times a read of historic state failing in the database is retried before the
+    /// query fails
+    #[arg(long, default_value_t = 3)]
+    db_read_retries: u32,
+    /// Maximum gas simulated and returned by `eth_estimateGas`, estimates are also bounded by the
+    /// gas limit of the latest block
+    #[arg(long)]
+    max_estimate_gas: Option<u64>,
+    /// Maximum gas executed by `eth_call` and `eth_estimateGas`, whatever gas limit the request
+    /// sets
+    #[arg(long)]
+    rpc_gas_cap: Option<u64>,
+    /// Stop logging a summary line for every built block
+    #[arg(long)]
+    no_block_summary: bool,
+    /// Compress responses with gzip or deflate when the client accepts it
+    #[arg(long)]
+    compress_responses: bool,
+    /// Minimum size in bytes of a response body to be compressed
+    #[arg(long, default_value_t = 1024)]
+    compression_min_size: usize,
+    /// Maximum number of storage slots in a single `eth_getProof` request
+    #[arg(long, default_value_t = DEFAULT_MAX_PROOF_SLOTS)]
+    max_proof_slots: usize,
+    /// Maximum number of `eth_call`, `eth_estimateGas`, `eth_getProof` and `eth_feeHistory`
+    /// requests processed concurrently across both ports, further ones wait for their turn
+    #[arg(long, default_value_t = DEFAULT_MAX_HEAVY_READS)]
+    max_heavy_reads: usize,
+}
+
+/// Settings of a port mirrored to op-geth.
+struct MirrorConfig {
+    port: &'static str,
+    request: RequestConfig,
+    log: MirrorLogConfig,
+    /// Whether calls are written to the audit log, only enabled on the authenticated port.
+    audit_log: bool,
+    /// Minimum size of a response body to be compressed, responses are never compressed if `None`.
+    compression_min_size: Option<usize>,
+}
+
+#[derive(Subcommand)]
+enum CliCommand {
+    /// Print the state root computed from the genesis config without starting the node
+    GenesisStateRoot {
+        #[arg(long, default_value_t = CHAIN_ID)]
+        chain_id: u64,
+        #[arg(long, default_value = L2_CONTRACT_GENESIS)]
+        l2_contract_genesis: PathBuf,
+    },
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
@@ -52,33 +274,83 @@ struct Claims {
     iat: u64,
 }
 
+// TODO: genesis should come from a file (path specified by CLI)
+const CHAIN_ID: u64 = 42069;
+const L2_CONTRACT_GENESIS: &str =
+    "src/tests/optimism/packages/contracts-bedrock/deployments/genesis.json";
 const EIP1559_ELASTICITY_MULTIPLIER: u64 = 6;
 const EIP1559_BASE_FEE_MAX_CHANGE_DENOMINATOR: U256 = U256::from_limbs([250, 0, 0, 0]);
 const JWT_VALID_DURATION_IN_SECS: u64 = 60;
+/// Large enough for batch requests and contract deployments
+const MAX_REQUEST_BODY_SIZE: u64 = 10 * 1024 * 1024;
 /// JWT secret key is either passed in as an env var `JWT_SECRET` or file path arg `--jwtsecret`
 static JWTSECRET: Lazy<Vec<u8>> = Lazy::new(|| {
     let mut jwt = std::env::var("JWT_SECRET").unwrap_or_default();
     if jwt.is_empty() {
         let args = Args::parse();
-        jwt = fs::read_to_string(args.jwtsecret).expect("JWT file should exist");
+        let path = args.jwtsecret.expect("JWT secret should be passed in");
+        jwt = fs::read_to_string(path).expect("JWT file should exist");
     }
     hex::decode(jwt).expect("JWT secret should be a hex string")
 });
 
 pub async fn run() {
+    let args = Args::parse();
+    if let Some(CliCommand::GenesisStateRoot {
+        chain_id,
+        l2_contract_genesis,
+    }) = args.command
+    {
+        let genesis_config = GenesisConfig {
+            chain_id,
+            l2_contract_genesis,
+            evm_native: !args.move_only,
+            custom_frameworks: args.custom_frameworks,
+            // The treasury is not part of the genesis state, the placeholder gives the same root
+            ..GenesisConfig::dev()
+        };
+        println!("{}", genesis_state_root(&genesis_config));
+        return;
+    }
+
     // TODO: think about channel size bound
     let (state_channel, rx) = mpsc::channel(1_000);
 
-    // TODO: genesis should come from a file (path specified by CLI)
-    let genesis_config = GenesisConfig {
-        chain_id: 42069,
-        l2_contract_genesis: Path::new(
-            "src/tests/optimism/packages/contracts-bedrock/deployments/genesis.json",
-        )
-        .into(),
+    let mut genesis_config = GenesisConfig {
+        chain_id: CHAIN_ID,
+        l2_contract_genesis: Path::new(L2_CONTRACT_GENESIS).into(),
+        treasury: args
+            .treasury
+            .map_or(PLACEHOLDER_TREASURY, |treasury| treasury.to_move_address()),
+        dev: args.dev,
+        allow_unprotected_txs: args.allow_unprotected_txs,
+        timestamp: args.genesis_timestamp,
+        evm_native: !args.move_only,
+        disabled_evm_opcodes: args.disabled_evm_opcodes,
+        evm_memory_limit: args.evm_memory_limit,
+        custom_frameworks: args.custom_frameworks,
+        max_module_size: args.max_module_size,
+        max_module_dependencies: args.max_module_dependencies,
+        verify_state_root: !args.accept_genesis_state_root,
         ..Default::default()
     };
 
+    let mut state = InMemoryState::new();
+    let (genesis_changes, table_changes) =
+        genesis::init(&genesis_config, &state).expect("Genesis frameworks should deploy");
+    // The computed root differs from the configured one only if verification is disabled
+    genesis_config.initial_state_root =
+        genesis::apply(genesis_changes, table_changes, &genesis_config, &mut state);
+    let retention = match (args.state_retention.as_str(), args.retained_blocks) {
+        ("pruned", Some(blocks)) => StateRetention::Pruned { blocks },
+        _ => StateRetention::Archive,
+    };
+    let state_query =
+        InMemoryStateQueries::with_retention(genesis_config.initial_state_root, retention)
+            .with_read_retry(ReadRetry {
+                retries: args.db_read_retries,
+            });
+
     let block_hash = MovedBlockHash;
     let genesis_block = create_genesis_block(&block_hash, &genesis_config);
 
@@ -87,13 +359,12 @@ pub async fn run() {
     let head = genesis_block.hash;
     repository.add(&mut block_memory, genesis_block);
 
-    let mut state = InMemoryState::new();
-    let (genesis_changes, table_changes) = genesis::init(&genesis_config, &state);
-    let state_query = InMemoryStateQueries::from_genesis(genesis_config.initial_state_root);
-    genesis::apply(genesis_changes, table_changes, &genesis_config, &mut state);
-
     let base_token = MovedBaseTokenAccounts::new(genesis_config.treasury);
-    let state = moved::state_actor::StateActor::new(
+    let gas_price_bounds = GasPriceBounds {
+        floor: args.gas_price_floor,
+        ceiling: args.gas_price_ceiling,
+    };
+    let mut state = moved::state_actor::StateActor::new(
         rx,
         state,
         head,
@@ -114,42 +385,183 @@ pub async fn run() {
         state_query,
         moved::state_actor::StateActor::on_tx_in_memory(),
         moved::state_actor::StateActor::on_tx_batch_in_memory(),
-    );
+    )
+    .with_gas_price_bounds(gas_price_bounds)
+    .with_transaction_ordering(args.transaction_ordering)
+    .with_max_proof_slots(args.max_proof_slots);
+    if let Some(fee_recipient) = args.fee_recipient {
+        state = state.with_fee_recipient(fee_recipient);
+    }
+    if let Some(deposit_senders) = args.deposit_senders {
+        state = state.with_deposit_senders(deposit_senders);
+    }
+    if let Some(max_deposit_gas) = args.max_deposit_gas {
+        state = state.with_max_deposit_gas(max_deposit_gas);
+    }
+    if args.check_deposits {
+        state = state.with_deposit_checks();
+    }
+    if let Some(max_block_transactions) = args.max_block_transactions {
+        state = state.with_max_block_transactions(max_block_transactions);
+    }
+    if let Some(max_reorg_depth) = args.max_reorg_depth {
+        state = state.with_max_reorg_depth(max_reorg_depth);
+    }
+    if let Some(max_timestamp_drift) = args.max_timestamp_drift {
+        state = state.with_max_timestamp_drift(Duration::from_secs(max_timestamp_drift));
+    }
+    if let Some(build_deadline) = args.build_deadline {
+        state = state.with_build_deadline(Duration::from_millis(build_deadline));
+    }
+    if args.pending_state_calls {
+        state = state.with_pending_state_calls();
+    }
+    if args.parallel_execution {
+        state = state.with_parallel_execution();
+    }
+    if args.receipt_move_events {
+        state = state.with_receipt_move_events();
+    }
+    if let Some(max_estimate_gas) = args.max_estimate_gas {
+        state = state.with_max_estimate_gas(max_estimate_gas);
+    }
+    if let Some(rpc_gas_cap) = args.rpc_gas_cap {
+        state = state.with_rpc_gas_cap(rpc_gas_cap);
+    }
+    if args.no_block_summary {
+        state = state.without_block_summary();
+    }
+
+    let log_config = MirrorLogConfig {
+        mode: args.mirror_log,
+        max_string_len: args.mirror_log_max_string_len,
+    };
+    let timeouts = RequestTimeouts {
+        default: Duration::from_secs(args.request_timeout),
+        debug: Duration::from_secs(args.debug_request_timeout),
+    };
+    let compression_min_size = args.compress_responses.then_some(args.compression_min_size);
+    let read_limit = HeavyReadLimit::new(args.max_heavy_reads);
+    let evm_methods = if args.move_only {
+        MethodName::EVM_SPECIFIC.as_slice()
+    } else {
+        &[]
+    };
+    let debug_methods = if !args.debug_methods {
+        MethodName::DEBUG.as_slice()
+    } else {
+        &[]
+    };
+    let request_config = |disabled_methods: Vec<MethodName>| RequestConfig {
+        disabled_methods: disabled_methods
+            .into_iter()
+            .chain(evm_methods.iter().copied())
+            .chain(debug_methods.iter().copied())
+            .collect(),
+        timeouts,
+        read_limit: read_limit.clone(),
+        min_priority_fee: args.min_priority_fee,
+        check_payload_execution: args.check_payload_execution,
+    };
+    let http_config = Arc::new(MirrorConfig {
+        port: "9545",
+        request: request_config(args.http_disabled_methods),
+        log: log_config,
+        audit_log: false,
+        compression_min_size,
+    });
+    let auth_config = Arc::new(MirrorConfig {
+        port: "9551",
+        request: request_config(args.auth_disabled_methods),
+        log: log_config,
+        audit_log: args.audit_log,
+        compression_min_size,
+    });
 
     let http_state_channel = state_channel.clone();
     let http_server_addr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), 8545));
     let http_route = warp::any()
         .map(move || http_state_channel.clone())
-        .and(extract_request_data_filter())
-        .and_then(|state_channel, path, query, method, headers, body| {
+        .and(extract_request_data(args.max_request_body_size))
+        .and_then(move |state_channel, path, query, method, headers, body| {
             // TODO: Limit engine API access to only authenticated endpoint
-            mirror(state_channel, path, query, method, headers, body, "9545")
-        });
+            mirror(
+                state_channel,
+                path,
+                query,
+                method,
+                headers,
+                body,
+                http_config.clone(),
+                None,
+            )
+        })
+        .recover(recover_body_rejection);
 
     let auth_state_channel = state_channel;
     let auth_server_addr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), 8551));
     let auth_route = warp::any()
         .map(move || auth_state_channel.clone())
-        .and(extract_request_data_filter())
+        .and(extract_request_data(args.max_request_body_size))
         .and(validate_jwt())
-        .and_then(|state_channel, path, query, method, headers, body, _| {
-            mirror(state_channel, path, query, method, headers, body, "9551")
-        });
+        .and_then(
+            move |state_channel, path, query, method, headers, body, token: String| {
+                let issued_at = decode_issued_at(&token);
+                mirror(
+                    state_channel,
+                    path,
+                    query,
+                    method,
+                    headers,
+                    body,
+                    auth_config.clone(),
+                    issued_at,
+                )
+            },
+        )
+        .recover(recover_body_rejection);
+
+    let (_, http_server) =
+        warp::serve(http_route).bind_with_graceful_shutdown(http_server_addr, shutdown_signal());
+    let (_, auth_server) =
+        warp::serve(auth_route).bind_with_graceful_shutdown(auth_server_addr, shutdown_signal());
 
     let (_, _, state_result) = tokio::join!(
-        warp::serve(http_route).run(http_server_addr),
-        warp::serve(auth_route).run(auth_server_addr),
-        state.spawn(),
+        http_server,
+        auth_server,
+        state.spawn_with_shutdown(shutdown_signal()),
     );
     state_result.unwrap();
 }
 
+/// Resolves once the process receives either `SIGINT` or `SIGTERM`.
+async fn shutdown_signal() {
+    let mut terminate =
+        signal::unix::signal(SignalKind::terminate()).expect("SIGTERM handler should install");
+    tokio::select! {
+        _ = signal::ctrl_c() => (),
+        _ = terminate.recv() => (),
+    }
+}
+
+/// Computes the state root resulting from applying the genesis changes to an ephemeral state.
+fn genesis_state_root(genesis_config: &GenesisConfig) -> B256 {
+    let mut state = InMemoryState::new();
+    let (genesis_changes, table_changes) =
+        genesis::init(genesis_config, &state).expect("Genesis frameworks should deploy");
+    state
+        .apply_with_tables(genesis_changes, table_changes)
+        .expect("Genesis changes should be applicable");
+    state.state_root()
+}
+
 fn create_genesis_block(
     block_hash: &impl BlockHash,
     genesis_config: &GenesisConfig,
 ) -> ExtendedBlock {
     let genesis_header = Header {
         state_root: genesis_config.initial_state_root,
+        timestamp: genesis_config.timestamp,
         ..Default::default()
     };
     let hash = block_hash.block_hash(&genesis_header);
@@ -158,19 +570,92 @@ fn create_genesis_block(
     genesis_block.with_hash(hash).with_value(U256::ZERO)
 }
 
+fn parse_method_name(name: &str) -> std::result::Result<MethodName, String> {
+    name.parse().map_err(|e: JsonRpcError| e.message)
+}
+
+fn parse_opcode(opcode: &str) -> std::result::Result<u8, String> {
+    let hex = opcode.strip_prefix("0x").unwrap_or(opcode);
+    u8::from_str_radix(hex, 16).map_err(|e| format!("invalid opcode `{opcode}`: {e}"))
+}
+
+fn parse_transaction_ordering(name: &str) -> std::result::Result<TransactionOrdering, String> {
+    match name {
+        "tip" => Ok(TransactionOrdering::TipDescending),
+        "received-time" => Ok(TransactionOrdering::ReceivedTime),
+        _ => Err(format!(
+            "unknown transaction ordering `{name}`, expected `tip` or `received-time`"
+        )),
+    }
+}
+
+/// Why [`limit_body_size`] rejected a request, replied to by [`recover_body_rejection`].
+#[derive(Debug)]
+enum BodyRejection {
+    TooLarge,
+    ReadFailed,
+}
+
+impl warp::reject::Reject for BodyRejection {}
+
+/// Reads the request body, rejecting bodies larger than `max_bytes`. Bodies with a content length
+/// are rejected before they are read, streamed (chunked) bodies as soon as they exceed the limit.
+pub fn limit_body_size(
+    max_bytes: u64,
+) -> impl Filter<Extract = (Bytes,), Error = Rejection> + Clone {
+    warp::header::optional::<u64>("content-length")
+        .and(warp::body::stream())
+        .and_then(move |content_length: Option<u64>, body| async move {
+            if content_length.is_some_and(|length| length > max_bytes) {
+                return Err(warp::reject::custom(BodyRejection::TooLarge));
+            }
+            let mut body = std::pin::pin!(body);
+            let mut bytes = Vec::new();
+            while let Some(chunk) = body.next().await {
+                let mut chunk =
+                    chunk.map_err(|_| warp::reject::custom(BodyRejection::ReadFailed))?;
+                if (bytes.len() + chunk.remaining()) as u64 > max_bytes {
+                    return Err(warp::reject::custom(BodyRejection::TooLarge));
+                }
+                bytes.extend_from_slice(&chunk.copy_to_bytes(chunk.remaining()));
+            }
+            Ok::<_, Rejection>(Bytes::from(bytes))
+        })
+}
+
+/// Replies to requests rejected by [`limit_body_size`] with 413 or 400.
+pub async fn recover_body_rejection(
+    rejection: Rejection,
+) -> std::result::Result<impl warp::Reply, Rejection> {
+    let status = match rejection.find::<BodyRejection>() {
+        Some(BodyRejection::TooLarge) => StatusCode::PAYLOAD_TOO_LARGE,
+        Some(BodyRejection::ReadFailed) => StatusCode::BAD_REQUEST,
+        None => return Err(rejection),
+    };
+    Ok(warp::reply::with_status(
+        status.canonical_reason().unwrap_or_default(),
+        status,
+    ))
+}
+
+/// Extracts the request like [`warp_reverse_proxy::extract_request_data_filter`] with the body
+/// read by [`limit_body_size`].
+fn extract_request_data(
+    max_body_size: u64,
+) -> impl Filter<Extract = (FullPath, QueryParameters, Method, Headers, Bytes), Error = Rejection> + Clone
+{
+    warp::path::full()
+        .and(query_params_filter())
+        .and(warp::method())
+        .and(warp::header::headers_cloned())
+        .and(limit_body_size(max_body_size))
+}
+
 pub fn validate_jwt() -> impl Filter<Extract = (String,), Error = Rejection> + Clone {
     warp::header::<String>("authorization").and_then(|token: String| async move {
         // Token is embedded as a string in the form of `Bearer the.actual.token`
         let token = token.trim_start_matches("Bearer ").to_string();
-        let mut validation = Validation::default();
-        // OP node only sends `issued at` claims in the JWT token
-        validation.set_required_spec_claims(&["iat"]);
-        let decoded = jsonwebtoken::decode::<Claims>(
-            &token,
-            &DecodingKey::from_secret(&JWTSECRET),
-            &validation,
-        );
-        let iat = decoded.map_err(|_| warp::reject::reject())?.claims.iat;
+        let iat = decode_issued_at(&token).ok_or_else(warp::reject::reject)?;
         let now = SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)
             .expect("Current system time should be available")
@@ -182,6 +667,21 @@ pub fn validate_jwt() -> impl Filter<Extract = (String,), Error = Rejection> + C
     })
 }
 
+/// Decodes the `issued at` claim of `token`, or `None` if the token is not signed by the secret.
+fn decode_issued_at(token: &str) -> Option<u64> {
+    let mut validation = Validation::default();
+    // OP node only sends `issued at` claims in the JWT token
+    validation.set_required_spec_claims(&["iat"]);
+    let decoded =
+        jsonwebtoken::decode::<Claims>(token, &DecodingKey::from_secret(&JWTSECRET), &validation);
+    decoded.ok().map(|decoded| decoded.claims.iat)
+}
+
+/// Forwards the request to op-geth and compares the response with the one of op-move.
+///
+/// The call is written to the audit log if enabled in the `config`, attributed to the token
+/// `issued_at`.
+#[allow(clippy::too_many_arguments)]
 async fn mirror(
     state_channel: mpsc::Sender<StateMessage>,
     path: FullPath,
@@ -189,17 +689,22 @@ async fn mirror(
     method: Method,
     headers: Headers,
     body: Bytes,
-    port: &str,
+    config: Arc<MirrorConfig>,
+    issued_at: Option<u64>,
 ) -> std::result::Result<warp::reply::Response, Rejection> {
     use std::result::Result;
 
-    let is_zipped = headers
+    let accept_encoding = headers
         .get("accept-encoding")
-        .map(|x| x.to_str().unwrap().contains("gzip"))
+        .and_then(|x| x.to_str().ok())
+        .map(str::to_owned);
+    let is_zipped = accept_encoding
+        .as_deref()
+        .map(|x| x.contains("gzip"))
         .unwrap_or(false);
     let request: Result<serde_json::Value, _> = serde_json::from_slice(&body);
     let (geth_response_parts, geth_response_bytes, parsed_geth_response) =
-        match proxy(path, query, method, headers.clone(), body, port).await {
+        match proxy(path, query, method, headers.clone(), body, config.port).await {
             Ok(response) => {
                 let (parts, body) = response.into_parts();
                 let raw_bytes = hyper::body::to_bytes(body)
@@ -236,14 +741,21 @@ async fn mirror(
 
     let request = request.expect("geth responded, so body must have been JSON");
     let op_move_response =
-        moved_engine_api::request::handle(request.clone(), state_channel.clone()).await;
+        moved_engine_api::request::handle(request.clone(), state_channel.clone(), &config.request)
+            .await;
     let log = MirrorLog {
         request: &request,
         geth_response: &parsed_geth_response,
         op_move_response: &op_move_response,
-        port,
+        port: config.port,
     };
-    println!("{}", serde_json::to_string_pretty(&log).unwrap());
+    if let Some(log) = log.render(&config.log) {
+        println!("{log}");
+    }
+    if let Some(issued_at) = issued_at.filter(|_| config.audit_log) {
+        let audit_log = AuditLog::new(&request, &op_move_response, issued_at);
+        println!("{}", audit_log.render());
+    }
 
     // TODO: this is a hack because we currently can't compute the genesis
     // hash expected by op-node.
@@ -258,8 +770,70 @@ async fn mirror(
         return Ok(warp::reply::Response::from_parts(geth_response_parts, body));
     }
 
-    let body = hyper::Body::from(serde_json::to_vec(&op_move_response).unwrap());
-    Ok(warp::reply::Response::new(body))
+    let body = serde_json::to_vec(&op_move_response).unwrap();
+    match config.compression_min_size {
+        Some(min_size) => Ok(compress_response(
+            body,
+            accept_encoding.as_deref(),
+            min_size,
+        )),
+        None => Ok(warp::reply::Response::new(hyper::Body::from(body))),
+    }
+}
+
+/// Encodes `body` with gzip or deflate if the client accepts either of them and the body is at
+/// least `min_size` bytes long. Gzip is preferred when both are accepted.
+pub fn compress_response(
+    body: Vec<u8>,
+    accept_encoding: Option<&str>,
+    min_size: usize,
+) -> warp::reply::Response {
+    let accepted: Vec<_> = accept_encoding
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|encoding| {
+            let mut parts = encoding.split(';').map(str::trim);
+            let name = parts.next()?;
+            let rejected = parts.any(|param| {
+                param
+                    .strip_prefix("q=")
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .is_some_and(|q| q == 0.0)
+            });
+            (!rejected).then_some(name)
+        })
+        .collect();
+
+    let compressed = if body.len() < min_size {
+        None
+    } else if accepted.contains(&"gzip") {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(&body)
+            .and_then(|()| encoder.finish())
+            .ok()
+            .map(|bytes| ("gzip", bytes))
+    } else if accepted.contains(&"deflate") {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(&body)
+            .and_then(|()| encoder.finish())
+            .ok()
+            .map(|bytes| ("deflate", bytes))
+    } else {
+        None
+    };
+
+    match compressed {
+        Some((encoding, bytes)) => {
+            let mut response = warp::reply::Response::new(hyper::Body::from(bytes));
+            response
+                .headers_mut()
+                .insert(CONTENT_ENCODING, HeaderValue::from_static(encoding));
+            response
+        }
+        None => warp::reply::Response::new(hyper::Body::from(body)),
+    }
 }
 
 async fn proxy(
@@ -1,4 +1,4 @@
-use {moved_engine_api::jsonrpc::JsonRpcResponse, serde::Serialize};
+use {moved_engine_api::jsonrpc::JsonRpcResponse, serde::Serialize, serde_json::Value};
 
 #[derive(Debug, Serialize)]
 pub struct MirrorLog<'a> {
@@ -7,3 +7,50 @@ pub struct MirrorLog<'a> {
     pub op_move_response: &'a JsonRpcResponse,
     pub port: &'a str,
 }
+
+/// Selects which mirrored requests are logged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum MirrorLogMode {
+    #[default]
+    All,
+    /// Only requests where op-move responded with an error
+    Errors,
+    Off,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MirrorLogConfig {
+    pub mode: MirrorLogMode,
+    /// Strings longer than this many characters, like calldata or raw transactions, are truncated.
+    pub max_string_len: Option<usize>,
+}
+
+impl MirrorLog<'_> {
+    /// Serializes the log entry according to `config`, or returns `None` if it should be skipped.
+    pub fn render(&self, config: &MirrorLogConfig) -> Option<String> {
+        match config.mode {
+            MirrorLogMode::Off => return None,
+            MirrorLogMode::Errors if self.op_move_response.error.is_none() => return None,
+            _ => (),
+        }
+
+        let mut log = serde_json::to_value(self).expect("Mirror log must serialize");
+        if let Some(max_len) = config.max_string_len {
+            redact(&mut log, max_len);
+        }
+        Some(serde_json::to_string_pretty(&log).expect("Mirror log must serialize"))
+    }
+}
+
+fn redact(value: &mut Value, max_len: usize) {
+    match value {
+        Value::String(s) => {
+            if let Some((end, _)) = s.char_indices().nth(max_len) {
+                *s = format!("{}...<{} bytes redacted>", &s[..end], s.len() - end);
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(|v| redact(v, max_len)),
+        Value::Object(map) => map.values_mut().for_each(|v| redact(v, max_len)),
+        _ => (),
+    }
+}
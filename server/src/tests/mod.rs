@@ -1,15 +1,36 @@
 mod integration;
 
 use {
-    crate::{validate_jwt, Claims},
+    crate::{
+        audit::{AuditLog, AuditOutcome},
+        compress_response, create_genesis_block, genesis_state_root, limit_body_size,
+        mirror::{MirrorLog, MirrorLogConfig, MirrorLogMode},
+        recover_body_rejection, validate_jwt, Claims,
+    },
     aptos_types::transaction::{EntryFunction, TransactionPayload},
+    flate2::read::GzDecoder,
     jsonwebtoken::{EncodingKey, Header},
     move_core_types::{
         account_address::AccountAddress,
         ident_str,
         language_storage::{ModuleId, StructTag, TypeTag},
     },
-    std::time::SystemTime,
+    moved::{
+        block::MovedBlockHash,
+        genesis::{self, config::GenesisConfig},
+        storage::InMemoryState,
+    },
+    moved_engine_api::jsonrpc::JsonRpcResponse,
+    std::{io::Read, net::SocketAddr, time::SystemTime},
+    tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpStream,
+    },
+    warp::{
+        http::{header::CONTENT_ENCODING, StatusCode},
+        hyper::body::Bytes,
+        Filter,
+    },
 };
 
 #[tokio::test]
@@ -30,6 +51,85 @@ async fn test_authorized_request() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_oversized_request_body_is_rejected() {
+    let filter = limit_body_size(16)
+        .map(|_: Bytes| warp::reply())
+        .recover(recover_body_rejection);
+
+    let res = warp::test::request()
+        .method("POST")
+        .body([0; 16])
+        .reply(&filter)
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = warp::test::request()
+        .method("POST")
+        .body([0; 17])
+        .reply(&filter)
+        .await;
+    assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+/// Sends a POST request with a chunked body made of `chunks`, returning the raw response.
+async fn post_chunked(addr: SocketAddr, chunks: &[&[u8]]) -> String {
+    let mut request = b"POST / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\
+        Transfer-Encoding: chunked\r\n\r\n"
+        .to_vec();
+    for chunk in chunks {
+        request.extend_from_slice(format!("{:x}\r\n", chunk.len()).as_bytes());
+        request.extend_from_slice(chunk);
+        request.extend_from_slice(b"\r\n");
+    }
+    request.extend_from_slice(b"0\r\n\r\n");
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(&request).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
+
+#[tokio::test]
+async fn test_chunked_request_body_is_limited() {
+    let filter = limit_body_size(16)
+        .map(|body: Bytes| format!("{} bytes", body.len()))
+        .recover(recover_body_rejection);
+    let (addr, server) = warp::serve(filter).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    let response = post_chunked(addr, &[&[0; 10], &[0; 6]]).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    assert!(response.ends_with("16 bytes"), "{response}");
+
+    let response = post_chunked(addr, &[&[0; 10], &[0; 7]]).await;
+    assert!(response.starts_with("HTTP/1.1 413"), "{response}");
+}
+
+#[tokio::test]
+async fn test_large_responses_are_compressed_when_accepted() {
+    let body = serde_json::to_vec(&vec!["0x00"; 1_000]).unwrap();
+
+    let res = compress_response(body.clone(), Some("deflate, gzip;q=1.0"), 1024);
+    assert_eq!(res.headers()[CONTENT_ENCODING], "gzip");
+    let compressed = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert!(compressed.len() < body.len());
+    let mut decompressed = Vec::new();
+    GzDecoder::new(compressed.as_ref())
+        .read_to_end(&mut decompressed)
+        .unwrap();
+    assert_eq!(decompressed, body);
+
+    let res = compress_response(body.clone(), None, 1024);
+    assert!(res.headers().get(CONTENT_ENCODING).is_none());
+    let uncompressed = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert_eq!(uncompressed, body);
+
+    let res = compress_response(body[..100].to_vec(), Some("gzip"), 1024);
+    assert!(res.headers().get(CONTENT_ENCODING).is_none());
+}
+
 #[tokio::test]
 async fn test_unauthorized_requests() -> anyhow::Result<()> {
     std::env::set_var("JWT_SECRET", "00");
@@ -80,3 +180,118 @@ async fn test_entry_function_payload() -> anyhow::Result<()> {
     assert_eq!(serialized_payload[0], 2); // Starting with 2 indicates an entry function
     Ok(())
 }
+
+#[test]
+fn test_genesis_state_root_matches_validated_root() {
+    let genesis_config = GenesisConfig::dev();
+    let state_root = genesis_state_root(&genesis_config);
+
+    assert_eq!(state_root, genesis_config.initial_state_root);
+
+    // The printed root passes the state root verification done on startup
+    let config = GenesisConfig {
+        initial_state_root: state_root,
+        ..genesis_config
+    };
+    let mut state = InMemoryState::new();
+    let (changes, table_changes) = genesis::init(&config, &state).unwrap();
+    let validated_root = genesis::apply(changes, table_changes, &config, &mut state);
+
+    assert_eq!(state_root, validated_root);
+}
+
+#[test]
+fn test_genesis_block_has_configured_timestamp() {
+    let default_block = create_genesis_block(&MovedBlockHash, &GenesisConfig::dev());
+    assert_eq!(default_block.block.header.timestamp, 0);
+
+    let config = GenesisConfig {
+        timestamp: 0x6660737b,
+        ..GenesisConfig::dev()
+    };
+    let block = create_genesis_block(&MovedBlockHash, &config);
+
+    assert_eq!(block.block.header.timestamp, 0x6660737b);
+    assert_ne!(block.hash, default_block.hash);
+}
+
+#[test]
+fn test_mirror_log_truncates_long_strings() {
+    let input = format!("0x{}", "ab".repeat(1000));
+    let request = serde_json::json!({
+        "method": "eth_call",
+        "params": [{ "input": input }],
+    });
+    let response = JsonRpcResponse {
+        id: serde_json::Value::from(1),
+        jsonrpc: serde_json::Value::from("2.0"),
+        result: Some(serde_json::Value::from("0x")),
+        error: None,
+    };
+    let log = MirrorLog {
+        request: &request,
+        geth_response: &serde_json::Value::Null,
+        op_move_response: &response,
+        port: "9545",
+    };
+
+    let config = MirrorLogConfig {
+        mode: MirrorLogMode::All,
+        max_string_len: Some(64),
+    };
+    let rendered = log.render(&config).unwrap();
+    assert!(!rendered.contains(&input));
+    assert!(rendered.contains(&input[..64]));
+    assert!(rendered.contains("eth_call"));
+
+    let config = MirrorLogConfig {
+        mode: MirrorLogMode::Errors,
+        max_string_len: None,
+    };
+    assert!(log.render(&config).is_none());
+}
+
+#[test]
+fn test_auth_call_is_audited() {
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 7,
+        "method": "engine_forkchoiceUpdatedV3",
+        "params": [],
+    });
+    let response = JsonRpcResponse {
+        id: serde_json::Value::from(7),
+        jsonrpc: serde_json::Value::from("2.0"),
+        result: None,
+        error: Some(moved_engine_api::jsonrpc::JsonRpcError::without_data(
+            -32602,
+            "Not enough params",
+        )),
+    };
+
+    let log = AuditLog::new(&request, &response, 1_700_000_000);
+    assert_eq!(log.method, "engine_forkchoiceUpdatedV3");
+    assert_eq!(log.request_id, 7);
+    assert_eq!(
+        log.outcome,
+        AuditOutcome::Error {
+            code: -32602,
+            message: "Not enough params"
+        }
+    );
+
+    let rendered = log.render();
+    assert!(rendered.starts_with("AUDIT: {"));
+    assert!(!rendered.contains('\n'));
+    let entry: serde_json::Value =
+        serde_json::from_str(rendered.trim_start_matches("AUDIT: ")).unwrap();
+    assert_eq!(
+        entry,
+        serde_json::json!({
+            "method": "engine_forkchoiceUpdatedV3",
+            "request_id": 7,
+            "issued_at": 1_700_000_000,
+            "outcome": {"status": "error", "code": -32602, "message": "Not enough params"},
+        })
+    );
+}